@@ -0,0 +1,336 @@
+use crate::proc::generator::Profile;
+use crate::proc::workload::{Format, Policy};
+use crate::sched::gantt::Rows;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+// Codes the simulator exits with: the run went through, it failed or left tasks deadlocked or
+// stalled, the command line is invalid, the workload or trace is invalid, or a file could not be
+// read or written
+pub const EXIT_OK: u8 = 0;
+pub const EXIT_FAILED: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_INVALID: u8 = 3;
+pub const EXIT_IO: u8 = 4;
+
+// Workload run when none is given
+pub const DEFAULT_WORKLOAD: &str = "tasks.txt";
+
+pub const USAGE: &str = "\
+Usage: completely-fair-scheduler [OPTIONS] [COMMAND] [ARGS]
+
+Commands:
+  run [WORKLOAD]           Run a workload, printing the task run on every tick and a report (the default)
+  report [WORKLOAD]        Run a workload, printing only its report
+  replay TRACE             Replay the threads of an ftrace, trace-cmd or perf sched dump
+  compare WORKLOAD...      Run workloads and compare their metrics side by side
+  validate WORKLOAD...     Check workloads without running them
+  generate PROFILE         Generate a workload: batch, interactive, mixed, web or tiny
+  help                     Print this message
+
+Options of every command:
+  --seed N                 Seed of every random draw, 0 by default
+  -q, --quiet              Print nothing but errors
+  -o, --output PATH        Write what the command prints to a file instead
+  -h, --help               Print this message
+  -V, --version            Print the version
+
+Options of run, report, replay and compare:
+  --workload PATH          Workload to run, tasks.txt by default
+  --policy POLICY          Give every task the policy normal, batch or idle
+  --timer-slack N          Ticks timers may fire late, overriding the workload's
+  --priority-inheritance   Let mutex holders inherit the weight of their waiters
+  --format FORMAT          Report as text, json or csv (the statistics of every task)
+
+Outputs of run, report and replay:
+  --chrome-trace PATH      Trace in the Chrome Trace Event Format
+  --ftrace PATH            Sched events in ftrace text format
+  --events-jsonl PATH      Every event as a line of JSON
+  --series-csv PATH        Run queue length, min_vruntime and utilisation of every tick
+  --tasks-csv PATH         Statistics of every task as CSV
+  --tasks-json PATH        Statistics of every task as JSON
+  --gantt PATH             Gantt chart as SVG
+  --gantt-ascii            Print a Gantt chart
+  --gantt-rows ROWS        Rows of the Gantt charts: tasks (the default) or cpus
+
+Options of replay:
+  --tick-ns N              Nanoseconds of the trace per tick, 1000 by default
+
+Options of generate:
+  --tasks N                Number of tasks, or of waves of requests for web
+  --utilisation U          Share of the CPU the tasks keep busy, such as 0.7
+  --cpu-time LOW..HIGH     Range of the cpu_time of the tasks
+  --nice LOW..HIGH         Range of the nice values of the tasks
+  --format FORMAT          Workload format: toml (the default), json or legacy
+
+Exit codes: 0 success, 1 failed run or tasks left deadlocked or stalled, 2 invalid command line,
+3 invalid workload or trace, 4 unreadable or unwritable file.
+";
+
+// Error returned when the command line is invalid
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+// Formats a report or a comparison is written in
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReportFormat {
+    // As printed at the end of a run
+    #[default]
+    Text,
+    // The whole report serialised
+    Json,
+    // The statistics of every task, or the metrics of every workload compared, as CSV
+    Csv,
+}
+
+// Files written and charts printed about a run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outputs {
+    pub chrome_trace: Option<PathBuf>,
+    pub ftrace: Option<PathBuf>,
+    pub events_jsonl: Option<PathBuf>,
+    pub series_csv: Option<PathBuf>,
+    pub tasks_csv: Option<PathBuf>,
+    pub tasks_json: Option<PathBuf>,
+    pub gantt: Option<PathBuf>,
+    pub gantt_ascii: bool,
+    // Rows of the Gantt charts, a row per task if none
+    pub gantt_rows: Option<Rows>,
+}
+
+// Settings of the scheduler for a run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    // Policy given to every task, if any
+    pub policy: Option<Policy>,
+    // Timer slack replacing the workload's, if any
+    pub timer_slack: Option<u128>,
+    pub priority_inheritance: bool,
+    pub format: ReportFormat,
+    pub outputs: Outputs,
+}
+
+// Settings of the workload generator
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerateOptions {
+    pub profile: Profile,
+    pub tasks: Option<usize>,
+    pub utilisation: Option<f64>,
+    pub cpu_time: Option<(u64, u64)>,
+    pub nice: Option<(i8, i8)>,
+    // Format of the workload, following the extension of the output path or TOML if none
+    pub format: Option<Format>,
+}
+
+// What the simulator is asked to do
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    // Runs a workload; the per-tick lines are only printed when `trace` is set
+    Run { workload: PathBuf, trace: bool, options: RunOptions },
+    // Replays a kernel trace with the given nanoseconds per tick
+    Replay { trace: PathBuf, tick: u128, options: RunOptions },
+    Compare { workloads: Vec<PathBuf>, options: RunOptions },
+    Validate { workloads: Vec<PathBuf> },
+    Generate(GenerateOptions),
+    Help,
+    Version,
+}
+
+// A parsed command line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    // Seed of the scheduler and the generator, 0 if none
+    pub seed: Option<u64>,
+    pub quiet: bool,
+    // File what is printed is written to instead, if any
+    pub output: Option<PathBuf>,
+}
+
+// Names of the commands, the first being run when none is given
+const COMMANDS: [&str; 7] = ["run", "report", "replay", "compare", "validate", "generate", "help"];
+
+// Options followed by a value
+const VALUED: [&str; 20] = [
+    "--seed",
+    "--output",
+    "-o",
+    "--workload",
+    "--policy",
+    "--timer-slack",
+    "--format",
+    "--chrome-trace",
+    "--ftrace",
+    "--events-jsonl",
+    "--series-csv",
+    "--tasks-csv",
+    "--tasks-json",
+    "--gantt",
+    "--gantt-rows",
+    "--tick-ns",
+    "--tasks",
+    "--utilisation",
+    "--cpu-time",
+    "--nice",
+];
+
+// Returns a value parsed, or an error naming the option it follows
+fn value<T: FromStr>(flag: &str, text: &str) -> Result<T, UsageError> {
+    text.parse().map_err(|_| UsageError(format!("invalid value `{}` after {}", text, flag)))
+}
+
+// Parses a range written "low..high", or a single value
+fn range<T: FromStr + Copy>(flag: &str, text: &str) -> Result<(T, T), UsageError> {
+    match text.split_once("..") {
+        Some((low, high)) => Ok((value(flag, low)?, value(flag, high)?)),
+        None => value(flag, text).map(|value| (value, value)),
+    }
+}
+
+impl Cli {
+    // Parses the arguments following the program name. Options may come before or after the
+    // command, which is `run` when none is given.
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, UsageError> {
+        // Split the arguments into options, with their values, and the other arguments
+        let mut options: Vec<(&str, Option<&str>)> = Vec::new();
+        let mut positional: Vec<&str> = Vec::new();
+        let mut args = args.iter().map(AsRef::as_ref);
+        while let Some(arg) = args.next() {
+            if VALUED.contains(&arg) {
+                let value = args.next().ok_or(UsageError(format!("missing value after {}", arg)))?;
+                options.push((arg, Some(value)));
+            } else if arg.starts_with('-') && arg.len() > 1 {
+                options.push((arg, None));
+            } else {
+                positional.push(arg);
+            }
+        }
+        let name = match positional.first() {
+            Some(first) if COMMANDS.contains(first) => positional.remove(0),
+            _ => "run",
+        };
+
+        let mut cli = Cli { command: Command::Help, seed: None, quiet: false, output: None };
+        let mut run = RunOptions::default();
+        let mut workload: Option<&str> = None;
+        let mut tick = 1000;
+        let mut generate = GenerateOptions { profile: Profile::Mixed, tasks: None, utilisation: None, cpu_time: None, nice: None, format: None };
+        let mut format: Option<&str> = None;
+        let runs = matches!(name, "run" | "report" | "replay" | "compare");
+        let outputs = matches!(name, "run" | "report" | "replay");
+        for (flag, text) in options {
+            let text = text.unwrap_or_default();
+            match flag {
+                "--seed" => cli.seed = Some(value(flag, text)?),
+                "--quiet" | "-q" => cli.quiet = true,
+                "--output" | "-o" => cli.output = Some(PathBuf::from(text)),
+                "--help" | "-h" => return Ok(Cli { command: Command::Help, ..cli }),
+                "--version" | "-V" => return Ok(Cli { command: Command::Version, ..cli }),
+                "--format" if runs || name == "generate" => format = Some(text),
+                "--workload" if matches!(name, "run" | "report") => workload = Some(text),
+                "--policy" if runs => {
+                    run.policy = Some(match text {
+                        "normal" => Policy::Normal,
+                        "batch" => Policy::Batch,
+                        "idle" => Policy::Idle,
+                        _ => return Err(UsageError(format!("expected normal, batch or idle after --policy, found `{}`", text))),
+                    })
+                }
+                "--timer-slack" if runs => run.timer_slack = Some(value(flag, text)?),
+                "--priority-inheritance" if runs => run.priority_inheritance = true,
+                "--chrome-trace" if outputs => run.outputs.chrome_trace = Some(PathBuf::from(text)),
+                "--ftrace" if outputs => run.outputs.ftrace = Some(PathBuf::from(text)),
+                "--events-jsonl" if outputs => run.outputs.events_jsonl = Some(PathBuf::from(text)),
+                "--series-csv" if outputs => run.outputs.series_csv = Some(PathBuf::from(text)),
+                "--tasks-csv" if outputs => run.outputs.tasks_csv = Some(PathBuf::from(text)),
+                "--tasks-json" if outputs => run.outputs.tasks_json = Some(PathBuf::from(text)),
+                "--gantt" if outputs => run.outputs.gantt = Some(PathBuf::from(text)),
+                "--gantt-ascii" if outputs => run.outputs.gantt_ascii = true,
+                "--gantt-rows" if outputs => {
+                    run.outputs.gantt_rows = Some(match text {
+                        "tasks" => Rows::Tasks,
+                        "cpus" => Rows::Cpus,
+                        _ => return Err(UsageError(format!("expected tasks or cpus after --gantt-rows, found `{}`", text))),
+                    })
+                }
+                "--tick-ns" if name == "replay" => tick = value(flag, text)?,
+                "--tasks" if name == "generate" => generate.tasks = Some(value(flag, text)?),
+                "--utilisation" if name == "generate" => {
+                    generate.utilisation = Some(value(flag, text).and_then(|utilisation: f64| {
+                        if utilisation > 0.0 {
+                            Ok(utilisation)
+                        } else {
+                            Err(UsageError(String::from("--utilisation must be positive")))
+                        }
+                    })?)
+                }
+                "--cpu-time" if name == "generate" => generate.cpu_time = Some(range(flag, text)?),
+                "--nice" if name == "generate" => generate.nice = Some(range(flag, text)?),
+                _ => return Err(UsageError(format!("unknown option {} for {}", flag, name))),
+            }
+        }
+
+        // Formats mean a report format to runs and a workload format to the generator
+        if let Some(format) = format {
+            if name == "generate" {
+                generate.format = Some(match format {
+                    "toml" => Format::Toml,
+                    "json" => Format::Json,
+                    "legacy" => Format::Legacy,
+                    _ => return Err(UsageError(format!("expected toml, json or legacy after --format, found `{}`", format))),
+                });
+            } else {
+                run.format = match format {
+                    "text" => ReportFormat::Text,
+                    "json" => ReportFormat::Json,
+                    "csv" => ReportFormat::Csv,
+                    _ => return Err(UsageError(format!("expected text, json or csv after --format, found `{}`", format))),
+                };
+            }
+        }
+
+        // Commands taking a single argument reject others
+        let single = |positional: &[&str], what: &str| match positional {
+            [] => Ok(None),
+            [arg] => Ok(Some(PathBuf::from(arg))),
+            [_, extra, ..] => Err(UsageError(format!("unexpected argument `{}` after the {}", extra, what))),
+        };
+        cli.command = match name {
+            "run" | "report" => {
+                let path = match (workload, single(&positional, "workload")?) {
+                    (Some(_), Some(path)) => return Err(UsageError(format!("the workload is given twice, as {} and with --workload", path.display()))),
+                    (Some(path), None) => PathBuf::from(path),
+                    (None, path) => path.unwrap_or(PathBuf::from(DEFAULT_WORKLOAD)),
+                };
+                Command::Run { workload: path, trace: name == "run", options: run }
+            }
+            "replay" => {
+                let trace = single(&positional, "trace")?.ok_or(UsageError(String::from("missing trace to replay")))?;
+                Command::Replay { trace, tick, options: run }
+            }
+            "compare" if positional.is_empty() => return Err(UsageError(String::from("missing workloads to compare"))),
+            "compare" => Command::Compare { workloads: positional.iter().map(PathBuf::from).collect(), options: run },
+            "validate" if positional.is_empty() => return Err(UsageError(String::from("missing workloads to validate"))),
+            "validate" => Command::Validate { workloads: positional.iter().map(PathBuf::from).collect() },
+            "generate" => {
+                let profile = single(&positional, "profile")?.ok_or(UsageError(String::from(
+                    "missing profile to generate, expected batch, interactive, mixed, web or tiny",
+                )))?;
+                generate.profile = profile.to_string_lossy().parse().map_err(|e| UsageError(format!("{}", e)))?;
+                Command::Generate(generate)
+            }
+            _ => Command::Help,
+        };
+        Ok(cli)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::io::elevator::UnknownSchedulerError;
+use crate::proc::arrival::ParseArrivalError;
+use crate::proc::dist::ParseDistError;
+use crate::proc::graph::GraphError;
+use crate::proc::replay::ParseTraceError;
+use crate::proc::script::ParseScriptError;
+use crate::proc::task::TransitionError;
+use crate::sync::table::UnlockError;
+use crate::proc::workload::WorkloadError;
+use crate::sched::trace::SinkError;
+use std::error;
+use std::fmt;
+
+// Every error the library can return, so applications embedding the simulator can handle them in one place
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    // A script could not be parsed
+    Script(ParseScriptError),
+    // A burst length distribution could not be parsed
+    Dist(ParseDistError),
+    // An arrival could not be parsed
+    Arrival(ParseArrivalError),
+    // A kernel trace could not be replayed
+    Trace(ParseTraceError),
+    // A workload file could not be read
+    Workload(WorkloadError),
+    // No I/O scheduler has the requested name
+    UnknownScheduler(UnknownSchedulerError),
+    // The dependencies of the workload do not form a DAG
+    Graph(GraphError),
+    // A trace sink could not write out the events of the run
+    Sink(SinkError),
+    // A task was asked to make a transition its state does not allow
+    Transition(TransitionError),
+    // A task unlocked a mutex it does not hold
+    Unlock(UnlockError),
+    // A task has a weight of 0, which vruntime cannot be divided by
+    ZeroWeight(u16),
+    // Every id a task can have is taken, so no more tasks can be born or forked
+    PidsExhausted,
+    // A task was taken from an empty run queue
+    EmptyRunQueue,
+    // One of the threads of the simulation panicked
+    ThreadPanicked(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Script(e) => write!(f, "{}", e),
+            Error::Dist(e) => write!(f, "{}", e),
+            Error::Arrival(e) => write!(f, "{}", e),
+            Error::Trace(e) => write!(f, "{}", e),
+            Error::Workload(e) => write!(f, "{}", e),
+            Error::UnknownScheduler(e) => write!(f, "{}", e),
+            Error::Graph(e) => write!(f, "invalid task dependencies: {}", e),
+            Error::Sink(e) => write!(f, "{}", e),
+            Error::Transition(e) => write!(f, "{}", e),
+            Error::Unlock(e) => write!(f, "{}", e),
+            Error::ZeroWeight(id) => write!(f, "task {} has a weight of 0", id),
+            Error::PidsExhausted => write!(f, "every task id up to {} is taken", u16::MAX),
+            Error::EmptyRunQueue => write!(f, "the run queue is empty"),
+            Error::ThreadPanicked(name) => write!(f, "the {} thread panicked", name),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Script(e) => Some(e),
+            Error::Dist(e) => Some(e),
+            Error::Arrival(e) => Some(e),
+            Error::Trace(e) => Some(e),
+            Error::Workload(e) => Some(e),
+            Error::UnknownScheduler(e) => Some(e),
+            Error::Graph(e) => Some(e),
+            Error::Sink(e) => Some(e),
+            Error::Transition(e) => Some(e),
+            Error::Unlock(e) => Some(e),
+            Error::ZeroWeight(_) | Error::PidsExhausted | Error::EmptyRunQueue | Error::ThreadPanicked(_) => None,
+        }
+    }
+}
+
+impl From<ParseScriptError> for Error {
+    fn from(e: ParseScriptError) -> Self {
+        Error::Script(e)
+    }
+}
+
+impl From<ParseDistError> for Error {
+    fn from(e: ParseDistError) -> Self {
+        Error::Dist(e)
+    }
+}
+
+impl From<ParseArrivalError> for Error {
+    fn from(e: ParseArrivalError) -> Self {
+        Error::Arrival(e)
+    }
+}
+
+impl From<ParseTraceError> for Error {
+    fn from(e: ParseTraceError) -> Self {
+        Error::Trace(e)
+    }
+}
+
+impl From<WorkloadError> for Error {
+    fn from(e: WorkloadError) -> Self {
+        Error::Workload(e)
+    }
+}
+
+impl From<UnknownSchedulerError> for Error {
+    fn from(e: UnknownSchedulerError) -> Self {
+        Error::UnknownScheduler(e)
+    }
+}
+
+impl From<GraphError> for Error {
+    fn from(e: GraphError) -> Self {
+        Error::Graph(e)
+    }
+}
+
+impl From<TransitionError> for Error {
+    fn from(e: TransitionError) -> Self {
+        Error::Transition(e)
+    }
+}
+
+impl From<UnlockError> for Error {
+    fn from(e: UnlockError) -> Self {
+        Error::Unlock(e)
+    }
+}
+
+impl From<SinkError> for Error {
+    fn from(e: SinkError) -> Self {
+        Error::Sink(e)
+    }
+}
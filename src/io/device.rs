@@ -0,0 +1,189 @@
+use super::elevator::{IoKind, IoScheduler, Noop, Request};
+use crate::proc::dist::Dist;
+use crate::proc::script::Phase;
+use crate::proc::task::{Task, TransitionError};
+use serde::Serialize;
+
+// Name of the device serving I/O bursts that do not name one
+pub const DEFAULT_DEVICE: &str = "default";
+
+// A simulated device serving the I/O bursts of tasks, several at once up to its queue depth
+#[derive(Clone)]
+pub struct Device {
+    // Name I/O phases refer to the device by
+    name: String,
+    // Number of I/Os served at once, or None for no limit
+    depth: Option<usize>,
+    // Service time of every I/O, overriding the burst length drawn by the task when set
+    service: Option<Dist>,
+    // I/O scheduler ordering the I/Os waiting for a free slot
+    scheduler: Box<dyn IoScheduler>,
+    // I/Os submitted for a later tick, by tasks still on the CPU when they submitted them
+    pending: Vec<Request>,
+    // I/Os being served, all progressing every tick
+    inflight: Vec<Request>,
+    // Counters behind the device's statistics
+    stats: DeviceStats,
+}
+
+impl Device {
+    // Creates a device serving up to `depth` I/Os at once, or any number for None
+    pub fn new(name: &str, depth: Option<usize>) -> Self {
+        Self {
+            name: String::from(name),
+            depth: depth.map(|depth| depth.max(1)),
+            service: None,
+            scheduler: Box::new(Noop::new()),
+            pending: Vec::new(),
+            inflight: Vec::new(),
+            stats: DeviceStats { name: String::from(name), ..DeviceStats::default() },
+        }
+    }
+
+    // Sets the distribution the service time of every I/O is drawn from
+    pub fn with_service(mut self, service: Dist) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    // Sets the I/O scheduler ordering the I/Os waiting for the device
+    pub fn with_scheduler(mut self, scheduler: Box<dyn IoScheduler>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    // Returns the name of the device's I/O scheduler
+    pub fn scheduler(&self) -> &'static str {
+        self.scheduler.name()
+    }
+
+    // Returns the device's name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Returns the number of I/Os the device serves at once, or None for no limit
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    // Returns the distribution of service times, if the device overrides burst lengths
+    pub fn service(&self) -> Option<&Dist> {
+        self.service.as_ref()
+    }
+
+    // Queues the I/O a task is about to do from the given tick on
+    pub fn submit(&mut self, task: Task, at: u128) {
+        let kind = match task.get_phase() {
+            Some(Phase::Write(..)) => IoKind::Write,
+            _ => IoKind::Read,
+        };
+        self.pending.push(Request { task, kind, submitted: at });
+    }
+
+    // Dispatches the I/Os the scheduler picks into the free slots, advances every I/O in flight
+    // by a tick and returns the tasks whose I/O completed, or the error of a task that was not blocked on I/O
+    pub fn cycle(&mut self, now: u128) -> Result<Vec<Task>, TransitionError> {
+        // I/Os reach the scheduler on the tick they were submitted for
+        let (due, later): (Vec<Request>, Vec<Request>) = std::mem::take(&mut self.pending).into_iter().partition(|request| request.submitted <= now);
+        self.pending = later;
+        for request in due {
+            self.scheduler.add(request);
+        }
+        while self.depth.is_none_or(|depth| self.inflight.len() < depth) {
+            let mut request = match self.scheduler.dispatch(now) {
+                Some(request) => request,
+                None => break,
+            };
+            if let Some(service) = self.service.as_ref() {
+                request.task.redraw_phase(service);
+            }
+            self.inflight.push(request);
+        }
+
+        if !self.inflight.is_empty() {
+            self.stats.busy_ticks += 1;
+        }
+        self.stats.queued_ticks += (self.scheduler.len() + self.inflight.len()) as u128;
+
+        let mut done = Vec::new();
+        let mut idx = 0;
+        while idx < self.inflight.len() {
+            if self.inflight[idx].task.io_cycle()? {
+                let request = self.inflight.remove(idx);
+                // The latency covers the tick the I/O completed in
+                let latency = now + 1 - request.submitted;
+                self.scheduler.completed(request.kind, latency);
+                self.stats.total_latency += latency;
+                self.stats.max_latency = self.stats.max_latency.max(latency);
+                done.push(request.task);
+            } else {
+                idx += 1;
+            }
+        }
+        self.stats.completed += done.len() as u64;
+
+        Ok(done)
+    }
+
+    // Returns whether no I/O is waiting or in flight
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty() && self.scheduler.is_empty() && self.inflight.is_empty()
+    }
+
+    // Returns a task whose I/O is waiting or in flight
+    pub fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        let request = self.pending.iter_mut().chain(self.inflight.iter_mut()).find(|request| request.task.get_id() == id);
+        match request {
+            Some(request) => Some(&mut request.task),
+            None => self.scheduler.task_mut(id),
+        }
+    }
+
+    // Returns the device's statistics so far
+    pub fn stats(&self) -> &DeviceStats {
+        &self.stats
+    }
+}
+
+// Statistics of a device over a run
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DeviceStats {
+    // Name of the device
+    pub name: String,
+    // Number of I/Os served to completion
+    pub completed: u64,
+    // Ticks with at least one I/O in flight
+    pub busy_ticks: u128,
+    // Sum over every tick of the number of I/Os waiting or in flight
+    pub queued_ticks: u128,
+    // Sum and maximum of the ticks from submission to completion of every completed I/O
+    pub total_latency: u128,
+    pub max_latency: u128,
+}
+
+impl DeviceStats {
+    // Returns the fraction of the given number of ticks the device was busy
+    pub fn utilisation(&self, elapsed: u128) -> f64 {
+        if elapsed == 0 {
+            return 0.0;
+        }
+        self.busy_ticks as f64 / elapsed as f64
+    }
+
+    // Returns the mean ticks from submission to completion of the completed I/Os
+    pub fn mean_latency(&self) -> f64 {
+        if self.completed == 0 {
+            return 0.0;
+        }
+        self.total_latency as f64 / self.completed as f64
+    }
+
+    // Returns the mean number of I/Os waiting or in flight over the given number of ticks
+    pub fn mean_queue_length(&self, elapsed: u128) -> f64 {
+        if elapsed == 0 {
+            return 0.0;
+        }
+        self.queued_ticks as f64 / elapsed as f64
+    }
+}
@@ -0,0 +1,374 @@
+use crate::proc::task::Task;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+// Whether an I/O reads from or writes to its device
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IoKind {
+    Read,
+    Write,
+}
+
+// An I/O waiting in the request queue of a device
+#[derive(Clone, Debug)]
+pub struct Request {
+    // The task doing the I/O
+    pub task: Task,
+    // Whether the I/O reads or writes
+    pub kind: IoKind,
+    // Tick at which the I/O was submitted to the device
+    pub submitted: u128,
+}
+
+// A block I/O scheduler, deciding in which order the queued I/Os of a device are dispatched
+pub trait IoScheduler: Send {
+    // Returns the name the scheduler is selected by
+    fn name(&self) -> &'static str;
+
+    // Queues an I/O
+    fn add(&mut self, request: Request);
+
+    // Takes the next I/O to dispatch at the given tick, if the scheduler is willing to dispatch one
+    fn dispatch(&mut self, now: u128) -> Option<Request>;
+
+    // Tells the scheduler an I/O it dispatched completed after waiting and being served for `latency` ticks
+    fn completed(&mut self, _kind: IoKind, _latency: u128) {}
+
+    // Returns the number of queued I/Os
+    fn len(&self) -> usize;
+
+    // Returns whether no I/O is queued
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Returns the task of a queued I/O
+    fn task_mut(&mut self, id: u16) -> Option<&mut Task>;
+
+    // Returns a copy of the scheduler, queued I/Os included
+    fn box_clone(&self) -> Box<dyn IoScheduler>;
+}
+
+impl Clone for Box<dyn IoScheduler> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// Finds the task of a request in a queue
+fn find_task(queue: &mut VecDeque<Request>, id: u16) -> Option<&mut Task> {
+    queue.iter_mut().map(|request| &mut request.task).find(|task| task.get_id() == id)
+}
+
+// Dispatches I/Os in the order they were submitted
+#[derive(Clone, Default)]
+pub struct Noop {
+    queue: VecDeque<Request>,
+}
+
+impl Noop {
+    // Creates a scheduler with an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IoScheduler for Noop {
+    fn name(&self) -> &'static str {
+        "noop"
+    }
+
+    fn add(&mut self, request: Request) {
+        self.queue.push_back(request);
+    }
+
+    fn dispatch(&mut self, _now: u128) -> Option<Request> {
+        self.queue.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        find_task(&mut self.queue, id)
+    }
+
+    fn box_clone(&self) -> Box<dyn IoScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+// Dispatches reads ahead of writes, like mq-deadline, but serves any I/O that has waited past
+// its expiry time first and never passes over pending writes more than `writes_starved` times
+#[derive(Clone)]
+pub struct Deadline {
+    reads: VecDeque<Request>,
+    writes: VecDeque<Request>,
+    // Ticks a read or write may wait before it is dispatched ahead of everything else
+    read_expire: u128,
+    write_expire: u128,
+    // Number of times reads may be preferred while writes are pending
+    writes_starved: u32,
+    // Number of reads dispatched since the last write while writes were pending
+    starved: u32,
+}
+
+impl Deadline {
+    // Creates a deadline scheduler with expiry times scaled down from the kernel's 500ms and 5s
+    pub fn new() -> Self {
+        Self::with_expiry(50, 500)
+    }
+
+    // Creates a deadline scheduler with the given read and write expiry times
+    pub fn with_expiry(read_expire: u128, write_expire: u128) -> Self {
+        Self {
+            reads: VecDeque::new(),
+            writes: VecDeque::new(),
+            read_expire,
+            write_expire,
+            writes_starved: 2,
+            starved: 0,
+        }
+    }
+
+    // Returns whether the oldest I/O of a queue waited past the expiry time
+    fn expired(queue: &VecDeque<Request>, expire: u128, now: u128) -> bool {
+        queue.front().is_some_and(|request| now.saturating_sub(request.submitted) >= expire)
+    }
+}
+
+impl Default for Deadline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IoScheduler for Deadline {
+    fn name(&self) -> &'static str {
+        "deadline"
+    }
+
+    fn add(&mut self, request: Request) {
+        match request.kind {
+            IoKind::Read => self.reads.push_back(request),
+            IoKind::Write => self.writes.push_back(request),
+        }
+    }
+
+    fn dispatch(&mut self, now: u128) -> Option<Request> {
+        let write = if Self::expired(&self.reads, self.read_expire, now) {
+            false
+        } else if Self::expired(&self.writes, self.write_expire, now) {
+            true
+        } else {
+            self.reads.is_empty() || (!self.writes.is_empty() && self.starved >= self.writes_starved)
+        };
+
+        if write {
+            self.starved = 0;
+            self.writes.pop_front()
+        } else {
+            if !self.writes.is_empty() {
+                self.starved += 1;
+            }
+            self.reads.pop_front()
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.reads.len() + self.writes.len()
+    }
+
+    fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        find_task(&mut self.reads, id).or_else(|| find_task(&mut self.writes, id))
+    }
+
+    fn box_clone(&self) -> Box<dyn IoScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+// Shares the device between tasks in proportion to their weights, like BFQ. Every I/O gets a
+// virtual finish time of its length divided by the task's weight, counted from the later of the
+// device's virtual time and the finish time of the task's previous I/O, and the I/O with the
+// earliest finish time is dispatched first.
+#[derive(Clone, Default)]
+pub struct Bfq {
+    // Queued I/Os with their virtual start and finish times, in submission order
+    queue: VecDeque<(Request, u64, u64)>,
+    // Virtual time of the device, the start time of the last dispatched I/O
+    vtime: u64,
+    // Virtual finish time of the last I/O of every task
+    last_finish: HashMap<u16, u64>,
+}
+
+// Scale applied to I/O lengths before dividing them by weights, so light tasks still accrue virtual time
+const BFQ_SCALE: u64 = 1024;
+
+impl Bfq {
+    // Creates a scheduler with an empty queue and the virtual time at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IoScheduler for Bfq {
+    fn name(&self) -> &'static str {
+        "bfq"
+    }
+
+    fn add(&mut self, request: Request) {
+        let id = request.task.get_id();
+        let cost = request.task.get_phase_left().max(1) * BFQ_SCALE / u64::from(request.task.weight().max(1));
+        let start = self.vtime.max(self.last_finish.get(&id).copied().unwrap_or(0));
+        let finish = start + cost;
+        self.last_finish.insert(id, finish);
+        self.queue.push_back((request, start, finish));
+    }
+
+    fn dispatch(&mut self, _now: u128) -> Option<Request> {
+        // The earliest finish time wins, ties going to the I/O submitted first
+        let idx = self
+            .queue
+            .iter()
+            .enumerate()
+            .min_by_key(|(idx, (_, _, finish))| (*finish, *idx))
+            .map(|(idx, _)| idx)?;
+        let (request, start, _) = self.queue.remove(idx)?;
+        self.vtime = self.vtime.max(start);
+
+        Some(request)
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        self.queue.iter_mut().map(|(request, _, _)| &mut request.task).find(|task| task.get_id() == id)
+    }
+
+    fn box_clone(&self) -> Box<dyn IoScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+// Throttles writes to keep reads within a latency target, like Kyber. Reads and writes have
+// their own queues and in-flight limits; reads completing over the target halve the write
+// limit, and reads completing well within it let the write limit grow back by one.
+#[derive(Clone)]
+pub struct Kyber {
+    reads: VecDeque<Request>,
+    writes: VecDeque<Request>,
+    // Latency reads should complete within
+    read_target: u128,
+    // Number of writes that may be in flight at once, and its upper bound
+    write_depth: usize,
+    max_write_depth: usize,
+    // Number of writes in flight
+    writes_inflight: usize,
+}
+
+impl Kyber {
+    // Creates a Kyber scheduler with a read latency target of 20 ticks
+    pub fn new() -> Self {
+        Self::with_target(20, 8)
+    }
+
+    // Creates a Kyber scheduler with the given read latency target and maximum number of writes in flight
+    pub fn with_target(read_target: u128, max_write_depth: usize) -> Self {
+        let max_write_depth = max_write_depth.max(1);
+        Self {
+            reads: VecDeque::new(),
+            writes: VecDeque::new(),
+            read_target,
+            write_depth: max_write_depth,
+            max_write_depth,
+            writes_inflight: 0,
+        }
+    }
+
+    // Returns the number of writes currently allowed in flight
+    pub fn write_depth(&self) -> usize {
+        self.write_depth
+    }
+}
+
+impl Default for Kyber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IoScheduler for Kyber {
+    fn name(&self) -> &'static str {
+        "kyber"
+    }
+
+    fn add(&mut self, request: Request) {
+        match request.kind {
+            IoKind::Read => self.reads.push_back(request),
+            IoKind::Write => self.writes.push_back(request),
+        }
+    }
+
+    fn dispatch(&mut self, _now: u128) -> Option<Request> {
+        if let Some(request) = self.reads.pop_front() {
+            return Some(request);
+        }
+        if self.writes_inflight < self.write_depth {
+            let request = self.writes.pop_front()?;
+            self.writes_inflight += 1;
+            return Some(request);
+        }
+        None
+    }
+
+    fn completed(&mut self, kind: IoKind, latency: u128) {
+        match kind {
+            IoKind::Write => self.writes_inflight = self.writes_inflight.saturating_sub(1),
+            IoKind::Read if latency > self.read_target => self.write_depth = (self.write_depth / 2).max(1),
+            IoKind::Read if latency * 2 <= self.read_target => {
+                self.write_depth = (self.write_depth + 1).min(self.max_write_depth)
+            }
+            IoKind::Read => {}
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.reads.len() + self.writes.len()
+    }
+
+    fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        find_task(&mut self.reads, id).or_else(|| find_task(&mut self.writes, id))
+    }
+
+    fn box_clone(&self) -> Box<dyn IoScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+// Error returned when no I/O scheduler has the requested name
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownSchedulerError(pub String);
+
+impl fmt::Display for UnknownSchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown I/O scheduler `{}`, expected noop, deadline, bfq or kyber", self.0)
+    }
+}
+
+impl Error for UnknownSchedulerError {}
+
+// Creates the I/O scheduler with the given name, using its default tunables
+pub fn by_name(name: &str) -> Result<Box<dyn IoScheduler>, UnknownSchedulerError> {
+    match name {
+        "noop" | "none" | "fifo" => Ok(Box::new(Noop::new())),
+        "deadline" | "mq-deadline" => Ok(Box::new(Deadline::new())),
+        "bfq" => Ok(Box::new(Bfq::new())),
+        "kyber" => Ok(Box::new(Kyber::new())),
+        _ => Err(UnknownSchedulerError(String::from(name))),
+    }
+}
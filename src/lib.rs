@@ -1,2 +1,6 @@
+pub mod cli;
+pub mod error;
+pub mod io;
 pub mod proc;
 pub mod sched;
+pub mod sync;
@@ -1,73 +1,297 @@
 extern crate completely_fair_scheduler as cfs;
 
+use cfs::cli::{self, Cli, Command, GenerateOptions, Outputs, ReportFormat, RunOptions};
+use cfs::error::Error;
+use cfs::sched::chrome::ChromeTrace;
+use cfs::sched::export::{self, JsonLines, SeriesCsv};
+use cfs::sched::gantt::{Gantt, Rows};
+use cfs::sched::metrics::{self, Summary};
+use cfs::sched::report::Report;
+use cfs::sched::ftrace::Ftrace;
 use cfs::sched::scheduler::Scheduler;
-use cfs::proc::task::TaskChar;
+use cfs::sched::trace::{EventLog, Printer};
+use cfs::proc::generator::Generator;
+use cfs::proc::replay::{self, Replay};
+use cfs::proc::validate;
+use cfs::proc::workload::{Format, Workload};
 
-use std::io::Read;
+use std::io::BufWriter;
 use std::fs::File;
+use std::path::Path;
+use std::process::ExitCode;
 
-fn main() {
-    // Open the tasks file and check if it was successful
-    let mut file = match File::open("tasks.txt") {
-        Ok(file) => file,
+// Why a command failed: the code to exit with and the message to print on stderr
+struct Failure(u8, String);
+
+// Returns the failure of a file that could not be read or written
+fn io_failure(path: &Path, e: std::io::Error) -> Failure {
+    Failure(cli::EXIT_IO, format!("{}: {}", path.display(), e))
+}
+
+// Returns the failure of a run, an I/O one if a trace could not be written
+fn run_failure(e: Error) -> Failure {
+    match e {
+        Error::Sink(e) => Failure(cli::EXIT_IO, e.to_string()),
+        e => Failure(cli::EXIT_FAILED, format!("Scheduler error: {}", e)),
+    }
+}
+
+fn main() -> ExitCode {
+    // Parse the arguments following the program name, printing the usage if they are invalid
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
         Err(e) => {
-            println!("Could not find tasks file: {}", e);
-            return;
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            return ExitCode::from(cli::EXIT_USAGE);
         },
     };
 
-    // Read contents of the file into a string
-    let mut file_str = String::new();
-    match file.read_to_string(&mut file_str) {
-        Ok(_) => (),
-        Err(e) => {
-            println!("Unable to write contents of tasks file to string: {}", e);
-            return;
+    // Run the command, printing why it failed if it did
+    let result = match &cli.command {
+        Command::Run { workload, trace, options } => run(&cli, workload, *trace, options),
+        Command::Replay { trace, tick, options } => replay(&cli, trace, *tick, options),
+        Command::Compare { workloads, options } => compare(&cli, workloads, options),
+        Command::Validate { workloads } => validate(&cli, workloads),
+        Command::Generate(options) => generate(&cli, options),
+        Command::Help => emit(&cli, cli::USAGE),
+        Command::Version => emit(&cli, &format!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+    };
+    match result {
+        Ok(()) => ExitCode::from(cli::EXIT_OK),
+        Err(Failure(code, message)) => {
+            eprintln!("{}", message);
+            ExitCode::from(code)
         },
+    }
+}
+
+// Prints what a command outputs, or writes it to the file given by "--output". Nothing is printed when quiet.
+fn emit(cli: &Cli, text: &str) -> Result<(), Failure> {
+    match &cli.output {
+        Some(path) => std::fs::write(path, text).map_err(|e| io_failure(path, e)),
+        None if cli.quiet => Ok(()),
+        None => {
+            print!("{}", text);
+            Ok(())
+        },
+    }
+}
+
+// Reads a workload and checks it, printing its diagnostics on stderr, warnings only when not quiet.
+// Workloads with errors are refused.
+fn load(cli: &Cli, path: &Path) -> Result<Workload, Failure> {
+    let text = std::fs::read_to_string(path).map_err(|e| io_failure(path, e))?;
+    let workload = Workload::parse(&text, Format::from_path(path)).map_err(|e| Failure(cli::EXIT_INVALID, format!("{}: {}", path.display(), e)))?;
+    let diagnostics = validate::validate(&workload);
+    for diagnostic in diagnostics.iter().filter(|diagnostic| !cli.quiet || diagnostic.severity == validate::Severity::Error) {
+        eprintln!("{}: {}", path.display(), diagnostic);
+    }
+    if validate::has_errors(&diagnostics) {
+        return Err(Failure(cli::EXIT_INVALID, format!("{}: the workload has errors", path.display())));
+    }
+    Ok(workload)
+}
+
+// Creates a scheduler with the settings of the command line. Nothing is printed as it runs: the task run
+// on every tick and the report are returned instead, so they can go to the output.
+fn scheduler(cli: &Cli, options: &RunOptions) -> Scheduler {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    scheduler.print_report(false);
+    scheduler.seed(cli.seed.unwrap_or(0));
+    scheduler.priority_inheritance(options.priority_inheritance);
+    scheduler
+}
+
+// Adds the sinks writing the traces asked for to the scheduler, and an event log for the per-tick lines
+// when tracing and the Gantt charts if any
+fn sinks(scheduler: &mut Scheduler, outputs: &Outputs, trace: bool) -> Result<EventLog, Failure> {
+    let paths = [&outputs.chrome_trace, &outputs.ftrace, &outputs.events_jsonl, &outputs.series_csv];
+    for (idx, path) in paths.into_iter().enumerate() {
+        let path = match path {
+            Some(path) => path,
+            None => continue,
+        };
+        let file = BufWriter::new(File::create(path).map_err(|e| io_failure(path, e))?);
+        match idx {
+            0 => scheduler.sink(Box::new(ChromeTrace::new(file))),
+            1 => scheduler.sink(Box::new(Ftrace::new(file))),
+            2 => scheduler.sink(Box::new(JsonLines::new(file))),
+            _ => scheduler.sink(Box::new(SeriesCsv::new(file))),
+        }
+    }
+    let log = EventLog::new();
+    if trace || outputs.gantt.is_some() || outputs.gantt_ascii {
+        scheduler.sink(Box::new(log.clone()));
+    }
+    Ok(log)
+}
+
+// Runs a workload, printing the task run on every tick when tracing, then the report
+fn run(cli: &Cli, path: &Path, trace: bool, options: &RunOptions) -> Result<(), Failure> {
+    let mut workload = load(cli, path)?;
+    if let Some(policy) = options.policy {
+        workload.set_policy(policy);
+    }
+    let mut scheduler = scheduler(cli, options);
+    let log = sinks(&mut scheduler, &options.outputs, trace)?;
+    let tasks = workload.apply(&mut scheduler);
+    if let Some(slack) = options.timer_slack {
+        scheduler.timer_slack(slack);
+    }
+    let report = scheduler.run(tasks).map_err(run_failure)?;
+    let head = match trace {
+        true => ticks(&log),
+        false => String::new(),
     };
+    finish(cli, options, &report, &log, head)
+}
+
+// Returns the line of the task run on every tick of a run, as the printer prints them
+fn ticks(log: &EventLog) -> String {
+    log.events().iter().filter_map(Printer::line).map(|line| line + "\n").collect()
+}
 
-    // Split the contents of the file into lines
-    let task_lines = file_str.split("\n");
-
-    // Create a vector of split lines
-    let task_lines_vec: Vec<&str> = task_lines.collect();
-
-    // Create a vector to store properties of each task
-    let mut task_props = vec![];
-    for line in task_lines_vec {
-        // Split each line into task properties
-        let task_props_split = line.split_whitespace().take(4);
-
-        // Collect the task properties into a vector
-        task_props.push(task_props_split.collect::<Vec<&str>>());
-    }
-
-    // Create a vector to store tasks
-    let mut tasks = vec![];
-
-    // Index for assigning task IDs
-    let mut idx = 1;
-
-    // Create tasks from the properties
-    for task_prop in task_props {
-        match &task_prop[..] {
-            [cpu_time, cpu_burst_length, io_burst_length, weight] => {
-                tasks.push(TaskChar::new(
-                    idx,
-                    cpu_time.parse::<u64>().unwrap(),
-                    cpu_burst_length.parse::<u64>().unwrap(),
-                    io_burst_length.parse::<u64>().unwrap(),
-                    weight.parse::<u32>().unwrap(),
-                ));
-            }
-            _ => continue,
+// Writes the outputs of a run and its report, failing if tasks were left deadlocked or stalled.
+// The head goes out ahead of the text report; the JSON and CSV reports go out on their own.
+fn finish(cli: &Cli, options: &RunOptions, report: &Report, log: &EventLog, head: String) -> Result<(), Failure> {
+    summaries(&options.outputs, report)?;
+    let mut text = match options.format {
+        ReportFormat::Text => head,
+        _ => String::new(),
+    };
+    text.push_str(&gantt(&options.outputs, log)?);
+    text.push_str(&match options.format {
+        ReportFormat::Text => report.to_string(),
+        ReportFormat::Json => serde_json::to_string_pretty(report).map(|json| json + "\n").map_err(|e| Failure(cli::EXIT_FAILED, e.to_string()))?,
+        ReportFormat::Csv => export::tasks_csv(&report.tasks),
+    });
+    emit(cli, &text)?;
+    if !report.deadlocked.is_empty() || !report.stalled.is_empty() {
+        return Err(Failure(cli::EXIT_FAILED, String::from("The run ended with tasks deadlocked or stalled")));
+    }
+    Ok(())
+}
+
+// Writes the statistics of every task to the files asked for: as CSV and as JSON
+fn summaries(outputs: &Outputs, report: &Report) -> Result<(), Failure> {
+    if let Some(path) = &outputs.tasks_csv {
+        std::fs::write(path, export::tasks_csv(&report.tasks)).map_err(|e| io_failure(path, e))?;
+    }
+    if let Some(path) = &outputs.tasks_json {
+        let json = export::tasks_json(&report.tasks).map_err(std::io::Error::from).map_err(|e| io_failure(path, e))?;
+        std::fs::write(path, json).map_err(|e| io_failure(path, e))?;
+    }
+    Ok(())
+}
+
+// Draws the Gantt charts of the run asked for, writing the SVG one and returning the ASCII one
+// to go out ahead of the report, or nothing if it was not asked for
+fn gantt(outputs: &Outputs, log: &EventLog) -> Result<String, Failure> {
+    let rows = outputs.gantt_rows.unwrap_or(Rows::Tasks);
+    let chart = Gantt::new(&log.events());
+    if let Some(path) = &outputs.gantt {
+        std::fs::write(path, chart.svg(rows)).map_err(|e| io_failure(path, e))?;
+    }
+    match outputs.gantt_ascii {
+        true => Ok(format!("{}\n", chart.ascii(rows, 100))),
+        false => Ok(String::new()),
+    }
+}
+
+// Replays the threads of a kernel trace through the scheduler, with the replayed tasks, the task run on
+// every tick and what the kernel did ahead of the text report
+fn replay(cli: &Cli, path: &Path, tick: u128, options: &RunOptions) -> Result<(), Failure> {
+    let trace = std::fs::read_to_string(path).map_err(|e| io_failure(path, e))?;
+    let replayed = Replay::new().with_tick(tick).import(&trace).map_err(|e| Failure(cli::EXIT_INVALID, format!("{}: {}", path.display(), e)))?;
+
+    let mut scheduler = scheduler(cli, options);
+    let log = sinks(&mut scheduler, &options.outputs, true)?;
+    if let Some(slack) = options.timer_slack {
+        scheduler.timer_slack(slack);
+    }
+    let tasks = replayed.iter().map(|replayed| replayed.task.clone()).collect();
+    let report = scheduler.run(tasks).map_err(run_failure)?;
+    let observed: Vec<_> = replayed.iter().map(|replayed| replayed.observed.clone()).collect();
+    let head = format!("Replaying:\n{}{}In the trace:\n{}\n", replay::tasks_file(&replayed), ticks(&log), Summary::new(&observed, 0));
+    finish(cli, options, &report, &log, head)
+}
+
+// Runs every workload silently and compares their metrics
+fn compare(cli: &Cli, paths: &[std::path::PathBuf], options: &RunOptions) -> Result<(), Failure> {
+    let mut runs = Vec::new();
+    for path in paths {
+        let mut workload = load(cli, path)?;
+        if let Some(policy) = options.policy {
+            workload.set_policy(policy);
         }
-        idx += 1;
+        let mut scheduler = scheduler(cli, options);
+        let tasks = workload.apply(&mut scheduler);
+        if let Some(slack) = options.timer_slack {
+            scheduler.timer_slack(slack);
+        }
+        let report = scheduler.run(tasks).map_err(|e| Failure(cli::EXIT_FAILED, format!("{}: scheduler error: {}", path.display(), e)))?;
+        runs.push((path.display().to_string(), report.summary));
     }
 
-    // Create a scheduler instance
-    let mut scheduler = Scheduler::new();
+    let text = match options.format {
+        ReportFormat::Text => metrics::comparison(&runs),
+        ReportFormat::Json => {
+            let runs: Vec<_> = runs.iter().map(|(workload, summary)| serde_json::json!({ "workload": workload, "summary": summary })).collect();
+            serde_json::to_string_pretty(&runs).map(|json| json + "\n").map_err(|e| Failure(cli::EXIT_FAILED, e.to_string()))?
+        },
+        ReportFormat::Csv => export::summaries_csv(&runs),
+    };
+    emit(cli, &text)
+}
 
-    // Run the scheduler
-    scheduler.run(tasks);
+// Checks workloads without running them, printing their diagnostics, or that they are fine
+fn validate(cli: &Cli, paths: &[std::path::PathBuf]) -> Result<(), Failure> {
+    let mut text = String::new();
+    let mut invalid = false;
+    for path in paths {
+        let source = std::fs::read_to_string(path).map_err(|e| io_failure(path, e))?;
+        let diagnostics = match Workload::parse(&source, Format::from_path(path)) {
+            Ok(workload) => validate::validate(&workload),
+            Err(e) => {
+                text.push_str(&format!("{}: {}\n", path.display(), e));
+                invalid = true;
+                continue;
+            },
+        };
+        for diagnostic in diagnostics.iter() {
+            text.push_str(&format!("{}: {}\n", path.display(), diagnostic));
+        }
+        if diagnostics.is_empty() {
+            text.push_str(&format!("{}: ok\n", path.display()));
+        }
+        invalid |= validate::has_errors(&diagnostics);
+    }
+    emit(cli, &text)?;
+    match invalid {
+        true => Err(Failure(cli::EXIT_INVALID, String::from("Some workloads have errors"))),
+        false => Ok(()),
+    }
+}
+
+// Generates a random workload, in the format asked for, following the extension of the output, or TOML
+fn generate(cli: &Cli, options: &GenerateOptions) -> Result<(), Failure> {
+    let mut generator = Generator::new(options.profile).with_seed(cli.seed.unwrap_or(0));
+    if let Some(tasks) = options.tasks {
+        generator = generator.with_tasks(tasks);
+    }
+    if let Some(utilisation) = options.utilisation {
+        generator = generator.with_utilisation(utilisation);
+    }
+    if let Some((low, high)) = options.cpu_time {
+        generator = generator.with_cpu_time(low, high);
+    }
+    if let Some((low, high)) = options.nice {
+        generator = generator.with_nice(low, high);
+    }
+    let format = options.format.unwrap_or(cli.output.as_deref().map_or(Format::Toml, Format::from_path));
+    let workload = generator.generate().map_err(|e| Failure(cli::EXIT_FAILED, format!("Could not generate the workload: {}", e)))?;
+    let text = workload.write(format).map_err(|e| Failure(cli::EXIT_FAILED, format!("Could not write the workload: {}", e)))?;
+    emit(cli, &text)
 }
@@ -0,0 +1,118 @@
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Exp};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// When the instances of a task arrive in the system
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arrival {
+    // A single instance arriving at the given tick
+    At(u128),
+    // `count` instances, the first arriving at `start` and the others every `period` ticks
+    Periodic { start: u128, period: u128, count: u32 },
+    // `count` instances, the first arriving at `start` and the others after exponentially
+    // distributed gaps with the given mean, i.e. a Poisson arrival process
+    Poisson { start: u128, mean_gap: f64, count: u32 },
+}
+
+impl Arrival {
+    // Returns the arrival times of every instance in order, drawing Poisson gaps from the generator
+    pub fn instants(&self, rng: &mut StdRng) -> Vec<u128> {
+        match self {
+            Arrival::At(time) => vec![*time],
+            Arrival::Periodic { start, period, count } => {
+                (0..*count).map(|idx| start + period * idx as u128).collect()
+            }
+            Arrival::Poisson { start, mean_gap, count } => {
+                // A mean gap the parser would reject makes every gap exactly the mean
+                let gaps = Exp::new(1.0 / mean_gap).ok();
+                let mut time = *start as f64;
+                let mut instants = Vec::new();
+                for idx in 0..*count {
+                    if idx > 0 {
+                        time += gaps.map_or(*mean_gap, |gaps| gaps.sample(rng));
+                    }
+                    instants.push(time.round() as u128);
+                }
+                instants
+            }
+        }
+    }
+
+    // Returns the arrival time of the first instance
+    pub fn start(&self) -> u128 {
+        match self {
+            Arrival::At(start) | Arrival::Periodic { start, .. } | Arrival::Poisson { start, .. } => *start,
+        }
+    }
+}
+
+impl Default for Arrival {
+    fn default() -> Self {
+        Arrival::At(0)
+    }
+}
+
+impl fmt::Display for Arrival {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arrival::At(time) => write!(f, "{}", time),
+            Arrival::Periodic { start, period, count } => {
+                write!(f, "{} every {} count {}", start, period, count)
+            }
+            Arrival::Poisson { start, mean_gap, count } => {
+                write!(f, "{} poisson {} count {}", start, mean_gap, count)
+            }
+        }
+    }
+}
+
+// Error returned when an arrival cannot be parsed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseArrivalError(String);
+
+impl fmt::Display for ParseArrivalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid arrival: {}", self.0)
+    }
+}
+
+impl Error for ParseArrivalError {}
+
+// Parses a number in an arrival
+fn arg<T: FromStr>(what: &str, value: &str) -> Result<T, ParseArrivalError> {
+    value
+        .parse::<T>()
+        .map_err(|_| ParseArrivalError(format!("{} must be a number, found `{}`", what, value)))
+}
+
+impl FromStr for Arrival {
+    type Err = ParseArrivalError;
+
+    // Parses `T`, `T every P count N` or `T poisson M count N`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match &words[..] {
+            [start] => Ok(Arrival::At(arg("arrival time", start)?)),
+            [start, "every", period, "count", count] => {
+                let period = arg("period", period)?;
+                if period == 0 {
+                    return Err(ParseArrivalError(String::from("period must be positive")));
+                }
+                Ok(Arrival::Periodic { start: arg("arrival time", start)?, period, count: arg("count", count)? })
+            }
+            [start, "poisson", mean_gap, "count", count] => {
+                let mean_gap: f64 = arg("mean gap", mean_gap)?;
+                if mean_gap <= 0.0 || !mean_gap.is_finite() {
+                    return Err(ParseArrivalError(String::from("mean gap must be positive")));
+                }
+                Ok(Arrival::Poisson { start: arg("arrival time", start)?, mean_gap, count: arg("count", count)? })
+            }
+            _ => Err(ParseArrivalError(format!(
+                "expected `T`, `T every P count N` or `T poisson M count N`, found `{}`",
+                s.trim()
+            ))),
+        }
+    }
+}
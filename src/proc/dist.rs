@@ -0,0 +1,192 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Exp, Normal, Pareto, WeightedIndex};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// A distribution of burst lengths, sampled once per burst
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dist {
+    // Always the same length
+    Constant(u64),
+    // Uniformly distributed between the two bounds, inclusive
+    Uniform(u64, u64),
+    // Exponentially distributed with the given mean
+    Exponential(f64),
+    // Normally distributed with the given mean and standard deviation
+    Normal(f64, f64),
+    // Pareto distributed with the given scale and shape
+    Pareto(f64, f64),
+    // Drawn from a histogram of (length, weight) pairs
+    Empirical(Vec<(u64, f64)>),
+}
+
+impl Dist {
+    // Returns whether the distribution always yields zero
+    pub fn is_zero(&self) -> bool {
+        *self == Dist::Constant(0)
+    }
+
+    // Draws a burst length from the distribution, rounding to whole ticks and clamping at zero.
+    // Parameters the parser would reject make the distribution degenerate: the bounds of a uniform
+    // distribution are swapped if needed, an exponential or normal distribution always yields its
+    // mean, a Pareto distribution its scale and a histogram with no positive weight zero.
+    pub fn sample(&self, rng: &mut StdRng) -> u64 {
+        let length = match self {
+            Dist::Constant(length) => return *length,
+            Dist::Uniform(low, high) => return rng.gen_range(*low.min(high)..=*low.max(high)),
+            Dist::Exponential(mean) => Exp::new(1.0 / mean).map_or(*mean, |exp| exp.sample(rng)),
+            Dist::Normal(mean, std_dev) => Normal::new(*mean, *std_dev).map_or(*mean, |normal| normal.sample(rng)),
+            Dist::Pareto(scale, shape) => Pareto::new(*scale, *shape).map_or(*scale, |pareto| pareto.sample(rng)),
+            Dist::Empirical(buckets) => {
+                return match WeightedIndex::new(buckets.iter().map(|bucket| bucket.1)) {
+                    Ok(index) => buckets[index.sample(rng)].0,
+                    Err(_) => 0,
+                };
+            }
+        };
+
+        length.max(0.0).round() as u64
+    }
+
+    // Returns the expected burst length, which is infinite for heavy-tailed Pareto distributions
+    pub fn mean(&self) -> f64 {
+        match self {
+            Dist::Constant(length) => *length as f64,
+            Dist::Uniform(low, high) => (*low as f64 + *high as f64) / 2.0,
+            Dist::Exponential(mean) | Dist::Normal(mean, _) => *mean,
+            Dist::Pareto(scale, shape) if *shape > 1.0 => scale * shape / (shape - 1.0),
+            Dist::Pareto(..) => f64::INFINITY,
+            Dist::Empirical(buckets) => {
+                let total: f64 = buckets.iter().map(|bucket| bucket.1).sum();
+                buckets.iter().map(|bucket| bucket.0 as f64 * bucket.1).sum::<f64>() / total
+            }
+        }
+    }
+}
+
+impl From<u64> for Dist {
+    fn from(length: u64) -> Self {
+        Dist::Constant(length)
+    }
+}
+
+impl fmt::Display for Dist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dist::Constant(length) => write!(f, "{}", length),
+            Dist::Uniform(low, high) => write!(f, "uniform({},{})", low, high),
+            Dist::Exponential(mean) => write!(f, "exp({})", mean),
+            Dist::Normal(mean, std_dev) => write!(f, "normal({},{})", mean, std_dev),
+            Dist::Pareto(scale, shape) => write!(f, "pareto({},{})", scale, shape),
+            Dist::Empirical(buckets) => {
+                write!(f, "hist(")?;
+                for (idx, (length, weight)) in buckets.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", length, weight)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Error returned when a distribution cannot be parsed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseDistError(String);
+
+impl fmt::Display for ParseDistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid distribution: {}", self.0)
+    }
+}
+
+impl Error for ParseDistError {}
+
+// Parses a single number argument of a distribution
+fn arg<T: FromStr>(name: &str, value: &str) -> Result<T, ParseDistError> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| ParseDistError(format!("`{}` expects numbers, found `{}`", name, value.trim())))
+}
+
+impl FromStr for Dist {
+    type Err = ParseDistError;
+
+    // Parses a constant such as `50` or a distribution such as `exp(50)`, `uniform(10,20)`,
+    // `normal(50,5)`, `pareto(10,1.5)` or `hist(10:3,20:1)`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, args) = match s.split_once('(') {
+            Some((name, rest)) => match rest.strip_suffix(')') {
+                Some(args) => (name.trim(), args.split(',').collect::<Vec<&str>>()),
+                None => return Err(ParseDistError(format!("missing `)` in `{}`", s))),
+            },
+            None => return Ok(Dist::Constant(arg("constant", s)?)),
+        };
+
+        let dist = match (name, &args[..]) {
+            ("uniform", [low, high]) => {
+                let (low, high) = (arg(name, low)?, arg(name, high)?);
+                if low > high {
+                    return Err(ParseDistError(format!("uniform bounds {} and {} are reversed", low, high)));
+                }
+                Dist::Uniform(low, high)
+            }
+            ("exp", [mean]) => {
+                let mean: f64 = arg(name, mean)?;
+                if mean <= 0.0 || !mean.is_finite() {
+                    return Err(ParseDistError(format!("exponential mean {} must be positive", mean)));
+                }
+                Dist::Exponential(mean)
+            }
+            ("normal", [mean, std_dev]) => {
+                let (mean, std_dev): (f64, f64) = (arg(name, mean)?, arg(name, std_dev)?);
+                if std_dev < 0.0 || !std_dev.is_finite() || !mean.is_finite() {
+                    return Err(ParseDistError(format!("normal({},{}) has an invalid parameter", mean, std_dev)));
+                }
+                Dist::Normal(mean, std_dev)
+            }
+            ("pareto", [scale, shape]) => {
+                let (scale, shape): (f64, f64) = (arg(name, scale)?, arg(name, shape)?);
+                if scale <= 0.0 || shape <= 0.0 || !scale.is_finite() || !shape.is_finite() {
+                    return Err(ParseDistError(String::from("pareto scale and shape must be positive")));
+                }
+                Dist::Pareto(scale, shape)
+            }
+            ("hist", buckets) => {
+                let mut parsed = Vec::new();
+                for bucket in buckets {
+                    let (length, weight) = match bucket.split_once(':') {
+                        Some((length, weight)) => (arg(name, length)?, arg::<f64>(name, weight)?),
+                        None => (arg(name, bucket)?, 1.0),
+                    };
+                    if weight < 0.0 || !weight.is_finite() {
+                        return Err(ParseDistError(format!("histogram weight {} must not be negative", weight)));
+                    }
+                    parsed.push((length, weight));
+                }
+                if parsed.iter().map(|bucket| bucket.1).sum::<f64>() <= 0.0 {
+                    return Err(ParseDistError(String::from("histogram needs a bucket with positive weight")));
+                }
+                Dist::Empirical(parsed)
+            }
+            ("uniform" | "exp" | "normal" | "pareto", _) => {
+                return Err(ParseDistError(format!("wrong number of arguments to `{}`", name)))
+            }
+            _ => return Err(ParseDistError(format!("unknown distribution `{}`", name))),
+        };
+
+        Ok(dist)
+    }
+}
+
+// Creates the random number generator of a task from the simulation seed and the task id,
+// so every task draws from its own reproducible stream
+pub fn task_rng(seed: u64, id: u16) -> StdRng {
+    StdRng::seed_from_u64(seed ^ u64::from(id).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
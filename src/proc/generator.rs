@@ -0,0 +1,255 @@
+use super::arrival::Arrival;
+use super::dist::Dist;
+use super::script::{Phase, Script};
+use super::task::{TaskChar, NICE_TO_WEIGHT};
+use super::workload::{DeviceSpec, Policy, Workload, WorkloadTask};
+use rand::rngs::StdRng;
+use crate::error::Error;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
+
+// Device the I/O of web server tasks goes to
+const WEB_DEVICE: &str = "disk";
+
+// Kinds of workloads the generator makes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Profile {
+    // Long CPU-bound batch jobs with little I/O
+    Batch,
+    // Short CPU bursts between long waits, like editors and shells
+    Interactive,
+    // Batch and interactive tasks together
+    Mixed,
+    // Waves of short requests arriving close together, doing I/O on a shared disk
+    WebServer,
+    // Many tasks needing a few ticks each, arriving over time
+    Tiny,
+}
+
+impl Profile {
+    // Returns the name of the profile, as parsed
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Batch => "batch",
+            Profile::Interactive => "interactive",
+            Profile::Mixed => "mixed",
+            Profile::WebServer => "web",
+            Profile::Tiny => "tiny",
+        }
+    }
+}
+
+// Error returned when no profile has the requested name
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownProfileError(String);
+
+impl fmt::Display for UnknownProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown profile `{}`, expected batch, interactive, mixed, web or tiny", self.0)
+    }
+}
+
+impl std::error::Error for UnknownProfileError {}
+
+impl FromStr for Profile {
+    type Err = UnknownProfileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "batch" => Ok(Profile::Batch),
+            "interactive" => Ok(Profile::Interactive),
+            "mixed" => Ok(Profile::Mixed),
+            "web" | "webserver" => Ok(Profile::WebServer),
+            "tiny" => Ok(Profile::Tiny),
+            _ => Err(UnknownProfileError(String::from(s))),
+        }
+    }
+}
+
+// The parameters tasks of a kind are drawn from
+struct Shape {
+    name: &'static str,
+    cpu_time: (u64, u64),
+    nice: (i8, i8),
+    policy: Policy,
+    // Range of the mean CPU burst, and whether bursts are exponential rather than constant
+    cpu_burst: (u64, u64),
+    exponential: bool,
+    // Range of the mean I/O burst as a multiple of the mean CPU burst
+    io_ratio: (f64, f64),
+    // Tasks arrive at a tick drawn from 0 up to this one
+    arrivals: u128,
+}
+
+const BATCH: Shape = Shape {
+    name: "batch",
+    cpu_time: (2000, 10000),
+    nice: (0, 5),
+    policy: Policy::Batch,
+    cpu_burst: (200, 1000),
+    exponential: false,
+    io_ratio: (0.0, 0.05),
+    arrivals: 0,
+};
+
+const INTERACTIVE: Shape = Shape {
+    name: "interactive",
+    cpu_time: (100, 500),
+    nice: (-5, 0),
+    policy: Policy::Normal,
+    cpu_burst: (2, 8),
+    exponential: true,
+    io_ratio: (5.0, 20.0),
+    arrivals: 100,
+};
+
+const WEB: Shape = Shape {
+    name: "web",
+    cpu_time: (10, 50),
+    nice: (0, 0),
+    policy: Policy::Normal,
+    cpu_burst: (2, 5),
+    exponential: true,
+    io_ratio: (2.0, 6.0),
+    arrivals: 0,
+};
+
+const TINY: Shape = Shape {
+    name: "tiny",
+    cpu_time: (1, 10),
+    nice: (0, 0),
+    policy: Policy::Normal,
+    cpu_burst: (1, 3),
+    exponential: false,
+    io_ratio: (0.0, 2.0),
+    arrivals: 1000,
+};
+
+// Generates random workloads of a profile. Every draw comes from the seed, so the same settings
+// always give the same workload.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    profile: Profile,
+    seed: u64,
+    // Number of tasks, or of request waves for the web server, the profile's own if none
+    tasks: Option<usize>,
+    // Share of the CPU the tasks keep busy, the profile's own if none
+    utilisation: Option<f64>,
+    // Ranges of cpu_time and nice values, the profile's own if none
+    cpu_time: Option<(u64, u64)>,
+    nice: Option<(i8, i8)>,
+}
+
+impl Generator {
+    // Creates a generator of the given profile with seed 0
+    pub fn new(profile: Profile) -> Self {
+        Self { profile, seed: 0, tasks: None, utilisation: None, cpu_time: None, nice: None }
+    }
+
+    // Sets the seed every draw comes from
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // Sets the number of tasks, which for the web server is the number of waves of requests
+    pub fn with_tasks(mut self, tasks: usize) -> Self {
+        self.tasks = Some(tasks);
+        self
+    }
+
+    // Sets the share of the CPU the tasks keep busy between them, such as 0.7, by sizing their
+    // I/O bursts: each of the n tasks runs for u/n of the time while it alternates between
+    // bursts. Shares above 1 oversubscribe the CPU.
+    pub fn with_utilisation(mut self, utilisation: f64) -> Self {
+        self.utilisation = Some(utilisation);
+        self
+    }
+
+    // Sets the range the cpu_time of every task is drawn from, bounds included
+    pub fn with_cpu_time(mut self, low: u64, high: u64) -> Self {
+        self.cpu_time = Some((low.min(high), low.max(high)));
+        self
+    }
+
+    // Sets the range the nice value of every task is drawn from, bounds included
+    pub fn with_nice(mut self, low: i8, high: i8) -> Self {
+        let (low, high) = (low.clamp(-20, 19), high.clamp(-20, 19));
+        self.nice = Some((low.min(high), low.max(high)));
+        self
+    }
+
+    // Returns the number of tasks generated
+    fn count(&self) -> usize {
+        self.tasks.unwrap_or(match self.profile {
+            Profile::Batch => 8,
+            Profile::Interactive | Profile::Mixed => 16,
+            Profile::WebServer => 10,
+            Profile::Tiny => 200,
+        })
+    }
+
+    // Generates a workload, failing if there are more tasks than task ids
+    pub fn generate(&self) -> Result<Workload, Error> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let count = self.count();
+        let mut workload = Workload::default();
+        if self.profile == Profile::WebServer {
+            workload.devices.push(DeviceSpec { name: String::from(WEB_DEVICE), depth: Some(4), service: None, scheduler: None });
+        }
+
+        for idx in 0..count {
+            let id = u16::try_from(idx + 1).map_err(|_| Error::PidsExhausted)?;
+            let shape = match self.profile {
+                Profile::Batch => &BATCH,
+                Profile::Interactive => &INTERACTIVE,
+                Profile::Mixed if rng.gen_bool(0.5) => &BATCH,
+                Profile::Mixed => &INTERACTIVE,
+                Profile::WebServer => &WEB,
+                Profile::Tiny => &TINY,
+            };
+            let (low, high) = self.cpu_time.unwrap_or(shape.cpu_time);
+            let cpu_time = rng.gen_range(low.max(1)..=high.max(1));
+            let (low, high) = self.nice.unwrap_or(shape.nice);
+            let nice = rng.gen_range(low..=high);
+
+            // Bursts never outlast the task, and their I/O keeps the CPU at the target share if there is one
+            let cpu_mean = rng.gen_range(shape.cpu_burst.0..=shape.cpu_burst.1).min(cpu_time).max(1);
+            let io_ratio = match self.utilisation {
+                Some(utilisation) => (count as f64 / utilisation.max(f64::EPSILON) - 1.0).max(0.0),
+                None => rng.gen_range(shape.io_ratio.0..=shape.io_ratio.1),
+            };
+            let io_mean = cpu_mean as f64 * io_ratio;
+            let (cpu_burst, io_burst) = if shape.exponential {
+                (Dist::Exponential(cpu_mean as f64), if io_mean.round() >= 1.0 { Dist::Exponential(io_mean.round()) } else { Dist::Constant(0) })
+            } else {
+                (Dist::Constant(cpu_mean), Dist::Constant(io_mean.round() as u64))
+            };
+
+            let weight = NICE_TO_WEIGHT[(nice + 20) as usize];
+            let task = match self.profile {
+                // Requests come in waves of Poisson arrivals, a few ticks apart
+                Profile::WebServer => {
+                    let script = Script::looping(vec![Phase::Cpu(cpu_burst), Phase::Io(io_burst, Some(String::from(WEB_DEVICE)))]);
+                    let arrival = Arrival::Poisson {
+                        start: rng.gen_range(0..=2000),
+                        mean_gap: rng.gen_range(1..=5) as f64,
+                        count: rng.gen_range(5..=20),
+                    };
+                    TaskChar::scripted(id, cpu_time, script, weight).with_arrival(arrival)
+                }
+                _ => TaskChar::stochastic(id, cpu_time, cpu_burst, io_burst, weight).with_arrival(Arrival::At(rng.gen_range(0..=shape.arrivals))),
+            };
+            workload.tasks.push(WorkloadTask {
+                task,
+                name: Some(format!("{}-{}", shape.name, id)),
+                policy: shape.policy,
+                affinity: None,
+                group: None,
+                line: 0,
+            });
+        }
+        Ok(workload)
+    }
+}
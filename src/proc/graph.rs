@@ -0,0 +1,165 @@
+use super::task::TaskChar;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// Error returned when the dependencies of a workload do not form a valid DAG
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    // A task depends on an id that is not part of the workload
+    UnknownTask { task: u16, dep: u16 },
+    // The tasks on the path depend on each other in a cycle
+    Cycle(Vec<u16>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::UnknownTask { task, dep } => {
+                write!(f, "task {} depends on unknown task {}", task, dep)
+            }
+            GraphError::Cycle(path) => {
+                let ids: Vec<String> = path.iter().map(|id| id.to_string()).collect();
+                write!(f, "dependency cycle between tasks {}", ids.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+// Dependency graph between the tasks of a workload
+pub struct DepGraph {
+    // Predecessors of every task, by task id
+    deps: HashMap<u16, Vec<u16>>,
+    // Task ids in an order where every task comes after its predecessors
+    order: Vec<u16>,
+}
+
+impl DepGraph {
+    // Builds the dependency graph of a workload, checking that it is acyclic and closed
+    pub fn new(tasks: &[TaskChar]) -> Result<Self, GraphError> {
+        let deps: HashMap<u16, Vec<u16>> = tasks
+            .iter()
+            .map(|task| (task.get_id(), task.get_deps().to_vec()))
+            .collect();
+
+        // Check every dependency names a task of the workload
+        for task in tasks {
+            for dep in task.get_deps() {
+                if !deps.contains_key(dep) {
+                    return Err(GraphError::UnknownTask { task: task.get_id(), dep: *dep });
+                }
+            }
+        }
+
+        // Depth-first topological sort, tracking the current path to report cycles
+        let mut order = Vec::new();
+        let mut done = HashMap::new();
+        for task in tasks {
+            let mut path = Vec::new();
+            Self::visit(task.get_id(), &deps, &mut done, &mut path, &mut order)?;
+        }
+
+        Ok(Self { deps, order })
+    }
+
+    // Visits a task after all of its predecessors
+    fn visit(
+        id: u16,
+        deps: &HashMap<u16, Vec<u16>>,
+        done: &mut HashMap<u16, bool>,
+        path: &mut Vec<u16>,
+        order: &mut Vec<u16>,
+    ) -> Result<(), GraphError> {
+        match done.get(&id) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                let start = path.iter().position(|other| *other == id).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(id);
+                return Err(GraphError::Cycle(cycle));
+            }
+            None => {}
+        }
+
+        done.insert(id, false);
+        path.push(id);
+        for dep in &deps[&id] {
+            Self::visit(*dep, deps, done, path, order)?;
+        }
+        path.pop();
+        done.insert(id, true);
+        order.push(id);
+
+        Ok(())
+    }
+
+    // Returns whether any task of the workload has dependencies
+    pub fn has_edges(&self) -> bool {
+        self.deps.values().any(|deps| !deps.is_empty())
+    }
+
+    // Returns the predecessors of a task
+    pub fn deps(&self, id: u16) -> &[u16] {
+        self.deps.get(&id).map_or(&[], |deps| &deps[..])
+    }
+
+    // Computes the makespan and critical path of a finished run from the time every task was
+    // released to the run queue and the time it terminated
+    pub fn report(&self, times: &HashMap<u16, (u128, u128)>) -> DagReport {
+        let first = times.values().map(|time| time.0).min().unwrap_or(0);
+        let last = times.values().map(|time| time.1).max().unwrap_or(0);
+
+        // Longest chain of durations ending at every task, with the predecessor it goes through
+        let mut longest: HashMap<u16, (u128, Option<u16>)> = HashMap::new();
+        for id in &self.order {
+            let duration = times.get(id).map_or(0, |(released, finished)| finished - released);
+            let best = self
+                .deps(*id)
+                .iter()
+                .map(|dep| (longest[dep].0, *dep))
+                .max();
+            let entry = match best {
+                Some((length, dep)) => (length + duration, Some(dep)),
+                None => (duration, None),
+            };
+            longest.insert(*id, entry);
+        }
+
+        // Walk back from the end of the longest chain
+        let mut critical_path = Vec::new();
+        let mut curr = longest.iter().max_by_key(|(id, entry)| (entry.0, **id)).map(|(id, _)| *id);
+        while let Some(id) = curr {
+            critical_path.push(id);
+            curr = longest[&id].1;
+        }
+        critical_path.reverse();
+
+        DagReport {
+            makespan: last - first,
+            critical_length: critical_path.last().map_or(0, |id| longest[id].0),
+            critical_path,
+        }
+    }
+}
+
+// Summary of a DAG workload once every task has terminated
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DagReport {
+    // Ticks between the first release and the last termination
+    pub makespan: u128,
+    // Chain of dependent tasks with the largest total time from release to termination
+    pub critical_path: Vec<u16>,
+    // Total time from release to termination along the critical path
+    pub critical_length: u128,
+}
+
+impl fmt::Display for DagReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ids: Vec<String> = self.critical_path.iter().map(|id| id.to_string()).collect();
+        writeln!(f, "Makespan: {} ticks", self.makespan)?;
+        write!(f, "Critical path: {} ({} ticks)", ids.join(" -> "), self.critical_length)
+    }
+}
@@ -0,0 +1,194 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+// Number of sub-buckets every power of two is split into, as a power of two. Values below
+// twice that many sub-buckets get a bucket each, so small latencies are recorded exactly and
+// larger ones to within 1/8 of their value.
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const LINEAR: u64 = SUB_BUCKETS * 2;
+
+// A histogram of durations in ticks with log-linear buckets, like HdrHistogram: exact buckets
+// for small values, then every power of two split into the same number of equal buckets.
+// Histograms all share the same bucket layout, so they can be merged across tasks and runs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    // Number of values recorded in every bucket, up to the highest non-empty bucket
+    counts: Vec<u64>,
+    // Number, sum, smallest and largest of the values recorded
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+// A bucket of a histogram with the range of values it covers, both bounds included
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Bucket {
+    pub low: u64,
+    pub high: u64,
+    pub count: u64,
+}
+
+impl Histogram {
+    // Creates an empty histogram
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the index of the bucket a value falls in
+    fn index(value: u64) -> usize {
+        if value < LINEAR {
+            return value as usize;
+        }
+        let octave = 63 - value.leading_zeros();
+        let sub = (value >> (octave - SUB_BUCKET_BITS)) & (SUB_BUCKETS - 1);
+        (LINEAR + u64::from(octave - SUB_BUCKET_BITS - 1) * SUB_BUCKETS + sub) as usize
+    }
+
+    // Returns the range of values of a bucket
+    fn range(index: usize) -> (u64, u64) {
+        let index = index as u64;
+        if index < LINEAR {
+            return (index, index);
+        }
+        let octave = (index - LINEAR) / SUB_BUCKETS + u64::from(SUB_BUCKET_BITS) + 1;
+        let width = 1u64 << (octave - u64::from(SUB_BUCKET_BITS));
+        let low = (1u64 << octave) + (index - LINEAR) % SUB_BUCKETS * width;
+        (low, low + (width - 1))
+    }
+
+    // Records a value
+    pub fn record(&mut self, value: u64) {
+        let index = Self::index(value);
+        if index >= self.counts.len() {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+        self.min = if self.count == 0 { value } else { self.min.min(value) };
+        self.max = self.max.max(value);
+        self.count += 1;
+        self.sum += u128::from(value);
+    }
+
+    // Adds every value recorded in another histogram
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.count == 0 {
+            return;
+        }
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+        self.min = if self.count == 0 { other.min } else { self.min.min(other.min) };
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+
+    // Returns the number of values recorded
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Returns whether no value was recorded
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Returns the smallest value recorded, or 0
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    // Returns the largest value recorded, or 0
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    // Returns the mean of the values recorded, or 0
+    pub fn mean(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => self.sum as f64 / count as f64,
+        }
+    }
+
+    // Returns the value below or at which the given percentage of the values fall, to within
+    // the width of its bucket, or 0 for an empty histogram
+    pub fn percentile(&self, p: f64) -> u64 {
+        let rank = ((p / 100.0 * self.count as f64).ceil() as u64).clamp(1, self.count.max(1));
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::range(index).1.min(self.max);
+            }
+        }
+        0
+    }
+
+    // Returns the non-empty buckets in order of value
+    pub fn buckets(&self) -> Vec<Bucket> {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| {
+                let (low, high) = Self::range(index);
+                Bucket { low, high, count: *count }
+            })
+            .collect()
+    }
+
+    // Draws the histogram in the style of BCC's runqlat, with a bar scaled to the largest bucket
+    pub fn ascii(&self, unit: &str) -> String {
+        let buckets = self.buckets();
+        let largest = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(0);
+        let mut lines = vec![format!("{:>10} {:<12} : {:<8} distribution", unit, "", "count")];
+        for bucket in buckets {
+            let width = (bucket.count * 40).div_ceil(largest) as usize;
+            let range = format!("{} -> {}", bucket.low, bucket.high);
+            lines.push(format!("{:>23} : {:<8} |{:<40}|", range, bucket.count, "*".repeat(width)));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Serialize for Histogram {
+    // Exports the summary of the histogram along with its non-empty buckets
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Histogram", 8)?;
+        state.serialize_field("count", &self.count)?;
+        state.serialize_field("min", &self.min)?;
+        state.serialize_field("max", &self.max)?;
+        state.serialize_field("mean", &self.mean())?;
+        state.serialize_field("p50", &self.percentile(50.0))?;
+        state.serialize_field("p95", &self.percentile(95.0))?;
+        state.serialize_field("p99", &self.percentile(99.0))?;
+        state.serialize_field("buckets", &self.buckets())?;
+        state.end()
+    }
+}
+
+// Histograms of the scheduling latencies of a task or of a whole run
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Latencies {
+    // Ticks from becoming runnable after arriving or blocking to running
+    pub wakeup: Histogram,
+    // Ticks waited on the run queue before every slice, whether after a wakeup or a preemption
+    pub wait: Histogram,
+    // Ticks run in a row before leaving the CPU or being preempted
+    pub slice: Histogram,
+}
+
+impl Latencies {
+    // Adds every value recorded in other latency histograms
+    pub fn merge(&mut self, other: &Latencies) {
+        self.wakeup.merge(&other.wakeup);
+        self.wait.merge(&other.wait);
+        self.slice.merge(&other.slice);
+    }
+}
@@ -1,2 +1,12 @@
 pub mod task;
 pub mod queue;
+pub mod script;
+pub mod dist;
+pub mod graph;
+pub mod arrival;
+pub mod stats;
+pub mod histogram;
+pub mod replay;
+pub mod workload;
+pub mod validate;
+pub mod generator;
@@ -1,34 +1,45 @@
-use super::task::{Task, TaskStatus};
-use std::collections::HashMap;
+use super::task::{BlockReason, Task, TaskStatus};
+use std::collections::{HashMap, HashSet};
 
 pub struct TaskQueue {
     tasks: HashMap<u128, Vec<Task>>,
+    // tasks waiting for their dependencies to terminate
+    blocked: Vec<Task>,
+    // ids of the tasks that have terminated so far
+    terminated: HashSet<u16>,
 }
 
 impl TaskQueue {
     // constructor to create a new instance of TaskQueue
     pub fn new() -> Self {
-        Self { tasks: HashMap::new() }
+        Self { tasks: HashMap::new(), blocked: Vec::new(), terminated: HashSet::new() }
     }
 
     // method to add a task to the task queue
-    pub fn add(&mut self, task: Task) {
-        // if the task is already terminated or idle, return from the function
-        if task.get_status() == TaskStatus::Idle || task.get_status() == TaskStatus::Terminated {
+    pub fn add(&mut self, mut task: Task) {
+        // if the task has already exited or is blocked on I/O, return from the function
+        if task.get_status().has_exited() || task.get_status() == (TaskStatus::Blocked { reason: BlockReason::Io }) {
+            return;
+        }
+
+        // if some of the task's dependencies have not terminated yet, hold the task back until they do
+        if task.get_deps().iter().any(|dep| !self.terminated.contains(dep)) {
+            if task.get_status() == TaskStatus::New {
+                // a new task can always block, so this cannot fail
+                let _ = task.block(BlockReason::Dependencies);
+                let arrival = task.get_start_time();
+                task.stats_mut().blocked_at(arrival, false);
+            }
+            self.blocked.push(task);
             return;
         }
         
         // get the start time of the task
         let start_time = task.get_start_time();
         
-        // if the start time is not already a key in the hashmap, insert the start time as key and an empty vector as its value
-        if !self.tasks.contains_key(&start_time) {
-            self.tasks.insert(start_time, Vec::new());
-        }
-        
-        // add the task to the vector corresponding to the start time key
-        self.tasks.get_mut(&start_time)
-            .unwrap()
+        // add the task to the vector corresponding to the start time key, inserting an empty vector if the key is new
+        self.tasks.entry(start_time)
+            .or_default()
             .push(task);
     }
 
@@ -36,14 +47,14 @@ impl TaskQueue {
     pub fn append(&mut self, tasks: &[Task]) {
         // add each task in the input vector to the task queue
         for task in tasks {
-            self.add(*task);
+            self.add(task.clone());
         }
     }
 
     // method to retrieve and remove the task with the earliest start time from the task queue
     pub fn pop(&mut self) -> Vec<Task> {
-        // if the task queue is empty, return an empty vector
-        if self.is_empty() {
+        // if no task is ready to be born, return an empty vector
+        if self.tasks.is_empty() {
             return Vec::new();
         }
         
@@ -58,22 +69,65 @@ impl TaskQueue {
         }
         
         // remove the vector of tasks corresponding to the smallest start time key and return it
-        self.tasks.remove(&smallest_key).unwrap()
+        self.tasks.remove(&smallest_key).unwrap_or_default()
     }
 
-    // method to retrieve and remove the task with a specified start time from the task queue
-    pub fn remove(&mut self, time: u128) -> Vec<Task> {
-        // if the task queue is empty, return an empty vector
-        if self.is_empty() {
-            return Vec::new();
+    // method to retrieve and remove every task whose start time is at or before the given time, earliest first
+    pub fn pop_until(&mut self, time: u128) -> Vec<Task> {
+        // collect the due start times in order
+        let mut due: Vec<u128> = self.tasks.keys().filter(|key| **key <= time).copied().collect();
+        due.sort_unstable();
+
+        // remove the vectors of tasks corresponding to the due start times and concatenate them
+        let mut tasks = Vec::new();
+        for key in due {
+            tasks.extend(self.tasks.remove(&key).unwrap_or_default());
         }
+        tasks
+    }
 
+    // method to retrieve and remove the tasks with a specified start time from the task queue,
+    // or None if no task starts at that time
+    pub fn remove(&mut self, time: u128) -> Option<Vec<Task>> {
         // remove the vector of tasks corresponding to the specified start time key and return it
-        self.tasks.remove(&time).unwrap()
+        self.tasks.remove(&time)
+    }
+
+    // method to record that a task terminated at the given time, releasing the tasks that only waited for it
+    pub fn complete(&mut self, id: u16, time: u128) {
+        self.terminated.insert(id);
+
+        // split the blocked tasks into those whose dependencies have all terminated and those still waiting
+        let (ready, blocked): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.blocked)
+            .into_iter()
+            .partition(|task| task.get_deps().iter().all(|dep| self.terminated.contains(dep)));
+        self.blocked = blocked;
+
+        // the released tasks are born now and queued under the current time
+        for mut task in ready {
+            task.release(time);
+            self.add(task);
+        }
+    }
+
+    // method to retrieve the tasks still waiting for their dependencies
+    pub fn blocked(&self) -> &[Task] {
+        &self.blocked
     }
 
     // method to check if the task queue is empty
     pub fn is_empty(&self) -> bool {
-        self.tasks.is_empty()
+        self.tasks.is_empty() && self.blocked.is_empty()
+    }
+
+    // method to check if the only tasks left in the queue are waiting for their dependencies
+    pub fn is_stalled(&self) -> bool {
+        self.tasks.is_empty() && !self.blocked.is_empty()
+    }
+}
+
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,430 @@
+use super::arrival::Arrival;
+use super::dist::Dist;
+use super::script::{Phase, Script};
+use super::stats::TaskStats;
+use super::task::{TaskChar, NICE_TO_WEIGHT};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+// An error in a kernel trace, with the number of the line it is on (from 1), or 0 for the whole trace
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseTraceError {
+    line: usize,
+    reason: String,
+}
+
+impl ParseTraceError {
+    // Returns the number of the line the error is on, or 0 if it is about the whole trace
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "invalid trace: {}", self.reason),
+            line => write!(f, "invalid trace at line {}: {}", line, self.reason),
+        }
+    }
+}
+
+impl Error for ParseTraceError {}
+
+// A thread of a kernel trace turned into a task of the simulator
+#[derive(Clone, Debug)]
+pub struct ReplayedTask {
+    // Pid and command name of the thread in the trace
+    pub pid: u32,
+    pub comm: String,
+    // The task replaying the thread, numbered from 1 in order of arrival
+    pub task: TaskChar,
+    // What the real kernel did with the thread, in the same ticks as the task
+    pub observed: TaskStats,
+}
+
+// A sched event of a kernel trace, at a time in nanoseconds
+#[derive(Debug)]
+enum SchedEvent {
+    Switch { prev: (u32, String, i16), state: char, next: (u32, String, i16) },
+    Wakeup { task: (u32, String, i16) },
+    Fork { child: (u32, String) },
+    Exit { pid: u32 },
+}
+
+// What is known of a thread while the trace is read
+#[derive(Default)]
+struct Thread {
+    comm: String,
+    prio: i16,
+    arrival: u128,
+    phases: Vec<Phase>,
+    // Ticks run since the thread last blocked
+    burst: u64,
+    // Tick the thread has been running since, while it is on a CPU
+    running_since: Option<u128>,
+    // Tick the thread has been runnable since, while it waits for a CPU
+    runnable_since: Option<u128>,
+    // Tick the thread blocked at and whether it waits for I/O, while it is blocked
+    blocked: Option<(u128, bool)>,
+    // Whether the thread woke up rather than was preempted since it last ran
+    woken: bool,
+    stats: TaskStats,
+}
+
+impl Thread {
+    // Creates a thread first seen at the given tick, runnable from then on
+    fn new(comm: &str, prio: i16, arrival: u128) -> Self {
+        Self {
+            comm: String::from(comm),
+            prio,
+            arrival,
+            runnable_since: Some(arrival),
+            woken: true,
+            stats: TaskStats::new(0, arrival),
+            ..Self::default()
+        }
+    }
+
+    // Ends the CPU burst of the thread as it blocks or exits
+    fn end_burst(&mut self) {
+        if self.burst > 0 {
+            self.phases.push(Phase::Cpu(Dist::Constant(std::mem::take(&mut self.burst))));
+        }
+    }
+
+    // Ends the blocking of the thread at the given tick, as it wakes or runs
+    fn wake(&mut self, now: u128) {
+        if let Some((since, io)) = self.blocked.take() {
+            let gap = now.saturating_sub(since) as u64;
+            match io {
+                true => self.stats.io_wait += gap,
+                false => self.stats.sleep += gap,
+            }
+            if gap > 0 {
+                self.phases.push(match io {
+                    true => Phase::Io(Dist::Constant(gap), None),
+                    false => Phase::Sleep(Dist::Constant(gap)),
+                });
+            }
+            self.runnable_since = Some(now);
+            self.woken = true;
+        }
+    }
+}
+
+// Reconstructs the workload of a kernel trace: the arrival of every thread, its CPU bursts, and the
+// gaps it spent blocked in between, as sleeps or (for uninterruptible sleeps) I/Os. It reads the
+// text of ftrace, `trace-cmd report` and `perf script` dumps of the sched_switch, sched_wakeup,
+// sched_wakeup_new, sched_process_fork and sched_process_exit events, in either the raw field format
+// or trace-cmd's shorter one, and ignores every other line.
+#[derive(Clone, Copy, Debug)]
+pub struct Replay {
+    // Length of a tick in nanoseconds
+    tick: u128,
+}
+
+impl Replay {
+    // Creates an importer turning every microsecond of the trace into a tick
+    pub fn new() -> Self {
+        Self { tick: 1000 }
+    }
+
+    // Sets the length of a tick in nanoseconds
+    pub fn with_tick(mut self, nanos: u128) -> Self {
+        self.tick = nanos.max(1);
+        self
+    }
+
+    // Imports the threads of a trace that ran at least once, in order of arrival
+    pub fn import(&self, trace: &str) -> Result<Vec<ReplayedTask>, ParseTraceError> {
+        let mut threads: BTreeMap<u32, Thread> = BTreeMap::new();
+        let mut start = None;
+        let mut end = 0;
+        let mut switches = 0;
+
+        // Events are replayed in order of time, as the buffers of several CPUs may be dumped one after
+        // the other or interleaved out of order; events at the same time keep the order of the trace
+        let mut events = Vec::new();
+        for (idx, line) in trace.lines().enumerate() {
+            if let Some(parsed) = parse_line(line).map_err(|reason| ParseTraceError { line: idx + 1, reason })? {
+                events.push(parsed);
+            }
+        }
+        events.sort_by_key(|(time, _)| *time);
+
+        for (time, event) in events {
+            // Ticks count from the first event, rounded to the nearest tick
+            let start = *start.get_or_insert(time);
+            let now = (time.saturating_sub(start) + self.tick / 2) / self.tick;
+            end = end.max(now);
+
+            match event {
+                SchedEvent::Switch { prev, state, next } => {
+                    switches += 1;
+                    if prev.0 != 0 {
+                        // A thread running when the trace starts has been running since its first tick
+                        let thread = threads.entry(prev.0).or_insert_with(|| {
+                            let mut thread = Thread::new(&prev.1, prev.2, 0);
+                            thread.runnable_since = None;
+                            thread.running_since = Some(0);
+                            thread.stats.first_run = Some(0);
+                            thread
+                        });
+                        thread.prio = prev.2;
+                        if let Some(since) = thread.running_since.take() {
+                            let ran = now.saturating_sub(since) as u64;
+                            thread.burst += ran;
+                            thread.stats.runtime += ran;
+                            thread.stats.latency.slice.record(ran);
+                        }
+                        match state {
+                            'R' => {
+                                thread.stats.involuntary_switches += 1;
+                                thread.runnable_since = Some(now);
+                            }
+                            'Z' | 'X' => {
+                                thread.stats.voluntary_switches += 1;
+                                thread.end_burst();
+                                thread.stats.completion = Some(now);
+                            }
+                            state => {
+                                thread.stats.voluntary_switches += 1;
+                                thread.end_burst();
+                                thread.blocked = Some((now, state == 'D'));
+                            }
+                        }
+                    }
+                    if next.0 != 0 {
+                        let thread = threads.entry(next.0).or_insert_with(|| Thread::new(&next.1, next.2, now));
+                        thread.prio = next.2;
+                        // A thread whose wakeup is not in the trace is woken as it runs
+                        thread.wake(now);
+                        let waited = now.saturating_sub(thread.runnable_since.take().unwrap_or(now)) as u64;
+                        thread.stats.wait += waited;
+                        thread.stats.max_wait = thread.stats.max_wait.max(waited);
+                        thread.stats.latency.wait.record(waited);
+                        if std::mem::take(&mut thread.woken) {
+                            thread.stats.latency.wakeup.record(waited);
+                        }
+                        thread.stats.first_run.get_or_insert(now);
+                        thread.running_since = Some(now);
+                    }
+                }
+                SchedEvent::Wakeup { task: (pid, comm, prio) } if pid != 0 => {
+                    let thread = threads.entry(pid).or_insert_with(|| Thread::new(&comm, prio, now));
+                    thread.wake(now);
+                }
+                SchedEvent::Fork { child: (pid, comm) } => {
+                    threads.entry(pid).or_insert_with(|| Thread::new(&comm, 120, now));
+                }
+                // A thread killed off the CPU exits where it is
+                SchedEvent::Exit { pid } => {
+                    if let Some(thread) = threads.get_mut(&pid) {
+                        if thread.running_since.is_none() && thread.stats.completion.is_none() {
+                            thread.end_burst();
+                            thread.blocked = None;
+                            thread.stats.completion = Some(now);
+                        }
+                    }
+                }
+                SchedEvent::Wakeup { .. } => {}
+            }
+        }
+        if switches == 0 {
+            return Err(ParseTraceError { line: 0, reason: String::from("no sched_switch events found") });
+        }
+
+        // Threads still running when the trace ends are cut short
+        let mut replayed: Vec<(u32, Thread)> = threads
+            .into_iter()
+            .filter_map(|(pid, mut thread)| {
+                if let Some(since) = thread.running_since.take() {
+                    let ran = end.saturating_sub(since) as u64;
+                    thread.burst += ran;
+                    thread.stats.runtime += ran;
+                }
+                thread.end_burst();
+                match thread.stats.runtime > 0 || thread.phases.iter().any(|phase| matches!(phase, Phase::Cpu(_))) {
+                    true => Some((pid, thread)),
+                    false => None,
+                }
+            })
+            .collect();
+        replayed.sort_by_key(|(pid, thread)| (thread.arrival, *pid));
+
+        replayed
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (pid, thread))| {
+                let id = u16::try_from(idx + 1)
+                    .map_err(|_| ParseTraceError { line: 0, reason: format!("more than {} threads ran, one for every task id", u16::MAX) })?;
+                let nice = (thread.prio - 120).clamp(-20, 19);
+                let weight = NICE_TO_WEIGHT[(nice + 20) as usize];
+                let cpu_time = thread
+                    .phases
+                    .iter()
+                    .map(|phase| match phase {
+                        Phase::Cpu(Dist::Constant(length)) => *length,
+                        _ => 0,
+                    })
+                    .sum::<u64>()
+                    .max(1);
+                let mut observed = thread.stats;
+                observed.id = id;
+                observed.weight = weight;
+                Ok(ReplayedTask {
+                    pid,
+                    comm: thread.comm,
+                    task: TaskChar::scripted(id, cpu_time, Script::new(thread.phases), weight)
+                        .with_arrival(Arrival::At(thread.arrival)),
+                    observed,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Writes replayed tasks as lines of a tasks file, in the `cpu_time weight : script at tick` format
+pub fn tasks_file(tasks: &[ReplayedTask]) -> String {
+    tasks
+        .iter()
+        .map(|replayed| {
+            let task = &replayed.task;
+            format!(
+                "{} {} : {} at {}\n",
+                task.get_cpu_time(),
+                task.get_weight(),
+                task.get_script(),
+                task.get_arrival()
+            )
+        })
+        .collect()
+}
+
+// Parses a line of a trace into the time of its sched event in nanoseconds and the event, or
+// returns None for lines that are not sched events, such as headers and other events
+fn parse_line(line: &str) -> Result<Option<(u128, SchedEvent)>, String> {
+    // The timestamp is the first word of the form "seconds.fraction:", followed by the event name
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let idx = match words.iter().position(|word| parse_timestamp(word).is_some()) {
+        Some(idx) if idx + 1 < words.len() => idx,
+        _ => return Ok(None),
+    };
+    let time = parse_timestamp(words[idx]).unwrap_or(0);
+    let name = words[idx + 1].trim_end_matches(':');
+    let name = name.strip_prefix("sched:").unwrap_or(name);
+    let fields = &words[idx + 2..];
+
+    let event = match name {
+        "sched_switch" => {
+            let (prev, next) = match fields.iter().position(|word| *word == "==>") {
+                Some(arrow) => (&fields[..arrow], &fields[arrow + 1..]),
+                None => return Err(String::from("sched_switch without a `==>`")),
+            };
+            let (prev_task, state) = match key_values(prev) {
+                Some(values) => (task_of(&values, "prev_")?, values.get("prev_state").cloned().unwrap_or_default()),
+                None => short_task(prev)?,
+            };
+            let next_task = match key_values(next) {
+                Some(values) => task_of(&values, "next_")?,
+                None => short_task(next)?.0,
+            };
+            // "R+" is a preempted task, and a task may be in several states at once like "S|D"
+            let state = state.chars().next().unwrap_or('R');
+            SchedEvent::Switch { prev: prev_task, state, next: next_task }
+        }
+        "sched_wakeup" | "sched_wakeup_new" => match key_values(fields) {
+            Some(values) => SchedEvent::Wakeup { task: task_of(&values, "")? },
+            None => SchedEvent::Wakeup { task: short_task(fields)?.0 },
+        },
+        "sched_process_fork" => match key_values(fields) {
+            Some(values) => {
+                let pid = number(&values, "child_pid")?;
+                SchedEvent::Fork { child: (pid, values.get("child_comm").cloned().unwrap_or_default()) }
+            }
+            None => return Ok(None),
+        },
+        "sched_process_exit" => match key_values(fields) {
+            Some(values) => SchedEvent::Exit { pid: number(&values, "pid")? },
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some((time, event)))
+}
+
+// Parses a timestamp of the form "seconds.fraction:" into nanoseconds
+fn parse_timestamp(word: &str) -> Option<u128> {
+    let (secs, fraction) = word.strip_suffix(':')?.split_once('.')?;
+    if secs.is_empty() || fraction.is_empty() || fraction.len() > 9 {
+        return None;
+    }
+    if !secs.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = format!("{:0<9}", fraction).parse::<u128>().ok()?;
+    Some(secs.parse::<u128>().ok()? * 1_000_000_000 + nanos)
+}
+
+// Collects the "key=value" fields of an event, with words without a key belonging to the value
+// before them, as command names may contain spaces. Returns None if there are no such fields.
+fn key_values(words: &[&str]) -> Option<BTreeMap<String, String>> {
+    let mut values: BTreeMap<String, String> = BTreeMap::new();
+    let mut last: Option<String> = None;
+    for word in words {
+        match word.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                values.insert(String::from(key), String::from(value));
+                last = Some(String::from(key));
+            }
+            _ => {
+                if let Some(value) = last.as_ref().and_then(|key| values.get_mut(key)) {
+                    value.push(' ');
+                    value.push_str(word);
+                }
+            }
+        }
+    }
+    match values.is_empty() {
+        true => None,
+        false => Some(values),
+    }
+}
+
+// Returns a number field of an event
+fn number(values: &BTreeMap<String, String>, key: &str) -> Result<u32, String> {
+    match values.get(key).map(|value| value.parse::<u32>()) {
+        Some(Ok(number)) => Ok(number),
+        Some(Err(_)) => Err(format!("invalid {} `{}`", key, values[key])),
+        None => Err(format!("missing {}", key)),
+    }
+}
+
+// Returns the pid, command name and priority of a task from the fields with the given prefix
+fn task_of(values: &BTreeMap<String, String>, prefix: &str) -> Result<(u32, String, i16), String> {
+    let pid = number(values, &format!("{}pid", prefix))?;
+    let comm = values.get(&format!("{}comm", prefix)).cloned().unwrap_or_default();
+    let prio = values.get(&format!("{}prio", prefix)).and_then(|prio| prio.parse::<i16>().ok()).unwrap_or(120);
+    Ok((pid, comm, prio))
+}
+
+// Parses a task in trace-cmd's short format "comm:pid [prio] state", returning it with its state
+fn short_task(words: &[&str]) -> Result<((u32, String, i16), String), String> {
+    let first = words.first().ok_or_else(|| String::from("missing task"))?;
+    let (comm, pid) = first.rsplit_once(':').ok_or_else(|| format!("invalid task `{}`", first))?;
+    let pid = pid.parse::<u32>().map_err(|_| format!("invalid pid `{}`", pid))?;
+    let prio = words
+        .get(1)
+        .and_then(|prio| prio.strip_prefix('[')?.strip_suffix(']')?.parse::<i16>().ok())
+        .unwrap_or(120);
+    let state = words.get(2).map(|state| String::from(*state)).unwrap_or_default();
+    Ok(((pid, String::from(comm), prio), state))
+}
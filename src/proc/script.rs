@@ -0,0 +1,407 @@
+use super::dist::Dist;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// A single step in the behaviour script of a task
+#[derive(Clone, Debug, PartialEq)]
+pub enum Phase {
+    // Run on the CPU for a number of ticks drawn from the distribution
+    Cpu(Dist),
+    // Wait for an I/O burst with a length drawn from the distribution, optionally on a named device
+    Io(Dist, Option<String>),
+    // Like an I/O burst, but writing rather than reading, which I/O schedulers may treat differently
+    Write(Dist, Option<String>),
+    // Sleep for a number of ticks drawn from the distribution
+    Sleep(Dist),
+    // Sleep until the given tick, or not at all if it has passed
+    SleepUntil(u128),
+    // Change the nice value of the task
+    Nice(i8),
+    // Fork a child task running the given script
+    Fork(Script),
+    // Operate on a simulated synchronisation primitive, possibly blocking
+    Sync(SyncOp),
+}
+
+// An operation on a named synchronisation primitive
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncOp {
+    // Acquire a mutex, blocking while another task holds it
+    Lock(String),
+    // Release a mutex held by the task
+    Unlock(String),
+    // Decrement a semaphore, blocking while its count is zero
+    Down(String),
+    // Increment a semaphore or hand it to the first task waiting on it
+    Up(String),
+    // Wait on a wait queue, releasing the mutex (if any) meanwhile and reacquiring it once woken
+    Wait(String, Option<String>),
+    // Wake the first task waiting on a wait queue
+    Signal(String),
+    // Wake every task waiting on a wait queue
+    Broadcast(String),
+    // Send a message on a channel, waking the first task waiting to receive
+    Send(String),
+    // Receive a message from a channel, blocking while it is empty
+    Recv(String),
+}
+
+impl fmt::Display for SyncOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncOp::Lock(name) => write!(f, "lock {}", name),
+            SyncOp::Unlock(name) => write!(f, "unlock {}", name),
+            SyncOp::Down(name) => write!(f, "down {}", name),
+            SyncOp::Up(name) => write!(f, "up {}", name),
+            SyncOp::Wait(queue, None) => write!(f, "wait {}", queue),
+            SyncOp::Wait(queue, Some(mutex)) => write!(f, "wait {} {}", queue, mutex),
+            SyncOp::Signal(name) => write!(f, "signal {}", name),
+            SyncOp::Broadcast(name) => write!(f, "broadcast {}", name),
+            SyncOp::Send(name) => write!(f, "send {}", name),
+            SyncOp::Recv(name) => write!(f, "recv {}", name),
+        }
+    }
+}
+
+impl Phase {
+    // Returns whether the phase takes at least one tick to complete
+    pub fn is_timed(&self) -> bool {
+        match self {
+            Phase::Cpu(length) => !length.is_zero(),
+            Phase::Io(..) | Phase::Write(..) | Phase::Sleep(_) => true,
+            _ => false,
+        }
+    }
+}
+
+// The behaviour of a task, described as a sequence of phases optionally ending in a repeating loop
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    // Phases of the script in execution order
+    phases: Vec<Phase>,
+    // Index of the first phase of the repeating tail, if the script loops
+    loop_start: Option<usize>,
+}
+
+impl Script {
+    // Creates a script that runs its phases once
+    pub fn new(phases: Vec<Phase>) -> Self {
+        Self { phases, loop_start: None }
+    }
+
+    // Creates a script that runs its phases in an endless loop
+    pub fn looping(phases: Vec<Phase>) -> Self {
+        Self { phases, loop_start: Some(0) }
+    }
+
+    // Creates a script that runs the prefix once and then loops over the tail
+    pub fn with_loop(mut prefix: Vec<Phase>, mut tail: Vec<Phase>) -> Self {
+        let loop_start = prefix.len();
+        prefix.append(&mut tail);
+
+        Self { phases: prefix, loop_start: Some(loop_start) }
+    }
+
+    // Creates the classic script of alternating CPU and I/O bursts
+    pub fn bursts(cpu_burst_length: impl Into<Dist>, io_burst_length: impl Into<Dist>) -> Self {
+        Self::looping(vec![Phase::Cpu(cpu_burst_length.into()), Phase::Io(io_burst_length.into(), None)])
+    }
+
+    // Returns the phases of the script
+    #[inline]
+    pub fn phases(&self) -> &[Phase] { &self.phases }
+
+    // Returns the index of the first looping phase, if any
+    #[inline]
+    pub fn loop_start(&self) -> Option<usize> { self.loop_start }
+
+    // Returns the phase at the given index
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<&Phase> { self.phases.get(idx) }
+
+    // Returns the number of phases in the script
+    #[inline]
+    pub fn len(&self) -> usize { self.phases.len() }
+
+    // Returns whether the script has no phases
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.phases.is_empty() }
+
+    // Returns the index of the phase executed after the given one, or None when the script ends
+    pub fn next(&self, idx: usize) -> Option<usize> {
+        if idx + 1 < self.phases.len() {
+            Some(idx + 1)
+        } else {
+            self.loop_start.filter(|start| *start < self.phases.len())
+        }
+    }
+}
+
+// Writes a list of phases in the textual script syntax
+fn write_phases(f: &mut fmt::Formatter, phases: &[Phase]) -> fmt::Result {
+    for (idx, phase) in phases.iter().enumerate() {
+        if idx > 0 {
+            write!(f, "; ")?;
+        }
+        match phase {
+            Phase::Cpu(length) => write!(f, "cpu {}", length)?,
+            Phase::Io(length, None) => write!(f, "io {}", length)?,
+            Phase::Io(length, Some(device)) => write!(f, "io {} {}", length, device)?,
+            Phase::Write(length, None) => write!(f, "write {}", length)?,
+            Phase::Write(length, Some(device)) => write!(f, "write {} {}", length, device)?,
+            Phase::Sleep(length) => write!(f, "sleep {}", length)?,
+            Phase::SleepUntil(time) => write!(f, "sleep until {}", time)?,
+            Phase::Nice(nice) => write!(f, "nice {}", nice)?,
+            Phase::Fork(script) => write!(f, "fork {{ {} }}", script)?,
+            Phase::Sync(op) => write!(f, "{}", op)?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.loop_start {
+            None => write_phases(f, &self.phases),
+            Some(0) => {
+                write!(f, "loop {{ ")?;
+                write_phases(f, &self.phases)?;
+                write!(f, " }}")
+            }
+            Some(start) => {
+                write_phases(f, &self.phases[..start])?;
+                write!(f, "; loop {{ ")?;
+                write_phases(f, &self.phases[start..])?;
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+// Error returned when a script cannot be parsed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseScriptError(String);
+
+impl fmt::Display for ParseScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid script: {}", self.0)
+    }
+}
+
+impl Error for ParseScriptError {}
+
+// Splits the input at the first occurrence of the separator outside parentheses, so the
+// arguments of distributions such as `hist(10:3,20:1)` are never split
+pub fn split_outside(input: &str, separator: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c == separator && depth == 0 => return Some((&input[..idx], &input[idx + c.len_utf8()..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Recursive descent parser over the tokens of a script
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    // Splits the input into words, braces and semicolons, keeping parenthesised
+    // distribution arguments such as `uniform(1, 5)` inside a single word
+    fn new(input: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        let mut depth = 0;
+        for (idx, c) in input.char_indices() {
+            let separator = depth == 0 && (c.is_whitespace() || matches!(c, '{' | '}' | ';'));
+            if separator {
+                if let Some(begin) = start.take() {
+                    tokens.push(&input[begin..idx]);
+                }
+                if !c.is_whitespace() {
+                    tokens.push(&input[idx..idx + 1]);
+                }
+                continue;
+            }
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            start.get_or_insert(idx);
+        }
+        if let Some(begin) = start {
+            tokens.push(&input[begin..]);
+        }
+
+        Self { tokens, pos: 0 }
+    }
+
+    // Returns the next token without consuming it
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    // Consumes and returns the next token
+    fn bump(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    // Consumes the expected token or fails
+    fn expect(&mut self, expected: &str) -> Result<(), ParseScriptError> {
+        match self.bump() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(ParseScriptError(format!("expected `{}`, found `{}`", expected, token))),
+            None => Err(ParseScriptError(format!("expected `{}`, found end of script", expected))),
+        }
+    }
+
+    // Parses a numeric argument of the given phase
+    fn number<T: FromStr>(&mut self, phase: &str) -> Result<T, ParseScriptError> {
+        match self.bump() {
+            Some(token) => token
+                .parse::<T>()
+                .map_err(|_| ParseScriptError(format!("`{}` expects a number, found `{}`", phase, token))),
+            None => Err(ParseScriptError(format!("`{}` expects a number", phase))),
+        }
+    }
+
+    // Parses the length distribution of the given phase
+    fn length(&mut self, phase: &str) -> Result<Dist, ParseScriptError> {
+        match self.bump() {
+            Some(token) => token
+                .parse::<Dist>()
+                .map_err(|e| ParseScriptError(format!("`{}` length: {}", phase, e))),
+            None => Err(ParseScriptError(format!("`{}` expects a length", phase))),
+        }
+    }
+
+    // Parses the name of the primitive the given phase operates on
+    fn name(&mut self, phase: &str) -> Result<String, ParseScriptError> {
+        match self.optional_name() {
+            Some(name) => Ok(name),
+            None => Err(ParseScriptError(format!("`{}` expects a name", phase))),
+        }
+    }
+
+    // Parses a name if the next token is one
+    fn optional_name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(name) if name != ";" && name != "}" && name != "{" => {
+                self.bump();
+                Some(String::from(name))
+            }
+            _ => None,
+        }
+    }
+
+    // Parses phases until the end of input or a closing brace
+    fn script(&mut self) -> Result<Script, ParseScriptError> {
+        let mut phases = Vec::new();
+        let mut tail = None;
+
+        while let Some(token) = self.peek() {
+            if token == "}" {
+                break;
+            }
+            if token == ";" {
+                self.bump();
+                continue;
+            }
+            if tail.is_some() {
+                return Err(ParseScriptError(String::from("`loop` must be the last phase of a script")));
+            }
+
+            self.bump();
+            match token {
+                "cpu" => phases.push(Phase::Cpu(self.length(token)?)),
+                "sleep" if self.peek() == Some("until") => {
+                    self.bump();
+                    phases.push(Phase::SleepUntil(self.number("sleep until")?));
+                }
+                "sleep" => phases.push(Phase::Sleep(self.length(token)?)),
+                "nice" => {
+                    let nice: i8 = self.number(token)?;
+                    if !(-20..=19).contains(&nice) {
+                        return Err(ParseScriptError(format!("nice value {} is outside -20..19", nice)));
+                    }
+                    phases.push(Phase::Nice(nice));
+                }
+                "io" => {
+                    let length = self.length(token)?;
+                    let device = self.optional_name();
+                    phases.push(Phase::Io(length, device));
+                }
+                "write" => {
+                    let length = self.length(token)?;
+                    let device = self.optional_name();
+                    phases.push(Phase::Write(length, device));
+                }
+                "lock" => phases.push(Phase::Sync(SyncOp::Lock(self.name(token)?))),
+                "unlock" => phases.push(Phase::Sync(SyncOp::Unlock(self.name(token)?))),
+                "down" => phases.push(Phase::Sync(SyncOp::Down(self.name(token)?))),
+                "up" => phases.push(Phase::Sync(SyncOp::Up(self.name(token)?))),
+                "signal" => phases.push(Phase::Sync(SyncOp::Signal(self.name(token)?))),
+                "broadcast" => phases.push(Phase::Sync(SyncOp::Broadcast(self.name(token)?))),
+                "send" => phases.push(Phase::Sync(SyncOp::Send(self.name(token)?))),
+                "recv" => phases.push(Phase::Sync(SyncOp::Recv(self.name(token)?))),
+                "wait" => {
+                    let queue = self.name(token)?;
+                    let mutex = self.optional_name();
+                    phases.push(Phase::Sync(SyncOp::Wait(queue, mutex)));
+                }
+                "fork" => {
+                    self.expect("{")?;
+                    let child = self.script()?;
+                    self.expect("}")?;
+                    phases.push(Phase::Fork(child));
+                }
+                "loop" => {
+                    self.expect("{")?;
+                    let body = self.script()?;
+                    self.expect("}")?;
+                    if body.loop_start.is_some() {
+                        return Err(ParseScriptError(String::from("loops cannot be nested")));
+                    }
+                    if !body.phases.iter().any(Phase::is_timed) {
+                        return Err(ParseScriptError(String::from("a loop needs at least one cpu, io or sleep phase")));
+                    }
+                    tail = Some(body.phases);
+                }
+                other => return Err(ParseScriptError(format!("unknown phase `{}`", other))),
+            }
+        }
+
+        Ok(match tail {
+            Some(tail) => Script::with_loop(phases, tail),
+            None => Script::new(phases),
+        })
+    }
+}
+
+impl FromStr for Script {
+    type Err = ParseScriptError;
+
+    // Parses a script such as `cpu 500; loop { cpu exp(5); io 40 disk0 }`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let script = parser.script()?;
+        if let Some(token) = parser.peek() {
+            return Err(ParseScriptError(format!("unexpected `{}`", token)));
+        }
+        if script.is_empty() {
+            return Err(ParseScriptError(String::from("script has no phases")));
+        }
+
+        Ok(script)
+    }
+}
@@ -0,0 +1,168 @@
+use super::histogram::Latencies;
+use serde::Serialize;
+
+// Scheduling statistics of a task, like the kernel's schedstats. Every tick from the task's
+// arrival to its completion is spent either running, waiting on the run queue, waiting for I/O or
+// sleeping (on a timer, dependencies or a synchronisation primitive), so the four times add up to
+// its lifetime.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TaskStats {
+    // Id of the task
+    pub id: u16,
+    // Weight of the task when it terminated, ignoring inheritance
+    pub weight: u32,
+    // Tick at which the task arrived
+    pub arrival: u128,
+    // Tick at which the task first ran
+    pub first_run: Option<u128>,
+    // Tick at which the task terminated, just after it last ran or finished blocking
+    pub completion: Option<u128>,
+    // Ticks spent on the CPU
+    pub runtime: u64,
+    // Ticks spent runnable on the run queue, waiting for the CPU
+    pub wait: u64,
+    // Ticks spent blocked on I/O, in uninterruptible sleep
+    pub io_wait: u64,
+    // Ticks spent sleeping on a timer or blocked on dependencies or a synchronisation primitive
+    pub sleep: u64,
+    // Times the task gave up the CPU to block, sleep or exit
+    pub voluntary_switches: u64,
+    // Times the task was preempted while it still wanted the CPU
+    pub involuntary_switches: u64,
+    // Times the task moved between CPUs. Never counted: the simulator has a single CPU, so this is
+    // always 0 and only kept for the schedstat layout of the exports.
+    pub migrations: u64,
+    // Longest single wait on the run queue
+    pub max_wait: u64,
+    // Histograms of the task's wakeup latencies, waits and slice lengths
+    pub latency: Latencies,
+    // Tick from which the task has been runnable, while it is on the run queue
+    #[serde(skip)]
+    runnable_since: Option<u128>,
+    // Tick from which the task has been blocked, while it is off the run queue, and whether it is doing I/O
+    #[serde(skip)]
+    blocked_since: Option<(u128, bool)>,
+    // Number of the pick that ran the task last, if it was preempted afterwards
+    #[serde(skip)]
+    preempted_at: Option<u64>,
+    // Whether the task became runnable by arriving or waking rather than by being preempted
+    #[serde(skip)]
+    woken: bool,
+    // Ticks the task has run in a row so far
+    #[serde(skip)]
+    running_for: u64,
+}
+
+impl TaskStats {
+    // Creates the statistics of a task arriving at the given tick, runnable from then on
+    pub fn new(id: u16, arrival: u128) -> Self {
+        Self { id, arrival, runnable_since: Some(arrival), woken: true, ..Self::default() }
+    }
+
+    // Records that the task blocked from the given tick without running, such as a new task
+    // waiting for its dependencies or starting with an I/O
+    pub(crate) fn blocked_at(&mut self, at: u128, io: bool) {
+        if let Some(since) = self.runnable_since.take() {
+            self.wait += at.saturating_sub(since) as u64;
+        }
+        self.blocked_since.get_or_insert((at, io));
+    }
+
+    // Records that the task stopped being blocked at the given tick, returning whether it was
+    fn unblocked(&mut self, at: u128) -> bool {
+        match self.blocked_since.take() {
+            Some((since, io)) => {
+                let ticks = at.saturating_sub(since) as u64;
+                match io {
+                    true => self.io_wait += ticks,
+                    false => self.sleep += ticks,
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Records that the task joined the run queue and can run from the given tick
+    pub(crate) fn queued(&mut self, ready: u128) {
+        if self.unblocked(ready) {
+            self.woken = true;
+        }
+        if self.runnable_since.is_none() {
+            self.runnable_since = Some(ready);
+        }
+    }
+
+    // Records that the task was picked to run at the given tick by the given pick of the scheduler
+    pub(crate) fn picked(&mut self, now: u128, pick: u64) {
+        let waited = now.saturating_sub(self.runnable_since.take().unwrap_or(now)) as u64;
+        self.wait += waited;
+        self.max_wait = self.max_wait.max(waited);
+        self.first_run.get_or_insert(now);
+        self.runtime += 1;
+        match self.preempted_at.take() {
+            // The task kept the CPU, carrying on with its slice
+            Some(last) if pick == last + 1 => {
+                self.running_for += 1;
+                return;
+            }
+            // Another task ran since the task was preempted, ending its slice
+            Some(_) => {
+                self.involuntary_switches += 1;
+                self.end_slice();
+            }
+            None => {}
+        }
+        // A new slice starts after waiting on the run queue
+        self.latency.wait.record(waited);
+        if std::mem::take(&mut self.woken) {
+            self.latency.wakeup.record(waited);
+        }
+        self.running_for = 1;
+    }
+
+    // Records the length of the slice the task ran
+    fn end_slice(&mut self) {
+        if self.running_for > 0 {
+            self.latency.slice.record(std::mem::take(&mut self.running_for));
+        }
+    }
+
+    // Records that the task ran at the given tick by the given pick and stays on the run queue
+    pub(crate) fn preempted(&mut self, now: u128, pick: u64) {
+        self.preempted_at = Some(pick);
+        self.runnable_since = Some(now + 1);
+    }
+
+    // Records that the task ran at the given tick and left the CPU to do I/O, block or sleep
+    pub(crate) fn left_cpu(&mut self, now: u128, io: bool) {
+        self.voluntary_switches += 1;
+        self.blocked_since = Some((now + 1, io));
+        self.end_slice();
+    }
+
+    // Records that the task with the given weight terminated at the given tick, straight from the
+    // CPU or at the end of a blocking phase
+    pub(crate) fn exited(&mut self, end: u128, from_cpu: bool, weight: u32) {
+        self.weight = weight;
+        if from_cpu {
+            self.voluntary_switches += 1;
+            self.end_slice();
+        }
+        self.unblocked(end);
+        if let Some(since) = self.runnable_since.take() {
+            self.wait += end.saturating_sub(since) as u64;
+        }
+        self.completion = Some(end);
+    }
+
+    // Returns the ticks spent off the run queue, waiting for I/O or sleeping
+    pub fn blocked(&self) -> u64 {
+        self.io_wait + self.sleep
+    }
+
+    // Returns the ticks from arrival to completion once the task has completed
+    pub fn turnaround(&self) -> Option<u128> {
+        self.completion.map(|completion| completion.saturating_sub(self.arrival))
+    }
+}
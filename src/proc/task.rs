@@ -1,29 +1,155 @@
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+use super::arrival::Arrival;
+use super::dist::{self, Dist};
+use super::script::{Phase, Script, SyncOp};
+use super::stats::TaskStats;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+// Load weights indexed by nice value + 20, as in the Linux kernel's sched_prio_to_weight table
+pub const NICE_TO_WEIGHT: [u32; 40] = [
+    88761, 71755, 56483, 46273, 36291,
+    29154, 23254, 18705, 14949, 11916,
+    9548, 7620, 6100, 4904, 3906,
+    3121, 2501, 1991, 1586, 1277,
+    1024, 820, 655, 526, 423,
+    335, 272, 215, 172, 137,
+    110, 87, 70, 56, 45,
+    36, 29, 23, 18, 15,
+];
+
+// What a blocked task is waiting for
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    // An I/O burst on a device
+    Io,
+    // The tasks it depends on to terminate
+    Dependencies,
+    // A mutex held by another task
+    Mutex,
+    // A semaphore with a count of zero
+    Semaphore,
+    // A wake-up on a wait queue
+    WaitQueue,
+    // A message on a channel
+    Channel,
+}
+
+impl fmt::Display for BlockReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockReason::Io => write!(f, "I/O"),
+            BlockReason::Dependencies => write!(f, "dependencies"),
+            BlockReason::Mutex => write!(f, "a mutex"),
+            BlockReason::Semaphore => write!(f, "a semaphore"),
+            BlockReason::WaitQueue => write!(f, "a wait queue"),
+            BlockReason::Channel => write!(f, "a channel"),
+        }
+    }
+}
+
+// The states of a task. A task starts out New, is Runnable while it waits for the CPU and Running
+// while it is on it, is Blocked or Sleeping while it waits for something else, and is Stopped while
+// held off the run queue. A task that has exited is a Zombie until the scheduler reaps it and it is Dead.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
-    // Status of a task when it is not running and not waiting for any resources
-    Idle,
-    // Status of a task when it is currently running
-    Running,
-    // Status of a task when it has completed all its execution
-    Terminated,
-    // Status of a task when it is waiting for some resources to become available
-    Waiting,
     // Status of a task when it is newly created and has not started yet
     New,
+    // Status of a task when it is waiting for the CPU
+    Runnable,
+    // Status of a task when it is currently running
+    Running,
+    // Status of a task waiting for an I/O, its dependencies or a synchronisation primitive
+    Blocked { reason: BlockReason },
+    // Status of a task sleeping until a timer wakes it
+    Sleeping,
+    // Status of a task held off the run queue until it is continued
+    Stopped,
+    // Status of a task that has exited but has not been reaped yet
+    Zombie,
+    // Status of a task that has exited and been reaped
+    Dead,
+}
+
+impl TaskStatus {
+    // Returns whether a task can go from this state to the given state
+    pub fn can_become(&self, to: TaskStatus) -> bool {
+        use TaskStatus::*;
+        match (*self, to) {
+            // Nothing comes after death, and a zombie can only be reaped
+            (Dead, _) => false,
+            (Zombie, to) => to == Dead,
+            (_, Dead) | (_, New) => false,
+            // A stopped task can only be continued or killed
+            (Stopped, to) => to == Runnable || to == Zombie,
+            // A task has to be on the run queue before it can run
+            (from, Running) => from == Runnable,
+            _ => true,
+        }
+    }
+
+    // Returns whether the task has exited
+    pub fn has_exited(&self) -> bool {
+        matches!(self, TaskStatus::Zombie | TaskStatus::Dead)
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TaskStatus::New => write!(f, "new"),
+            TaskStatus::Runnable => write!(f, "runnable"),
+            TaskStatus::Running => write!(f, "running"),
+            TaskStatus::Blocked { reason } => write!(f, "blocked on {}", reason),
+            TaskStatus::Sleeping => write!(f, "sleeping"),
+            TaskStatus::Stopped => write!(f, "stopped"),
+            TaskStatus::Zombie => write!(f, "a zombie"),
+            TaskStatus::Dead => write!(f, "dead"),
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
+// Error returned when a task is asked to make a transition its state does not allow
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransitionError {
+    // Id of the task
+    pub id: u16,
+    // State the task was in
+    pub from: TaskStatus,
+    // State the task was asked to go to
+    pub to: TaskStatus,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "task {} cannot go from {} to {}", self.id, self.from, self.to)
+    }
+}
+
+impl Error for TransitionError {}
+
+#[derive(Clone, Debug)]
 pub struct TaskChar {
     // Unique identifier of the task
     id: u16,
     // Total CPU time required to complete the task
     cpu_time: u64,
-    // Length of a CPU burst of the task
-    cpu_burst_length: u64,
-    // Length of an I/O burst of the task
-    io_burst_length: u64,
+    // Distribution of the CPU burst lengths of the task
+    cpu_burst_length: Dist,
+    // Distribution of the I/O burst lengths of the task
+    io_burst_length: Dist,
     // A weight that determines the priority of the task
     weight: u32,
+    // Behaviour script of the task
+    script: Script,
+    // Ids of the tasks that must terminate before this task can start
+    deps: Vec<u16>,
+    // When the instances of the task arrive
+    arrival: Arrival,
 }
 
 impl TaskChar {
@@ -36,12 +162,49 @@ impl TaskChar {
         weight: u32
     ) -> Self {
 
+        Self::stochastic(id, cpu_time, Dist::Constant(cpu_burst_length), Dist::Constant(io_burst_length), weight)
+
+    }
+
+    // Creates a new instance of TaskChar whose burst lengths are drawn from distributions
+    pub fn stochastic(
+        id: u16,
+        cpu_time: u64,
+        cpu_burst_length: Dist,
+        io_burst_length: Dist,
+        weight: u32
+    ) -> Self {
+
         Self {
             id,
             cpu_time,
+            script: Script::bursts(cpu_burst_length.clone(), io_burst_length.clone()),
             cpu_burst_length,
             io_burst_length,
-            weight
+            weight,
+            deps: Vec::new(),
+            arrival: Arrival::default()
+        }
+
+    }
+
+    // Creates a new instance of TaskChar whose behaviour is described by a script
+    pub fn scripted(
+        id: u16,
+        cpu_time: u64,
+        script: Script,
+        weight: u32
+    ) -> Self {
+
+        Self {
+            id,
+            cpu_time,
+            cpu_burst_length: Dist::Constant(0),
+            io_burst_length: Dist::Constant(0),
+            weight,
+            script,
+            deps: Vec::new(),
+            arrival: Arrival::default()
         }
 
     }
@@ -54,31 +217,69 @@ impl TaskChar {
     #[inline]
     pub fn get_cpu_time(&self) -> u64 { self.cpu_time }
 
-    // Returns the length of a CPU burst of the task
+    // Returns the distribution of the CPU burst lengths of the task
     #[inline]
-    pub fn get_cpu_burst_length(&self) -> u64 { self.cpu_burst_length }
+    pub fn get_cpu_burst_length(&self) -> &Dist { &self.cpu_burst_length }
 
-    // Returns the length of an I/O burst of the task
+    // Returns the distribution of the I/O burst lengths of the task
     #[inline]
-    pub fn get_io_burst_length(&self) -> u64 { self.io_burst_length }
+    pub fn get_io_burst_length(&self) -> &Dist { &self.io_burst_length }
 
     // Returns the weight that determines the priority of the task
     #[inline]
     pub fn get_weight(&self) -> u32 { self.weight }
+
+    // Sets the weight that determines the priority of the task
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    // Returns the behaviour script of the task
+    #[inline]
+    pub fn get_script(&self) -> &Script { &self.script }
+
+    // Returns the ids of the tasks that must terminate before this task can start
+    #[inline]
+    pub fn get_deps(&self) -> &[u16] { &self.deps }
+
+    // Makes the task wait for the given tasks to terminate before it starts
+    pub fn with_deps(mut self, deps: Vec<u16>) -> Self {
+        self.deps = deps;
+        self
+    }
+
+    // Returns when the instances of the task arrive
+    #[inline]
+    pub fn get_arrival(&self) -> &Arrival { &self.arrival }
+
+    // Sets when the instances of the task arrive
+    pub fn with_arrival(mut self, arrival: Arrival) -> Self {
+        self.arrival = arrival;
+        self
+    }
 }
 
 #[derive(Debug)]
 pub struct Task {
     id: u16, // unique identifier for the task
     cpu_time: u64, // amount of time the task will spend on the CPU
-    cpu_burst_length: u64, // length of time the task will spend on the CPU in each burst
-    io_burst_length: u64, // length of time the task will spend waiting for I/O in each burst
-    state: TaskStatus, // current status of the task (Idle, Running, Terminated, Waiting, New)
+    script: Script, // behaviour script the task follows
+    phase: usize, // index of the current phase in the script
+    phase_left: u64, // ticks remaining in the current phase
+    forks: Vec<Script>, // scripts of children forked but not yet spawned
+    pending: Option<SyncOp>, // synchronisation operation the task is waiting for the scheduler to carry out
+    nice: i8, // nice value of the task
+    rng: StdRng, // random number generator the burst lengths are drawn from
+    deps: Vec<u16>, // ids of the tasks that must terminate before this task can start
+    state: TaskStatus, // current status of the task
     runtime: u64, // total amount of time the task has spent on the CPU
     vruntime: u64, // virtual runtime used by the task scheduler
-    idle_time: u64, // total amount of time the task has spent waiting for I/O
+    idle_time: u64, // amount of time the task has spent in its current I/O burst
     start_time: u128, // start time of the task in nanoseconds
     weight: u32, // priority weight of the task
+    inherited: u32, // weight inherited from tasks blocked on mutexes the task holds, or 0
+    stats: TaskStats, // scheduling statistics of the task
 }
 
 impl Task {
@@ -92,18 +293,42 @@ impl Task {
         weight: u32
     ) -> Self {
 
-        Self {
+        Self::scripted(id, cpu_time, Script::bursts(cpu_burst_length, io_burst_length), start_time, weight)
+
+    }
+
+    // Creates a new instance of Task that follows the given behaviour script
+    pub fn scripted(
+        id: u16,
+        cpu_time: u64,
+        script: Script,
+        start_time: u128,
+        weight: u32
+    ) -> Self {
+
+        let mut task = Self {
             id,
             cpu_time,
-            cpu_burst_length,
-            io_burst_length,
+            script,
+            phase: 0,
+            phase_left: 0,
+            forks: Vec::new(),
+            pending: None,
+            nice: 0,
+            rng: dist::task_rng(0, id),
+            deps: Vec::new(),
             state: TaskStatus::New,
             runtime: 0,
             vruntime: 0,
             idle_time: 0,
             start_time,
-            weight
-        }
+            weight,
+            inherited: 0,
+            stats: TaskStats::new(id, start_time)
+        };
+        task.begin();
+
+        task
 
     }
 
@@ -132,86 +357,339 @@ impl Task {
         self.runtime
     }
 
-    // Terminates the task
-    pub fn terminate(&mut self) {
-        self.state = TaskStatus::Terminated
+    // Returns the total time the task has spent waiting for I/O, as its statistics count it
+    pub fn get_io_wait(&self) -> u64 {
+        self.stats.io_wait
+    }
+
+    // Returns the total time the task has spent sleeping on a timer, dependencies or a
+    // synchronisation primitive, as its statistics count it
+    pub fn get_sleep_time(&self) -> u64 {
+        self.stats.sleep
+    }
+
+    // Returns the task's scheduling statistics
+    pub fn stats(&self) -> &TaskStats {
+        &self.stats
+    }
+
+    // Returns the task's scheduling statistics for the scheduler to update
+    pub(crate) fn stats_mut(&mut self) -> &mut TaskStats {
+        &mut self.stats
     }
 
-    // Returns the task's weight
+    // Moves the task to the given state if its current state allows it
+    fn transition(&mut self, to: TaskStatus) -> Result<(), TransitionError> {
+        if !self.state.can_become(to) {
+            return Err(TransitionError { id: self.id, from: self.state, to });
+        }
+        self.state = to;
+        Ok(())
+    }
+
+    // Terminates the task, leaving it a zombie until it is reaped
+    pub fn terminate(&mut self) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Zombie)
+    }
+
+    // Reaps a terminated task
+    pub fn reap(&mut self) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Dead)
+    }
+
+    // Stops a runnable task until it is continued
+    pub fn stop(&mut self) -> Result<(), TransitionError> {
+        match self.state {
+            TaskStatus::Runnable | TaskStatus::Running => self.transition(TaskStatus::Stopped),
+            from => Err(TransitionError { id: self.id, from, to: TaskStatus::Stopped }),
+        }
+    }
+
+    // Continues a stopped task, making it runnable again
+    pub fn cont(&mut self) -> Result<(), TransitionError> {
+        match self.state {
+            TaskStatus::Stopped => self.transition(TaskStatus::Runnable),
+            from => Err(TransitionError { id: self.id, from, to: TaskStatus::Runnable }),
+        }
+    }
+
+    // Returns the task's effective weight, including any weight inherited through a mutex
     pub fn weight(&self) -> u32 {
+        self.weight.max(self.inherited)
+    }
+
+    // Returns the task's own weight, ignoring inheritance
+    pub fn base_weight(&self) -> u32 {
         self.weight
     }
 
+    // Sets the weight the task inherits from the tasks waiting on its mutexes, 0 for none,
+    // and returns whether its effective weight changed
+    pub fn set_inherited(&mut self, inherited: u32) -> bool {
+        let before = self.weight();
+        self.inherited = inherited;
+        self.weight() != before
+    }
+
+    // Returns the task's nice value
+    pub fn get_nice(&self) -> i8 {
+        self.nice
+    }
+
+    // Sets the task's nice value and the matching weight
+    pub fn set_nice(&mut self, nice: i8) {
+        self.nice = nice.clamp(-20, 19);
+        self.weight = NICE_TO_WEIGHT[(self.nice + 20) as usize];
+    }
+
+    // Reseeds the task's random number generator from the simulation seed and restarts its script,
+    // so the burst lengths of the task are reproducible for a given seed
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = dist::task_rng(seed, self.id);
+        self.restart(self.start_time);
+    }
+
+    // Returns a seed for a child of the task, drawn from the task's own random number generator
+    pub fn child_seed(&mut self) -> u64 {
+        self.rng.gen()
+    }
+
+    // Returns the ids of the tasks that must terminate before this task can start
+    pub fn get_deps(&self) -> &[u16] {
+        &self.deps
+    }
+
+    // Sets the ids of the tasks that must terminate before this task can start
+    pub fn set_deps(&mut self, deps: Vec<u16>) {
+        self.deps = deps;
+    }
+
+    // Blocks the task until its I/O completes, its dependencies terminate or a synchronisation primitive wakes it
+    pub fn block(&mut self, reason: BlockReason) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Blocked { reason })
+    }
+
+    // Releases a task blocked on its dependencies at the given time, as if it was born then
+    pub fn release(&mut self, time: u128) {
+        if self.state == (TaskStatus::Blocked { reason: BlockReason::Dependencies }) {
+            self.state = TaskStatus::New;
+            self.start_time = time;
+        }
+    }
+
+    // Returns the synchronisation operation the task is waiting for the scheduler to carry out
+    pub fn take_pending(&mut self) -> Option<SyncOp> {
+        self.pending.take()
+    }
+
+    // Moves the task past a synchronisation operation that has completed, waking it if it was blocked
+    pub fn resume(&mut self) -> Result<(), TransitionError> {
+        match self.state {
+            TaskStatus::Blocked { .. } => self.transition(TaskStatus::Runnable)?,
+            TaskStatus::Stopped | TaskStatus::Zombie | TaskStatus::Dead => {
+                return Err(TransitionError { id: self.id, from: self.state, to: TaskStatus::Runnable })
+            }
+            _ => {}
+        }
+        self.advance();
+        Ok(())
+    }
+
+    // Returns the phase the task is currently executing
+    pub fn get_phase(&self) -> Option<&Phase> {
+        self.script.get(self.phase)
+    }
+
+    // Returns the ticks left in the current phase
+    pub fn get_phase_left(&self) -> u64 {
+        self.phase_left.saturating_sub(self.idle_time)
+    }
+
+    // Redraws the length of the current I/O or sleep phase from the given distribution,
+    // as when a device imposes its own service time
+    pub fn redraw_phase(&mut self, length: &Dist) {
+        self.phase_left = length.sample(&mut self.rng);
+    }
+
+    // Returns whether the current phase of the task needs the CPU
+    pub fn wants_cpu(&self) -> bool {
+        matches!(self.get_phase(), Some(Phase::Cpu(_)))
+    }
+
+    // Returns the scripts of the children forked since the last call
+    pub fn take_forks(&mut self) -> Vec<Script> {
+        std::mem::take(&mut self.forks)
+    }
+
+    // Returns the task's virtual runtime as of its last update
+    pub fn get_vruntime(&self) -> u64 {
+        self.vruntime
+    }
+
     // Returns the task's virtual runtime
     pub fn vruntime(&mut self, now: u128) -> u64 {
         let dt: u64 = now.overflowing_sub(self.start_time).0 as u64;
-        let delta_exec_weighted: u64 = dt / (self.weight as u64);
+        let delta_exec_weighted: u64 = dt / (self.weight() as u64);
         self.vruntime += delta_exec_weighted;
 
         self.vruntime
     }
 
-    // Changes the task's state to idle
-    pub fn to_idle(&mut self) {
-        match self.state {
-            TaskStatus::Terminated => panic!("Cannot yield a terminated task ({:?})!", self.id),
-            _ => self.state = TaskStatus::Idle
-        }
+    // Changes the task's state to sleeping
+    pub fn to_sleep(&mut self) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Sleeping)
     }
 
-    // Changes the task's state to waiting
-    pub fn schedule(&mut self) {
+    // Wakes a sleeping task, moving it past its sleep phase
+    pub fn wake(&mut self) -> Result<(), TransitionError> {
         match self.state {
-            TaskStatus::Terminated => panic!("Cannot schedule a terminated task: ({:?})!", self.id),
-            _ => self.state = TaskStatus::Waiting
+            TaskStatus::Sleeping => {
+                self.advance();
+                Ok(())
+            },
+            from => Err(TransitionError { id: self.id, from, to: TaskStatus::Runnable })
         }
     }
 
+    // Changes the task's state to runnable
+    pub fn schedule(&mut self) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Runnable)
+    }
+
     // Changes the task's state to running
-    pub fn run(&mut self) {
-        self.state = TaskStatus::Running
+    pub fn run(&mut self) -> Result<(), TransitionError> {
+        self.transition(TaskStatus::Running)
     }
 
 
     pub fn restart(&mut self, time: u128) {
-        // Resets the task's runtime, idle_time, status, and start_time to their default values. 
+        // Resets the task's runtime, idle_time, status, script position and start_time to their default values. 
         self.runtime = 0;
         self.idle_time = 0;
         self.state = TaskStatus::New;
         self.start_time = time;
+        self.stats = TaskStats::new(self.id, time);
+        self.forks.clear();
+        self.pending = None;
+        self.begin();
+    }
+
+    // Enters the first phase of the script, skipping untimed phases
+    fn begin(&mut self) {
+        self.phase = 0;
+        if self.script.is_empty() {
+            self.state = TaskStatus::Zombie;
+        } else if !self.enter() {
+            self.advance();
+        }
+    }
+
+    // Moves the task to the next timed phase of its script, terminating it when the script ends.
+    // The state changes made here and in `enter` are always allowed from the states of a task
+    // that is new, on the CPU or coming back from waiting, which the public transitions check for.
+    fn advance(&mut self) {
+        // Bound the number of untimed phases skipped so a malformed loop cannot spin forever
+        for _ in 0..=self.script.len() {
+            match self.script.next(self.phase) {
+                Some(next) => self.phase = next,
+                None => break,
+            }
+            if self.enter() {
+                return;
+            }
+        }
+        self.state = TaskStatus::Zombie;
+    }
+
+    // Sets up the current phase, drawing its length, and returns whether the task stops there.
+    // Nice changes and forks take effect immediately, while synchronisation operations stop
+    // the task until the scheduler carries them out.
+    fn enter(&mut self) -> bool {
+        match self.script.get(self.phase).cloned() {
+            Some(Phase::Cpu(length)) if !length.is_zero() => {
+                // A CPU burst always takes at least one tick, whatever the distribution yields
+                self.phase_left = length.sample(&mut self.rng).max(1);
+                if matches!(self.state, TaskStatus::Blocked { .. } | TaskStatus::Sleeping) {
+                    self.state = TaskStatus::Runnable;
+                }
+                true
+            },
+            Some(Phase::Io(length, _)) | Some(Phase::Write(length, _)) => {
+                self.phase_left = length.sample(&mut self.rng);
+                self.idle_time = 0;
+                if self.state != TaskStatus::New {
+                    self.state = TaskStatus::Blocked { reason: BlockReason::Io };
+                }
+                true
+            },
+            Some(Phase::Sleep(length)) => {
+                self.phase_left = length.sample(&mut self.rng);
+                if self.state != TaskStatus::New {
+                    self.state = TaskStatus::Sleeping;
+                }
+                true
+            },
+            Some(Phase::SleepUntil(_)) => {
+                if self.state != TaskStatus::New {
+                    self.state = TaskStatus::Sleeping;
+                }
+                true
+            },
+            Some(Phase::Nice(nice)) => {
+                self.set_nice(nice);
+                false
+            },
+            Some(Phase::Fork(script)) => {
+                self.forks.push(script);
+                false
+            },
+            Some(Phase::Sync(op)) => {
+                self.pending = Some(op);
+                true
+            },
+            _ => false
+        }
     }
     
-    pub fn cpu_cycle(&mut self) {
+    pub fn cpu_cycle(&mut self) -> Result<(), TransitionError> {
         // Executes one CPU cycle for the task.
         // If the task is running, the runtime is incremented by 1. 
         // If the runtime is equal or greater than the task's cpu_time, the task is terminated. 
-        // If the runtime is a multiple of the cpu_burst_length, the task goes idle.
+        // If the current CPU phase is over, the task moves on to the next phase of its script.
+        // A task that is not running cannot run a cycle.
         match self.state {
             TaskStatus::Running => {
                 self.runtime += 1;
                 if self.runtime >= self.cpu_time {
-                    self.terminate();
-                } else if self.runtime % self.cpu_burst_length == 0 {
-                    self.to_idle();
+                    self.terminate()?;
+                } else if self.wants_cpu() {
+                    self.phase_left -= 1;
+                    if self.phase_left == 0 {
+                        self.advance();
+                    }
                 }
+                Ok(())
             },
-            _ => println!("Task {:?} is not running", self.id)
+            from => Err(TransitionError { id: self.id, from, to: TaskStatus::Running })
         }
     }
     
-    pub fn io_cycle(&mut self) {
-        // Executes one I/O cycle for the task.
-        // If the task is idle, the idle_time is incremented by 1. 
-        // If the idle_time is equal or greater than the task's io_burst_length, the task is scheduled to run.
+    pub fn io_cycle(&mut self) -> Result<bool, TransitionError> {
+        // Executes one I/O cycle for the task and returns whether the current I/O phase finished.
+        // If the task is blocked on I/O, the idle_time is incremented by 1. 
+        // If the idle_time is equal or greater than the length of the current phase, the task moves on to the next phase.
+        // A task that is not blocked on I/O cannot do an I/O cycle.
         match self.state {
-            TaskStatus::Idle => {
+            TaskStatus::Blocked { reason: BlockReason::Io } => {
                 self.idle_time += 1;
-                if self.idle_time >= self.io_burst_length {
+                if self.idle_time >= self.phase_left {
                     self.idle_time = 0;
-                    self.schedule();
+                    self.advance();
+                    return Ok(true);
                 }
+                Ok(false)
             },
-            _ => println!("Task {:?} is currently not idle", self.id)
+            from => Err(TransitionError { id: self.id, from, to: TaskStatus::Blocked { reason: BlockReason::Io } })
         }
     }
     
@@ -231,21 +709,26 @@ impl Clone for Task {
         Self {
             id:                 self.id,
             cpu_time:           self.cpu_time,
-            cpu_burst_length:   self.cpu_burst_length,
-            io_burst_length:    self.io_burst_length,
+            script:             self.script.clone(),
+            phase:              self.phase,
+            phase_left:         self.phase_left,
+            forks:              self.forks.clone(),
+            pending:            self.pending.clone(),
+            nice:               self.nice,
+            rng:                self.rng.clone(),
+            deps:               self.deps.clone(),
             state:              self.state,
             runtime:            self.runtime,
             vruntime:           self.vruntime,
             idle_time:          self.idle_time,
             start_time:         self.start_time,
-            weight:             self.weight
+            weight:             self.weight,
+            inherited:          self.inherited,
+            stats:              self.stats.clone()
         }
     }
 }
 
-// Implement the Copy trait for the Task struct
-impl Copy for Task {}
-
 // Implement the Send trait for the Task struct to allow it to be sent between threads
 unsafe impl Send for Task {}
 
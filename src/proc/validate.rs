@@ -0,0 +1,185 @@
+use super::dist::Dist;
+use super::script::{Phase, Script};
+use super::workload::{Workload, WorkloadTask};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// How bad a problem found in a workload is
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    // The workload runs, but probably not as meant
+    Warning,
+    // The workload cannot run: the simulation would panic, hang or ignore the task's description
+    Error,
+}
+
+// A problem found in a workload, with the line it is on (from 1), or 0 if it is not tied to one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    // Id of the task the problem is about, if any
+    pub task: Option<u16>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.line {
+            0 => write!(f, "{}: ", severity)?,
+            line => write!(f, "{} at line {}: ", severity, line)?,
+        }
+        match self.task {
+            Some(id) => write!(f, "task {}: {}", id, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Returns whether any of the diagnostics is an error
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+// Collects the diagnostics of a workload
+struct Checker<'a> {
+    workload: &'a Workload,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, severity: Severity, task: Option<&WorkloadTask>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            line: task.map_or(0, |task| task.line),
+            task: task.map(|task| task.task.get_id()),
+            message,
+        });
+    }
+
+    // Checks a task on its own
+    fn task(&mut self, task: &WorkloadTask) {
+        let char = &task.task;
+        // vruntime divides by the weight
+        if char.get_weight() == 0 {
+            self.report(Severity::Error, Some(task), String::from("weight must be positive"));
+        }
+        if char.get_cpu_time() == 0 {
+            self.report(Severity::Warning, Some(task), String::from("cpu_time is 0, but the task still runs for a tick"));
+        }
+        if let Some(group) = &task.group {
+            if !self.workload.groups.contains(group) {
+                self.report(Severity::Error, Some(task), format!("group `{}` is not declared", group));
+            }
+        }
+        self.script(task, char.get_script(), char.get_cpu_time());
+    }
+
+    // Checks a script of a task, or of a child it forks
+    fn script(&mut self, task: &WorkloadTask, script: &Script, cpu_time: u64) {
+        let phases = script.phases();
+        let (once, tail) = phases.split_at(script.loop_start().unwrap_or(phases.len()));
+
+        // A task only terminates by running out of script or of cpu_time, so a loop that never
+        // runs on the CPU goes on forever
+        let runs = |phase: &Phase| matches!(phase, Phase::Cpu(length) if !length.is_zero());
+        if !tail.is_empty() && !tail.iter().any(runs) {
+            let reason = match tail.iter().find(|phase| matches!(phase, Phase::Cpu(_))) {
+                Some(_) => "its CPU bursts are 0 ticks long, so it loops forever without running and never terminates",
+                None => "it loops forever without running on the CPU, so it never terminates",
+            };
+            self.report(Severity::Error, Some(task), String::from(reason));
+        }
+
+        // CPU bursts longer than cpu_time are cut short, which a cpu_time of 0 is already reported for
+        let cpu: Vec<&Dist> = phases.iter().filter_map(|phase| if let Phase::Cpu(length) = phase { Some(length) } else { None }).collect();
+        let longest = cpu.iter().filter(|length| length.mean().is_finite()).max_by(|a, b| a.mean().total_cmp(&b.mean()));
+        match longest {
+            Some(longest) if cpu_time > 0 && longest.mean() > cpu_time as f64 => self.report(
+                Severity::Warning,
+                Some(task),
+                format!("the CPU burst `{}` is longer than the cpu_time of {}, so the task ends before it does", longest, cpu_time),
+            ),
+            Some(_) if cpu_time > 0 && tail.is_empty() => {
+                let total: f64 = once.iter().filter_map(|phase| if let Phase::Cpu(length) = phase { Some(length.mean()) } else { None }).sum();
+                if total.is_finite() && total > cpu_time as f64 {
+                    self.report(
+                        Severity::Warning,
+                        Some(task),
+                        format!("the script runs {} ticks on the CPU, more than the cpu_time of {}, so it is cut short", total, cpu_time),
+                    );
+                }
+            }
+            _ => {}
+        }
+        if once.iter().any(|phase| matches!(phase, Phase::Cpu(length) if length.is_zero())) {
+            self.report(Severity::Warning, Some(task), String::from("CPU bursts of 0 ticks are skipped"));
+        }
+
+        // I/O to a device that is not declared is most likely a typo of one that is
+        for phase in phases {
+            match phase {
+                Phase::Io(_, Some(device)) | Phase::Write(_, Some(device)) if !self.workload.devices.iter().any(|spec| &spec.name == device) => {
+                    self.report(Severity::Error, Some(task), format!("device `{}` is not declared", device));
+                }
+                Phase::Fork(child) => self.script(task, child, cpu_time),
+                _ => {}
+            }
+        }
+    }
+
+    // Checks the tasks against each other and the workload
+    fn workload(&mut self) {
+        let mut devices = BTreeSet::new();
+        for device in self.workload.devices.iter() {
+            if !devices.insert(&device.name) {
+                self.report(Severity::Error, None, format!("device `{}` is declared twice", device.name));
+            }
+        }
+        let mut groups = BTreeSet::new();
+        for group in self.workload.groups.iter() {
+            if !groups.insert(group) {
+                self.report(Severity::Error, None, format!("group `{}` is declared twice", group));
+            }
+        }
+
+        // Parsed workloads number their tasks in order, so only names can collide
+        let ids: BTreeSet<u16> = self.workload.tasks.iter().map(|task| task.task.get_id()).collect();
+        let mut names: BTreeMap<&str, u16> = BTreeMap::new();
+        for task in self.workload.tasks.iter() {
+            let id = task.task.get_id();
+            if let Some(name) = task.name.as_deref() {
+                if let Some(first) = names.get(name) {
+                    self.report(Severity::Error, Some(task), format!("name `{}` is already used by task {}", name, first));
+                } else {
+                    names.insert(name, id);
+                }
+            }
+        }
+        for task in self.workload.tasks.iter() {
+            for dep in task.task.get_deps() {
+                if *dep == task.task.get_id() {
+                    self.report(Severity::Error, Some(task), String::from("depends on itself"));
+                } else if !ids.contains(dep) {
+                    self.report(Severity::Error, Some(task), format!("depends on task {}, which does not exist", dep));
+                }
+            }
+            self.task(task);
+        }
+    }
+}
+
+// Checks a workload for tasks that cannot run or probably do not run as meant, before it is run.
+// Errors are problems that would make the simulation panic or hang, such as a weight of zero or a
+// loop that never runs on the CPU, or that contradict the workload, such as a duplicate name or
+// an undeclared group or device. Warnings are oddities such as a CPU burst longer than the task's
+// cpu_time. Diagnostics come in order of the tasks, workload-wide ones first.
+pub fn validate(workload: &Workload) -> Vec<Diagnostic> {
+    let mut checker = Checker { workload, diagnostics: Vec::new() };
+    checker.workload();
+    checker.diagnostics
+}
@@ -0,0 +1,665 @@
+use super::arrival::Arrival;
+use super::dist::Dist;
+use super::script::{self, Script};
+use super::task::{TaskChar, NICE_TO_WEIGHT};
+use crate::io::device::Device;
+use crate::io::elevator;
+use crate::sched::scheduler::Scheduler;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// Weight of tasks of the idle policy, like the kernel's SCHED_IDLE
+pub const IDLE_WEIGHT: u32 = 3;
+
+// An error in a workload, with the number of the line it is on (from 1), or 0 if it is not tied
+// to a line
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkloadError {
+    line: usize,
+    reason: String,
+}
+
+impl WorkloadError {
+    fn new(line: usize, reason: impl Into<String>) -> Self {
+        Self { line, reason: reason.into() }
+    }
+
+    // Returns the number of the line the error is on, or 0 if it is not tied to a line
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for WorkloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "invalid workload: {}", self.reason),
+            line => write!(f, "invalid workload at line {}: {}", line, self.reason),
+        }
+    }
+}
+
+impl Error for WorkloadError {}
+
+// The formats workloads are written in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    // The TOML schema, with a `[[task]]` table per task
+    Toml,
+    // The same schema in JSON
+    Json,
+    // The line-based format of `tasks.txt`
+    Legacy,
+}
+
+impl Format {
+    // Returns the format of a file from its extension, the legacy one unless it is `.toml` or `.json`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Format::Toml,
+            Some("json") => Format::Json,
+            _ => Format::Legacy,
+        }
+    }
+}
+
+// Scheduling policies of tasks. The simulator only has the fair class, so batch tasks are
+// scheduled like normal ones and idle tasks run at the idle weight.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    #[default]
+    Normal,
+    Batch,
+    Idle,
+}
+
+// A device of a workload, created for every run
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceSpec {
+    pub name: String,
+    // I/Os served at once, any number if none
+    pub depth: Option<usize>,
+    // Distribution of the service time of every I/O, the burst length if none
+    pub service: Option<Dist>,
+    // Name of the I/O scheduler, which is known to `elevator::by_name`
+    pub scheduler: Option<String>,
+}
+
+impl DeviceSpec {
+    // Creates the device
+    pub fn device(&self) -> Device {
+        let mut device = Device::new(&self.name, self.depth);
+        if let Some(service) = &self.service {
+            device = device.with_service(service.clone());
+        }
+        if let Some(io_scheduler) = self.scheduler.as_deref().and_then(|name| elevator::by_name(name).ok()) {
+            device = device.with_scheduler(io_scheduler);
+        }
+        device
+    }
+}
+
+// A task of a workload, with what the simulator runs and what only describes it
+#[derive(Clone, Debug)]
+pub struct WorkloadTask {
+    pub task: TaskChar,
+    pub name: Option<String>,
+    pub policy: Policy,
+    // CPUs the task may run on, any if none
+    pub affinity: Option<Vec<u16>>,
+    pub group: Option<String>,
+    // Line the task is declared on, or 0 if unknown
+    pub line: usize,
+}
+
+// A workload: the tasks to run and the devices, semaphores and timer slack they run with
+#[derive(Clone, Debug, Default)]
+pub struct Workload {
+    pub tasks: Vec<WorkloadTask>,
+    pub devices: Vec<DeviceSpec>,
+    pub semaphores: Vec<(String, u32)>,
+    pub groups: Vec<String>,
+    pub timer_slack: Option<u128>,
+}
+
+impl Workload {
+    // Reads a workload from a file in the format given by its extension
+    pub fn load(path: &Path) -> Result<Self, WorkloadError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| WorkloadError::new(0, format!("could not read the file: {}", e)))?;
+        Self::parse(&text, Format::from_path(path))
+    }
+
+    // Parses a workload written in the given format
+    pub fn parse(text: &str, format: Format) -> Result<Self, WorkloadError> {
+        match format {
+            Format::Toml => {
+                let raw: RawWorkload<toml::Spanned<RawTask>> =
+                    toml::from_str(text).map_err(|e| WorkloadError::new(e.span().map_or(0, |span| line_of(text, span.start)), e.message()))?;
+                let tasks = raw.tasks.iter().map(|task| (line_of(text, task.span().start), task.get_ref())).collect();
+                raw.build(tasks)
+            }
+            Format::Json => {
+                let raw: RawWorkload<RawTask> = serde_json::from_str(text).map_err(|e| {
+                    // Messages end with the position, which the error gives on its own
+                    let message = e.to_string();
+                    let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(message, _)| message);
+                    WorkloadError::new(e.line(), message)
+                })?;
+                let lines = json_task_lines(text);
+                let tasks = raw.tasks.iter().enumerate().map(|(idx, task)| (lines.get(idx).copied().unwrap_or(0), task)).collect();
+                raw.build(tasks)
+            }
+            Format::Legacy => legacy(text),
+        }
+    }
+
+    // Writes the workload in the given format. The line-based format has no names, policies,
+    // affinities or groups, so they are left out.
+    pub fn write(&self, format: Format) -> Result<String, WorkloadError> {
+        match format {
+            Format::Toml => toml::to_string(&RawWorkload::new(self)).map_err(|e| WorkloadError::new(0, e.to_string())),
+            Format::Json => serde_json::to_string_pretty(&RawWorkload::new(self)).map(|json| json + "\n").map_err(|e| WorkloadError::new(0, e.to_string())),
+            Format::Legacy => Ok(legacy_text(self)),
+        }
+    }
+
+    // Gives every task the given policy. Idle tasks run at the idle weight, and tasks leaving the
+    // idle policy run at the weight of nice 0, as their own is not known.
+    pub fn set_policy(&mut self, policy: Policy) {
+        for task in self.tasks.iter_mut() {
+            let weight = match (task.policy, policy) {
+                (_, Policy::Idle) => IDLE_WEIGHT,
+                (Policy::Idle, _) => NICE_TO_WEIGHT[20],
+                _ => task.task.get_weight(),
+            };
+            task.task = task.task.clone().with_weight(weight);
+            task.policy = policy;
+        }
+    }
+
+    // Returns the name of a task, if it has one
+    pub fn name(&self, id: u16) -> Option<&str> {
+        self.tasks.iter().find(|task| task.task.get_id() == id).and_then(|task| task.name.as_deref())
+    }
+
+    // Sets the scheduler up with the devices, semaphores and timer slack of the workload, and
+    // returns the tasks to run
+    pub fn apply(&self, scheduler: &mut Scheduler) -> Vec<TaskChar> {
+        for device in self.devices.iter() {
+            scheduler.device(device.device());
+        }
+        for (name, count) in self.semaphores.iter() {
+            scheduler.semaphore(name, *count);
+        }
+        if let Some(slack) = self.timer_slack {
+            scheduler.timer_slack(slack);
+        }
+        self.tasks.iter().map(|task| task.task.clone()).collect()
+    }
+}
+
+// Returns the number of the line the byte at the given offset is on
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+// Returns the lines the elements of the top-level `tasks` array of a JSON workload start on. JSON
+// values carry no positions once parsed, so the text is scanned for them.
+fn json_task_lines(text: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let (mut depth, mut line) = (0, 1);
+    let (mut in_string, mut escaped) = (false, false);
+    let mut key = String::new();
+    // Whether the array being read is the `tasks` one, and whether an element starts next
+    let (mut in_tasks, mut element) = (false, false);
+    for c in text.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ if depth == 1 => key.push(c),
+                _ => {}
+            }
+            continue;
+        }
+        if in_tasks && element && !c.is_whitespace() && c != ']' {
+            lines.push(line);
+            element = false;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                if depth == 1 {
+                    key.clear();
+                }
+            }
+            '{' | '[' => {
+                depth += 1;
+                if depth == 2 && c == '[' && key == "tasks" {
+                    in_tasks = true;
+                    element = true;
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 1 {
+                    in_tasks = false;
+                }
+            }
+            ',' if depth == 2 && in_tasks => element = true,
+            _ => {}
+        }
+    }
+    lines
+}
+
+// A value parsed from a string, or from a number for values such as burst lengths and arrival times
+struct Parsed<T>(T);
+
+impl<'de, T: FromStr> Deserialize<'de> for Parsed<T>
+where
+    T::Err: fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Text {
+            Number(u64),
+            String(String),
+        }
+        let text = match Text::deserialize(deserializer).map_err(|_| de::Error::custom("expected a number or a string"))? {
+            Text::Number(number) => number.to_string(),
+            Text::String(text) => text,
+        };
+        text.parse().map(Parsed).map_err(de::Error::custom)
+    }
+}
+
+impl<T: fmt::Display> Serialize for Parsed<T> {
+    // Writes the value as a number if it is one, or as the string it is parsed from
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = self.0.to_string();
+        match text.parse::<u64>() {
+            Ok(number) => serializer.serialize_u64(number),
+            Err(_) => serializer.serialize_str(&text),
+        }
+    }
+}
+
+// A task a dependency is on, by id or by name
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum RawDep {
+    Id(u16),
+    Name(String),
+}
+
+// A task as written in the structured formats
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawTask {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    cpu_time: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nice: Option<i8>,
+    #[serde(default, skip_serializing_if = "is_normal")]
+    policy: Policy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arrival: Option<Parsed<Arrival>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    affinity: Option<Vec<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_burst: Option<Parsed<Dist>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    io_burst: Option<Parsed<Dist>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    script: Option<Parsed<Script>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    after: Vec<RawDep>,
+}
+
+// Returns whether a policy is the default one, which is left out when writing a task
+fn is_normal(policy: &Policy) -> bool {
+    *policy == Policy::Normal
+}
+
+impl RawTask {
+    // Describes a task of a workload, with burst lengths if it runs the classic bursts and its
+    // script otherwise
+    fn new(task: &WorkloadTask) -> Self {
+        let char = &task.task;
+        let (cpu_burst, io_burst) = (char.get_cpu_burst_length(), char.get_io_burst_length());
+        let classic = *char.get_script() == Script::bursts(cpu_burst.clone(), io_burst.clone());
+        Self {
+            name: task.name.clone(),
+            cpu_time: char.get_cpu_time(),
+            weight: (task.policy != Policy::Idle).then_some(char.get_weight()),
+            nice: None,
+            policy: task.policy,
+            arrival: (*char.get_arrival() != Arrival::default()).then(|| Parsed(char.get_arrival().clone())),
+            affinity: task.affinity.clone(),
+            group: task.group.clone(),
+            cpu_burst: classic.then(|| Parsed(cpu_burst.clone())),
+            io_burst: (classic && !io_burst.is_zero()).then(|| Parsed(io_burst.clone())),
+            script: (!classic).then(|| Parsed(char.get_script().clone())),
+            after: char.get_deps().iter().map(|dep| RawDep::Id(*dep)).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawDevice {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service: Option<Parsed<Dist>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheduler: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawSemaphore {
+    name: String,
+    count: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawGroup {
+    name: String,
+}
+
+// A workload as written in the structured formats, with tasks that may carry their position
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawWorkload<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timer_slack: Option<u64>,
+    #[serde(default, rename = "device", alias = "devices", skip_serializing_if = "Vec::is_empty")]
+    devices: Vec<RawDevice>,
+    #[serde(default, rename = "semaphore", alias = "semaphores", skip_serializing_if = "Vec::is_empty")]
+    semaphores: Vec<RawSemaphore>,
+    #[serde(default, rename = "group", alias = "groups", skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<RawGroup>,
+    #[serde(rename = "task", alias = "tasks")]
+    tasks: Vec<T>,
+}
+
+impl RawWorkload<RawTask> {
+    // Describes a workload for writing
+    fn new(workload: &Workload) -> Self {
+        Self {
+            timer_slack: workload.timer_slack.map(|slack| u64::try_from(slack).unwrap_or(u64::MAX)),
+            devices: workload
+                .devices
+                .iter()
+                .map(|device| RawDevice {
+                    name: device.name.clone(),
+                    depth: device.depth,
+                    service: device.service.clone().map(Parsed),
+                    scheduler: device.scheduler.clone(),
+                })
+                .collect(),
+            semaphores: workload.semaphores.iter().map(|(name, count)| RawSemaphore { name: name.clone(), count: *count }).collect(),
+            groups: workload.groups.iter().map(|name| RawGroup { name: name.clone() }).collect(),
+            tasks: workload.tasks.iter().map(RawTask::new).collect(),
+        }
+    }
+}
+
+impl<T> RawWorkload<T> {
+    // Checks the fields of the workload against each other and builds it out of its tasks, each
+    // with the line it is on
+    fn build(&self, tasks: Vec<(usize, &RawTask)>) -> Result<Workload, WorkloadError> {
+        let mut workload = Workload { timer_slack: self.timer_slack.map(u128::from), ..Workload::default() };
+        for device in self.devices.iter() {
+            if device.depth == Some(0) {
+                return Err(WorkloadError::new(0, format!("device `{}`: `depth` must be positive", device.name)));
+            }
+            if let Some(Err(e)) = device.scheduler.as_deref().map(elevator::by_name) {
+                return Err(WorkloadError::new(0, format!("device `{}`: `scheduler`: {}", device.name, e)));
+            }
+            workload.devices.push(DeviceSpec {
+                name: device.name.clone(),
+                depth: device.depth,
+                service: device.service.as_ref().map(|service| service.0.clone()),
+                scheduler: device.scheduler.clone(),
+            });
+        }
+        workload.semaphores = self.semaphores.iter().map(|semaphore| (semaphore.name.clone(), semaphore.count)).collect();
+        workload.groups = self.groups.iter().map(|group| group.name.clone()).collect();
+
+        for (idx, (line, raw)) in tasks.iter().enumerate() {
+            let id = u16::try_from(idx + 1).map_err(|_| WorkloadError::new(*line, "too many tasks"))?;
+            let err = |field: &str, reason: String| {
+                let task = raw.name.as_ref().map_or(format!("task {}", id), |name| format!("task {} (`{}`)", id, name));
+                WorkloadError::new(*line, format!("{}: `{}` {}", task, field, reason))
+            };
+
+            let weight = match (raw.policy, raw.weight, raw.nice) {
+                (Policy::Idle, None, None) => IDLE_WEIGHT,
+                (Policy::Idle, ..) => return Err(err("policy", String::from("idle runs at the idle weight and takes no weight or nice"))),
+                (_, Some(_), Some(_)) => return Err(err("nice", String::from("cannot be given along with a weight"))),
+                (_, Some(weight), None) => weight,
+                (_, None, nice) => {
+                    let nice = nice.unwrap_or(0);
+                    if !(-20..=19).contains(&nice) {
+                        return Err(err("nice", format!("must be between -20 and 19, found {}", nice)));
+                    }
+                    NICE_TO_WEIGHT[(nice + 20) as usize]
+                }
+            };
+
+            let mut task = match (&raw.script, &raw.cpu_burst, &raw.io_burst) {
+                (Some(script), None, None) => TaskChar::scripted(id, raw.cpu_time, script.0.clone(), weight),
+                (None, Some(cpu_burst), io_burst) => TaskChar::stochastic(
+                    id,
+                    raw.cpu_time,
+                    cpu_burst.0.clone(),
+                    io_burst.as_ref().map_or(Dist::Constant(0), |io_burst| io_burst.0.clone()),
+                    weight,
+                ),
+                (Some(_), ..) => return Err(err("script", String::from("cannot be given along with burst lengths"))),
+                (None, None, _) => return Err(err("cpu_burst", String::from("or a `script` is required"))),
+            };
+            if let Some(arrival) = &raw.arrival {
+                task = task.with_arrival(arrival.0.clone());
+            }
+
+            // Dependencies name earlier or later tasks by id or name
+            let mut deps = Vec::new();
+            for dep in raw.after.iter() {
+                let dep = match dep {
+                    RawDep::Id(dep) if usize::from(*dep) >= 1 && usize::from(*dep) <= tasks.len() => *dep,
+                    RawDep::Id(dep) => return Err(err("after", format!("names task {}, which does not exist", dep))),
+                    RawDep::Name(name) => match tasks.iter().position(|(_, task)| task.name.as_ref() == Some(name)) {
+                        Some(idx) => (idx + 1) as u16,
+                        None => return Err(err("after", format!("names task `{}`, which does not exist", name))),
+                    },
+                };
+                deps.push(dep);
+            }
+            task = task.with_deps(deps);
+
+            // There is a single simulated CPU, CPU 0
+            if let Some(affinity) = &raw.affinity {
+                if !affinity.contains(&0) {
+                    return Err(err("affinity", String::from("must include CPU 0, the only simulated CPU")));
+                }
+            }
+
+            workload.tasks.push(WorkloadTask {
+                task,
+                name: raw.name.clone(),
+                policy: raw.policy,
+                affinity: raw.affinity.clone(),
+                group: raw.group.clone(),
+                line: *line,
+            });
+        }
+        Ok(workload)
+    }
+}
+
+// Parses a workload in the line-based format of `tasks.txt`. Blank lines and lines starting with
+// `#` are skipped; any other line that is not understood is an error.
+pub fn legacy(text: &str) -> Result<Workload, WorkloadError> {
+    let mut workload = Workload::default();
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let err = |reason: String| WorkloadError::new(line_no, reason);
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+
+        // Lines of the form "semaphore name count" declare semaphores
+        if let ["semaphore", name, count] = &words[..] {
+            let count = count.parse::<u32>().map_err(|e| err(format!("invalid semaphore count: {}", e)))?;
+            workload.semaphores.push((String::from(*name), count));
+            continue;
+        }
+
+        // Lines of the form "timer_slack ticks" let sleeping tasks wake up to `ticks` late
+        if let ["timer_slack", slack] = &words[..] {
+            workload.timer_slack = Some(slack.parse::<u128>().map_err(|e| err(format!("invalid timer slack: {}", e)))?);
+            continue;
+        }
+
+        // Lines of the form "device name depth [service] [scheduler name]" declare devices
+        if let ["device", name, depth, rest @ ..] = &words[..] {
+            let (service, io_scheduler) = match rest.iter().position(|word| *word == "scheduler") {
+                Some(idx) => (&rest[..idx], rest.get(idx + 1).copied()),
+                None => (rest, None),
+            };
+            let depth = match *depth {
+                "unlimited" => None,
+                depth => match depth.parse::<usize>() {
+                    Ok(depth) if depth > 0 => Some(depth),
+                    _ => return Err(err(format!("invalid device depth `{}`", depth))),
+                },
+            };
+            let service = match service {
+                [] => None,
+                service => Some(service.join(" ").parse::<Dist>().map_err(|e| err(e.to_string()))?),
+            };
+            if let Some(name) = io_scheduler {
+                elevator::by_name(name).map_err(|e| err(e.to_string()))?;
+            }
+            workload.devices.push(DeviceSpec {
+                name: String::from(*name),
+                depth,
+                service,
+                scheduler: io_scheduler.map(String::from),
+            });
+            continue;
+        }
+
+        // A trailing "after 1,2" clause lists the tasks that must terminate before this one starts
+        let (line, deps) = match line.split_once(" after ") {
+            Some((line, after)) => {
+                let deps = after.split(',').map(|dep| dep.trim().parse::<u16>()).collect::<Result<Vec<u16>, _>>();
+                (line, deps.map_err(|e| err(format!("invalid dependency: {}", e)))?)
+            }
+            None => (line, Vec::new()),
+        };
+
+        // An "at 100", "at 0 every 50 count 10" or "at 0 poisson 50 count 10" clause sets when the task arrives
+        let (line, arrival) = match line.rsplit_once(" at ") {
+            Some((line, at)) => (line, at.parse::<Arrival>().map_err(|e| err(e.to_string()))?),
+            None => (line, Arrival::default()),
+        };
+
+        let id = u16::try_from(workload.tasks.len() + 1).map_err(|_| err(String::from("too many tasks")))?;
+        let number = |what: &str, value: &str| value.parse::<u64>().map_err(|_| err(format!("{} must be a number, found `{}`", what, value)));
+        let weight = |value: &str| value.parse::<u32>().map_err(|_| err(format!("weight must be a number, found `{}`", value)));
+
+        // Lines of the form "cpu_time weight : script" describe scripted tasks, and other lines
+        // the "cpu_time cpu_burst io_burst weight" task properties. Histograms have colons of their
+        // own, inside their parentheses.
+        let task = match script::split_outside(line, ':') {
+            Some((head, script)) => {
+                let script = script.parse::<Script>().map_err(|e| err(e.to_string()))?;
+                match &head.split_whitespace().collect::<Vec<&str>>()[..] {
+                    [cpu_time, weight_value] => TaskChar::scripted(id, number("cpu time", cpu_time)?, script, weight(weight_value)?),
+                    _ => return Err(err(String::from("expected `cpu_time weight : script`"))),
+                }
+            }
+            None => match &line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [cpu_time, cpu_burst, io_burst, weight_value] => TaskChar::stochastic(
+                    id,
+                    number("cpu time", cpu_time)?,
+                    cpu_burst.parse::<Dist>().map_err(|e| err(e.to_string()))?,
+                    io_burst.parse::<Dist>().map_err(|e| err(e.to_string()))?,
+                    weight(weight_value)?,
+                ),
+                _ => return Err(err(String::from("expected `cpu_time cpu_burst io_burst weight` or `cpu_time weight : script`"))),
+            },
+        };
+        workload.tasks.push(WorkloadTask {
+            task: task.with_deps(deps).with_arrival(arrival),
+            name: None,
+            policy: Policy::Normal,
+            affinity: None,
+            group: None,
+            line: line_no,
+        });
+    }
+    Ok(workload)
+}
+
+// Writes a workload in the line-based format of `tasks.txt`
+fn legacy_text(workload: &Workload) -> String {
+    let mut lines = Vec::new();
+    for (name, count) in workload.semaphores.iter() {
+        lines.push(format!("semaphore {} {}", name, count));
+    }
+    if let Some(slack) = workload.timer_slack {
+        lines.push(format!("timer_slack {}", slack));
+    }
+    for device in workload.devices.iter() {
+        let mut line = format!("device {} {}", device.name, device.depth.map_or(String::from("unlimited"), |depth| depth.to_string()));
+        if let Some(service) = &device.service {
+            line.push_str(&format!(" {}", service));
+        }
+        if let Some(io_scheduler) = &device.scheduler {
+            line.push_str(&format!(" scheduler {}", io_scheduler));
+        }
+        lines.push(line);
+    }
+    for task in workload.tasks.iter() {
+        let raw = RawTask::new(task);
+        let weight = task.task.get_weight();
+        let mut line = match &raw.script {
+            Some(script) => format!("{} {} : {}", raw.cpu_time, weight, script.0),
+            None => format!("{} {} {} {}", raw.cpu_time, task.task.get_cpu_burst_length(), task.task.get_io_burst_length(), weight),
+        };
+        if let Some(arrival) = &raw.arrival {
+            line.push_str(&format!(" at {}", arrival.0));
+        }
+        if !raw.after.is_empty() {
+            let deps: Vec<String> = task.task.get_deps().iter().map(u16::to_string).collect();
+            line.push_str(&format!(" after {}", deps.join(",")));
+        }
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
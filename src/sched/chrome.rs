@@ -0,0 +1,160 @@
+use super::trace::{self, Event, EventKind, TaskRef, TraceSink};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+// Process id of the CPU tracks; every device gets a process of its own after it
+const CPUS: usize = 1;
+
+// Builds a Chrome Trace Event Format document out of scheduling events, as read by
+// ui.perfetto.dev and chrome://tracing. A tick is shown as a microsecond. Every CPU has a track
+// with a slice for every stretch a task ran in a row, the run queue length is a counter, and every
+// device has a track with a slice for every I/O it served.
+#[derive(Clone, Debug, Default)]
+pub struct TraceBuilder {
+    // Trace events written so far, apart from the metadata
+    out: Vec<Value>,
+    // The slice each CPU is running: the task, and the ticks it started at and runs up to
+    slices: BTreeMap<u16, (TaskRef, u64, u64)>,
+    // CPUs that ran a task
+    cpus: BTreeSet<u16>,
+    // The tick every task last ran or was woken on, after which an I/O it submits then starts
+    busy: BTreeMap<u16, u64>,
+    // Process id of every device, handed out in order of first use
+    devices: BTreeMap<String, usize>,
+    // Run queue length last written to the counter
+    runnable: Option<usize>,
+}
+
+impl TraceBuilder {
+    // Creates a builder with no events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds an event to the trace
+    pub fn event(&mut self, event: &Event) {
+        let time = ts(event.time);
+        match &event.kind {
+            // A task picked again right after its last tick carries on with its slice
+            EventKind::Picked { task, cpu } => {
+                self.busy.insert(task.id, time);
+                match self.slices.get_mut(cpu) {
+                    Some((running, _, end)) if running.id == task.id && *end == time => *end = time + 1,
+                    _ => {
+                        self.end_slice(*cpu);
+                        self.cpus.insert(*cpu);
+                        self.slices.insert(*cpu, (*task, time, time + 1));
+                    }
+                }
+            }
+            EventKind::Woken { task } => {
+                self.busy.insert(task.id, time);
+            }
+            EventKind::Migrated { task, from, to } => self.out.push(json!({
+                "name": "migrate", "cat": "sched", "ph": "i", "s": "t", "ts": time, "pid": CPUS, "tid": to,
+                "args": { "task": task.id, "from": from, "to": to },
+            })),
+            EventKind::IoSubmitted { task, device } => {
+                let pid = self.device(device);
+                let start = if self.busy.get(&task.id) == Some(&time) { time + 1 } else { time };
+                self.out.push(Self::io(task, device, pid, "b", start));
+            }
+            // The I/O takes up the tick it completes on
+            EventKind::IoCompleted { task, device } => {
+                let pid = self.device(device);
+                self.out.push(Self::io(task, device, pid, "e", time + 1));
+            }
+            EventKind::Tick { runnable, .. } if self.runnable != Some(*runnable) => {
+                self.runnable = Some(*runnable);
+                self.out.push(json!({
+                    "name": "run queue", "ph": "C", "ts": time, "pid": CPUS, "args": { "runnable": runnable },
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    // Writes out the slice a CPU is running, if any
+    fn end_slice(&mut self, cpu: u16) {
+        if let Some((task, start, end)) = self.slices.remove(&cpu) {
+            self.out.push(json!({
+                "name": format!("task {}", task.id), "cat": "task", "ph": "X", "ts": start, "dur": end - start,
+                "pid": CPUS, "tid": cpu, "args": { "id": task.id, "nice": task.nice },
+            }));
+        }
+    }
+
+    // Returns the process id of a device, giving it one on first use
+    fn device(&mut self, name: &str) -> usize {
+        let next = CPUS + 1 + self.devices.len();
+        *self.devices.entry(String::from(name)).or_insert(next)
+    }
+
+    // Creates the event beginning or ending the I/O of a task on a device. I/Os of a device may
+    // overlap, so they are async slices told apart by task.
+    fn io(task: &TaskRef, device: &str, pid: usize, phase: &str, time: u64) -> Value {
+        json!({
+            "name": format!("task {}", task.id), "cat": "io", "ph": phase, "id": format!("{}:{}", device, task.id),
+            "ts": time, "pid": pid, "tid": 0,
+        })
+    }
+
+    // Ends the slices still running and returns the trace document
+    pub fn finish(&mut self) -> Value {
+        let cpus: Vec<u16> = self.slices.keys().copied().collect();
+        for cpu in cpus {
+            self.end_slice(cpu);
+        }
+        let mut events = vec![json!({ "name": "process_name", "ph": "M", "pid": CPUS, "args": { "name": "CPUs" } })];
+        for cpu in self.cpus.iter() {
+            events.push(json!({ "name": "thread_name", "ph": "M", "pid": CPUS, "tid": cpu, "args": { "name": format!("CPU {}", cpu) } }));
+        }
+        for (name, pid) in self.devices.iter() {
+            events.push(json!({ "name": "process_name", "ph": "M", "pid": pid, "args": { "name": format!("device {}", name) } }));
+        }
+        events.append(&mut self.out);
+        *self = Self::default();
+        json!({ "traceEvents": events })
+    }
+}
+
+// Converts a tick to a timestamp, which JSON numbers cannot hold beyond 64 bits
+fn ts(time: u128) -> u64 {
+    u64::try_from(time).unwrap_or(u64::MAX)
+}
+
+// Builds the Chrome Trace Event Format document of the given events
+pub fn document(events: &[Event]) -> Value {
+    let mut builder = TraceBuilder::new();
+    for event in events {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+// A sink writing the Chrome Trace Event Format document of a run once it is over. A write error is
+// returned from `finish`, failing the run.
+pub struct ChromeTrace<W: Write + Send> {
+    builder: TraceBuilder,
+    writer: W,
+}
+
+impl<W: Write + Send> ChromeTrace<W> {
+    // Creates a sink writing the trace to the given writer, such as a file
+    pub fn new(writer: W) -> Self {
+        Self { builder: TraceBuilder::new(), writer }
+    }
+}
+
+impl<W: Write + Send> TraceSink for ChromeTrace<W> {
+    fn event(&mut self, event: &Event) {
+        self.builder.event(event);
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        let document = self.builder.finish();
+        let written = serde_json::to_writer(&mut self.writer, &document).map_err(std::io::Error::from);
+        written.and_then(|_| self.writer.flush()).map_err(|e| trace::sink_error("Chrome trace", e))
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, PoisonError, RwLock};
 
 // The Clock structure that holds the current time
 pub struct Clock(Arc<RwLock<u128>>);
@@ -9,15 +9,22 @@ impl Clock {
         Self(Arc::new(RwLock::new(0)))
     }
 
-    // Increments the current time by 1
+    // Increments the current time by 1. The time is a plain counter that is always valid, so a
+    // lock poisoned by a panicking thread is used as is.
     pub fn tick(&mut self) {
-        let mut w = self.0.write().unwrap();
+        let mut w = self.0.write().unwrap_or_else(PoisonError::into_inner);
         *w += 1;
     }
 
     // Returns the current time
     pub fn time(&self) -> u128 {
-        *self.0.read().unwrap()
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -0,0 +1,251 @@
+use super::metrics::{Percentiles, Summary};
+use super::trace::{self, Event, EventKind, TraceSink};
+use crate::proc::stats::TaskStats;
+use serde::Serialize;
+use std::io::Write;
+
+// Columns of the per-task CSV summary
+const TASK_COLUMNS: &str =
+    "id,weight,arrival,first_run,completion,turnaround,runtime,wait,io_wait,sleep,voluntary_switches,involuntary_switches,migrations,max_wait";
+
+// Returns a field of a CSV row, empty when there is no value
+fn field<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+// Returns the scheduling statistics of the given tasks as CSV, with a header and a row per task.
+// The first run, completion and turnaround are empty for tasks that never ran or terminated.
+pub fn tasks_csv(tasks: &[TaskStats]) -> String {
+    let mut csv = format!("{}\n", TASK_COLUMNS);
+    for stats in tasks {
+        let turnaround = stats.completion.map(|completion| completion.saturating_sub(stats.arrival));
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            stats.id,
+            stats.weight,
+            stats.arrival,
+            field(stats.first_run),
+            field(stats.completion),
+            field(turnaround),
+            stats.runtime,
+            stats.wait,
+            stats.io_wait,
+            stats.sleep,
+            stats.voluntary_switches,
+            stats.involuntary_switches,
+            stats.migrations,
+            stats.max_wait
+        ));
+    }
+    csv
+}
+
+// Returns the scheduling statistics of the given tasks as a JSON array, latency histograms included
+pub fn tasks_json(tasks: &[TaskStats]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(tasks)
+}
+
+// Columns of the CSV comparison of runs
+const SUMMARY_COLUMNS: &str = "workload,completed,makespan,throughput,utilisation,turnaround_mean,turnaround_median,turnaround_p95,turnaround_p99,\
+waiting_mean,waiting_median,waiting_p95,waiting_p99,response_mean,response_median,response_p95,response_p99,jain_index,vruntime_spread";
+
+// Returns the fields of percentiles in a CSV row
+fn percentiles(percentiles: &Percentiles) -> String {
+    format!("{:.4},{},{},{}", percentiles.mean, percentiles.median, percentiles.p95, percentiles.p99)
+}
+
+// Returns the aggregate metrics of named runs as CSV, with a header and a row per run
+pub fn summaries_csv(runs: &[(String, Summary)]) -> String {
+    let mut csv = format!("{}\n", SUMMARY_COLUMNS);
+    for (name, summary) in runs {
+        csv.push_str(&format!(
+            "{},{},{},{:.4},{:.4},{},{},{},{:.4},{}\n",
+            name,
+            summary.completed,
+            summary.makespan,
+            summary.throughput,
+            summary.utilisation,
+            percentiles(&summary.turnaround),
+            percentiles(&summary.waiting),
+            percentiles(&summary.response),
+            summary.jain_index,
+            summary.vruntime_spread
+        ));
+    }
+    csv
+}
+
+// The state of the scheduler at the end of a tick
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Sample {
+    // The tick
+    pub time: u128,
+    // Tasks left on the run queue
+    pub runnable: usize,
+    // Smallest vruntime on the run queue, which only ever grows
+    pub min_vruntime: u64,
+    // Task that ran on the tick, if any
+    pub running: Option<u16>,
+    // Share of the CPUs that ran a task on the tick
+    pub utilisation: f64,
+    // Share of the CPUs that ran a task over every tick so far
+    pub cumulative_utilisation: f64,
+}
+
+// Columns of the per-tick CSV time series
+const SAMPLE_COLUMNS: &str = "time,runnable,min_vruntime,running,utilisation,cumulative_utilisation";
+
+// Builds the time series of a run out of its scheduling events, with a sample per tick
+#[derive(Clone, Debug, Default)]
+pub struct SeriesBuilder {
+    // Task picked on the current tick
+    running: Option<u16>,
+    // Ticks seen and ticks a task ran on
+    ticks: u64,
+    busy: u64,
+}
+
+impl SeriesBuilder {
+    // Creates a builder with no ticks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reads an event, returning the sample of the tick it ends, if any
+    pub fn event(&mut self, event: &Event) -> Option<Sample> {
+        match event.kind {
+            EventKind::Picked { task, .. } => {
+                self.running = Some(task.id);
+                None
+            }
+            EventKind::Tick { runnable, min_vruntime } => {
+                let running = self.running.take();
+                let utilisation = if running.is_some() { 1.0 } else { 0.0 };
+                self.ticks += 1;
+                self.busy += running.is_some() as u64;
+                Some(Sample {
+                    time: event.time,
+                    runnable,
+                    min_vruntime,
+                    running,
+                    utilisation,
+                    cumulative_utilisation: self.busy as f64 / self.ticks as f64,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// Returns the samples of every tick of the given events
+pub fn series(events: &[Event]) -> Vec<Sample> {
+    let mut builder = SeriesBuilder::new();
+    events.iter().filter_map(|event| builder.event(event)).collect()
+}
+
+// Returns a sample as a CSV row
+fn sample_row(sample: &Sample) -> String {
+    format!(
+        "{},{},{},{},{:.4},{:.4}",
+        sample.time,
+        sample.runnable,
+        sample.min_vruntime,
+        field(sample.running),
+        sample.utilisation,
+        sample.cumulative_utilisation
+    )
+}
+
+// Returns the given samples as CSV, with a header and a row per tick. The running column is empty
+// on ticks the CPU was idle.
+pub fn series_csv(samples: &[Sample]) -> String {
+    let mut csv = format!("{}\n", SAMPLE_COLUMNS);
+    for sample in samples {
+        csv.push_str(&sample_row(sample));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Writes lines to a writer for a sink, keeping the first error to return once the run is over
+struct Lines<W: Write + Send> {
+    writer: W,
+    error: Option<std::io::Error>,
+    // What is written, for the error message
+    what: &'static str,
+}
+
+impl<W: Write + Send> Lines<W> {
+    fn write(&mut self, line: &str) {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{}", line).err();
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        let flushed = self.writer.flush();
+        match self.error.take().or(flushed.err()) {
+            Some(e) => Err(trace::sink_error(self.what, e)),
+            None => Ok(()),
+        }
+    }
+}
+
+// A sink writing every event of a run as a line of JSON, in the same form events serialise to
+pub struct JsonLines<W: Write + Send> {
+    lines: Lines<W>,
+}
+
+impl<W: Write + Send> JsonLines<W> {
+    // Creates a sink writing the events to the given writer, such as a file
+    pub fn new(writer: W) -> Self {
+        Self { lines: Lines { writer, error: None, what: "event log" } }
+    }
+}
+
+impl<W: Write + Send> TraceSink for JsonLines<W> {
+    fn event(&mut self, event: &Event) {
+        match serde_json::to_string(event) {
+            Ok(line) => self.lines.write(&line),
+            Err(e) => {
+                self.lines.error.get_or_insert(e.into());
+            }
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.lines.finish()
+    }
+}
+
+// A sink writing the time series of a run as CSV as it goes
+pub struct SeriesCsv<W: Write + Send> {
+    builder: SeriesBuilder,
+    lines: Lines<W>,
+    // Whether the header was written
+    started: bool,
+}
+
+impl<W: Write + Send> SeriesCsv<W> {
+    // Creates a sink writing the time series to the given writer, such as a file
+    pub fn new(writer: W) -> Self {
+        Self { builder: SeriesBuilder::new(), lines: Lines { writer, error: None, what: "time series" }, started: false }
+    }
+}
+
+impl<W: Write + Send> TraceSink for SeriesCsv<W> {
+    fn event(&mut self, event: &Event) {
+        if !std::mem::replace(&mut self.started, true) {
+            self.lines.write(SAMPLE_COLUMNS);
+        }
+        if let Some(sample) = self.builder.event(event) {
+            self.lines.write(&sample_row(&sample));
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.builder = SeriesBuilder::new();
+        self.started = false;
+        self.lines.finish()
+    }
+}
@@ -2,19 +2,49 @@
 extern crate raw_pointer as rptr;
 extern crate rbtree;
 use super::clock::Clock;
-use crate::proc::task::{Task, TaskStatus};
+use super::timer::TimerQueue;
+use super::trace::{self, Event, EventKind, TaskRef, TraceSink};
+use crate::error::Error;
+use crate::io::device::{Device, DeviceStats, DEFAULT_DEVICE};
+use crate::proc::script::Phase;
+use crate::proc::task::{BlockReason, Task, TaskStatus, TransitionError};
+use crate::sync::table::SyncTable;
 use rbtree::RBTree;
 use rptr::Pointer;
-use std::collections::VecDeque;
+use std::collections::BTreeMap;
 
 // Struct definition for FairAlgorithm
 pub struct FairAlgorithm {
     // Red-black tree for tasks sorted by key (u64)
     tree: RBTree<u64, Task>,
-    // Vector deque for idle tasks
-    idle: VecDeque<Task>,
+    // Devices serving the I/O bursts of idle tasks, by name
+    devices: BTreeMap<String, Device>,
+    // Timers waking sleeping tasks
+    timers: TimerQueue,
     // Pointer to the Clock object
     clock: Pointer<Clock>,
+    // Id handed out to the next forked task, which is past the last id once they run out
+    next_pid: u32,
+    // Tasks that terminated since they were last taken
+    exited: Vec<Task>,
+    // Tasks held off the run queue until they are continued
+    stopped: Vec<Task>,
+    // Simulated synchronisation primitives, holding the tasks blocked on them
+    sync: SyncTable,
+    // Whether mutex holders inherit the weight of the tasks blocked on them
+    inherit: bool,
+    // Id of the task picked to run on the current tick, until it is inserted back
+    running: Option<u16>,
+    // Number of times a task was picked to run
+    picks: u64,
+    // Earliest tick tasks joining the run queue can run at, later than now once the CPU of the current tick is taken
+    next_free: u128,
+    // Largest difference between the smallest and largest vruntime on the run queue so far
+    vruntime_spread: u64,
+    // Smallest vruntime on the run queue at the end of a tick, never going back
+    min_vruntime: u64,
+    // Sinks every scheduling event is delivered to
+    sinks: Vec<Box<dyn TraceSink>>,
 }
 
 // Implementation block for FairAlgorithm
@@ -24,58 +54,399 @@ impl FairAlgorithm {
         Self {
             // Initialize the red-black tree
             tree: RBTree::new(),
-            // Initialize the vector deque for idle tasks
-            idle: VecDeque::new(),
+            // Devices are created on first use unless added beforehand
+            devices: BTreeMap::new(),
+            // No task is sleeping yet
+            timers: TimerQueue::new(),
             // Store the clock object in a raw pointer
             clock: Pointer::new(clock),
+            // Forked tasks get ids above every task seen so far
+            next_pid: 1,
+            // No task has terminated yet
+            exited: Vec::new(),
+            // No task has been stopped yet
+            stopped: Vec::new(),
+            // No primitive exists until a task uses it
+            sync: SyncTable::new(),
+            // Priority inheritance is off unless enabled
+            inherit: false,
+            // No task has run yet
+            running: None,
+            picks: 0,
+            next_free: 0,
+            vruntime_spread: 0,
+            min_vruntime: 0,
+            // Events go nowhere unless a sink is added
+            sinks: Vec::new(),
         }
     }
 
-    // Function to insert tasks into the FairAlgorithm object
+    // Function to declare a semaphore with the given initial count
+    pub fn declare_semaphore(&mut self, name: &str, count: u32) {
+        self.sync.declare_semaphore(name, count);
+    }
+
+    // Function to enable or disable priority inheritance on mutexes
+    pub fn set_priority_inheritance(&mut self, inherit: bool) {
+        self.inherit = inherit;
+    }
+
+    // Function to add a device, replacing any device with the same name
+    pub fn add_device(&mut self, device: Device) {
+        self.devices.insert(String::from(device.name()), device);
+    }
+
+    // Function to get the statistics of every device, in order of name
+    pub fn device_stats(&self) -> Vec<DeviceStats> {
+        self.devices.values().map(|device| device.stats().clone()).collect()
+    }
+
+    // Function to set the ticks the timers of sleeping tasks may fire late to coalesce wakeups
+    pub fn set_timer_slack(&mut self, slack: u128) {
+        self.timers.set_slack(slack);
+    }
+
+    // Function to access the synchronisation primitives
+    pub fn sync(&self) -> &SyncTable {
+        &self.sync
+    }
+
+    // Function to add a sink receiving every scheduling event from now on
+    pub fn add_sink(&mut self, sink: Box<dyn TraceSink>) {
+        self.sinks.push(sink);
+    }
+
+    // Function to flush every event sink once the run is over, returning the first error a sink had
+    pub fn finish_sinks(&mut self) -> std::io::Result<()> {
+        let mut result = Ok(());
+        for sink in self.sinks.iter_mut() {
+            let finished = sink.finish();
+            if result.is_ok() {
+                result = finished;
+            }
+        }
+        result
+    }
+
+    // Function to take the event sinks back
+    pub fn take_sinks(&mut self) -> Vec<Box<dyn TraceSink>> {
+        std::mem::take(&mut self.sinks)
+    }
+
+    // Function to deliver an event happening now to every sink
+    fn emit(&mut self, kind: EventKind) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let event = Event { time: self.clock.time(), kind };
+        for sink in self.sinks.iter_mut() {
+            sink.event(&event);
+        }
+    }
+
+    // Function to make sure forked tasks never reuse ids up to and including `last`
+    pub fn reserve_pids(&mut self, last: u16) {
+        self.next_pid = self.next_pid.max(u32::from(last) + 1);
+    }
+
+    // Function to insert tasks into the FairAlgorithm object, stopping at the first task that
+    // cannot make the transition it is due or misuses a synchronisation primitive
     #[inline]
-    pub fn push(&mut self, tasks: Vec<Task>) {
+    pub fn push(&mut self, tasks: Vec<Task>) -> Result<(), Error> {
         // Iterate through the tasks in the input vector
         for task in tasks {
             // Call the insert function for each task
-            self.insert(task);
+            self.insert(task)?;
         }
+        Ok(())
     }
 
     // Function to insert a task into the FairAlgorithm object
     #[inline]
-    pub fn insert(&mut self, mut task: Task) {
+    pub fn insert(&mut self, task: Task) -> Result<(), Error> {
+        self.enqueue(task, None)
+    }
+
+    // Function to insert a task, forked by the given parent if it is a new child
+    fn enqueue(&mut self, mut task: Task, parent: Option<u16>) -> Result<(), Error> {
+        // vruntime is divided by the weight, so tasks of weight 0 are refused
+        if task.weight() == 0 {
+            return Err(Error::ZeroWeight(task.get_id()));
+        }
+        if task.get_status() == TaskStatus::New {
+            self.emit(EventKind::Born { task: TaskRef::new(&task), parent });
+        }
+        // Keep forked ids clear of the task's id
+        self.reserve_pids(task.get_id());
+        // Whether the task comes straight from the CPU, and the tick it can run again otherwise
+        let now = self.clock.time();
+        let from_cpu = self.running == Some(task.get_id());
+        if from_cpu {
+            self.running = None;
+        }
+        let ready = now.max(self.next_free);
+        loop {
+            // Spawn the children the task forked since it was last inserted
+            for script in task.take_forks() {
+                let pid = u16::try_from(self.next_pid).map_err(|_| Error::PidsExhausted)?;
+                let mut child = Task::scripted(
+                    pid,
+                    task.get_cpu_time(),
+                    script,
+                    self.clock.time(),
+                    task.base_weight(),
+                );
+                child.seed(task.child_seed());
+                self.reserve_pids(child.get_id());
+                self.enqueue(child, Some(task.get_id()))?;
+            }
+            // Carry out the synchronisation operation the task stopped at, if any
+            let op = match task.take_pending() {
+                Some(op) => op,
+                None => break,
+            };
+            let id = task.get_id();
+            let outcome = self.sync.execute(op, task, self.clock.time())?;
+            // Tasks woken by the operation go back to the run queue
+            let woken: Vec<u16> = outcome.woken.iter().map(|task| task.get_id()).collect();
+            for task in outcome.woken.iter() {
+                self.emit(EventKind::Woken { task: TaskRef::new(task) });
+            }
+            self.push(outcome.woken)?;
+            // Woken tasks may have been handed a mutex others are waiting on
+            if self.inherit {
+                for id in woken {
+                    self.refresh_inheritance(id);
+                }
+            }
+            // A task that blocked leaves the run queue until it is woken, passing its weight
+            // on to the holder of the mutex it blocked on
+            task = match outcome.caller {
+                Some(task) => task,
+                None => {
+                    if let Some(task) = self.sync.task_mut(id) {
+                        match from_cpu {
+                            true => task.stats_mut().left_cpu(now, false),
+                            false => task.stats_mut().blocked_at(ready, false),
+                        }
+                        let blocked = EventKind::Blocked { task: TaskRef::new(task), state: task.get_status() };
+                        self.emit(blocked);
+                    }
+                    if self.inherit {
+                        self.refresh_inheritance(id);
+                    }
+                    return Ok(());
+                }
+            };
+            // A task that released a mutex no longer inherits from its waiters
+            if self.inherit {
+                task.set_inherited(self.sync.max_waiter_weight(id));
+            }
+            task.resume()?;
+        }
+        // A new task whose script starts off the CPU goes straight to sleep or to its device
+        if task.get_status() == TaskStatus::New && !task.wants_cpu() {
+            match task.get_phase() {
+                Some(Phase::Sleep(_)) | Some(Phase::SleepUntil(_)) => task.to_sleep()?,
+                _ => task.block(BlockReason::Io)?,
+            }
+        }
         // Get the task's status
         let state = task.get_status();
-        // If the task is terminated, return immediately
-        if state == TaskStatus::Terminated {
-            return;
+        // If the task has exited, keep it aside for the scheduler to reap and return immediately
+        if state.has_exited() {
+            let weight = task.base_weight();
+            task.stats_mut().exited(if from_cpu { now + 1 } else { ready }, from_cpu, weight);
+            self.emit(EventKind::Terminated { task: TaskRef::new(&task) });
+            self.exited.push(task);
+            return Ok(());
         }
-        // If the task is idle, add it to the idle queue
-        else if state == TaskStatus::Idle {
-            self.idle.push_back(task);
-            return;
+        // If the task is stopped, hold it off the run queue until it is continued
+        else if state == TaskStatus::Stopped {
+            self.stopped.push(task);
+            return Ok(());
+        }
+        // A task leaving the CPU or starting off it to sleep or do I/O is blocked from then on
+        if state == TaskStatus::Sleeping || state == (TaskStatus::Blocked { reason: BlockReason::Io }) {
+            let io = state != TaskStatus::Sleeping;
+            match from_cpu {
+                true => task.stats_mut().left_cpu(now, io),
+                false => task.stats_mut().blocked_at(ready, io),
+            }
+            self.emit(EventKind::Blocked { task: TaskRef::new(&task), state });
+        }
+        // If the task is sleeping, arm a timer to wake it once it has been off the CPU for the
+        // length of its sleep, counting from the tick after it ran if it comes from the CPU
+        if state == TaskStatus::Sleeping {
+            let expires = match task.get_phase() {
+                Some(Phase::SleepUntil(time)) => *time,
+                _ => ready + u128::from(task.get_phase_left()),
+            };
+            self.timers.arm(task, expires);
+            return Ok(());
+        }
+        // If the task is blocked on I/O, submit its I/O to the device it targets
+        else if state == (TaskStatus::Blocked { reason: BlockReason::Io }) {
+            let device = match task.get_phase() {
+                Some(Phase::Io(_, device)) | Some(Phase::Write(_, device)) => device.as_deref().unwrap_or(DEFAULT_DEVICE),
+                _ => DEFAULT_DEVICE,
+            };
+            self.emit(EventKind::IoSubmitted { task: TaskRef::new(&task), device: String::from(device) });
+            self.devices
+                .entry(String::from(device))
+                .or_insert_with(|| Device::new(device, None))
+                .submit(task, ready);
+            return Ok(());
         }
         // Calculate the task's key (vruntime) using the clock's time
-        let key: u64 = task.vruntime(self.clock.time());
-        // Schedule the task
-        task.schedule();
+        let key: u64 = task.vruntime(now);
+        // Schedule the task, which stays runnable from the next tick if it was preempted
+        task.schedule()?;
+        if from_cpu {
+            task.stats_mut().preempted(now, self.picks);
+            self.emit(EventKind::Preempted { task: TaskRef::new(&task) });
+        }
+        task.stats_mut().queued(ready);
+        self.emit(EventKind::Enqueued { task: TaskRef::new(&task), vruntime: key });
         // Insert the task into the red-black tree using the key as the key
         self.tree.insert(key, task);
+        // Keep track of how far apart the vruntimes on the run queue drift
+        if let (Some((first, _)), Some((last, _))) = (self.tree.get_first(), self.tree.get_last()) {
+            self.vruntime_spread = self.vruntime_spread.max(last - first);
+        }
+        Ok(())
+    }
+
+    // Function to stop a runnable task, holding it off the run queue until it is continued.
+    // Returns false if no such task is queued, and an error if the task is waiting for something.
+    pub fn stop(&mut self, id: u16) -> Result<bool, TransitionError> {
+        if let Some(mut task) = self.dequeue(id) {
+            task.stop()?;
+            self.stopped.push(task);
+            return Ok(true);
+        }
+        match self.waiting_mut(id) {
+            Some(task) => task.stop().map(|_| true),
+            None => Ok(false),
+        }
     }
 
-    // Function to pop a task from the FairAlgorithm object
+    // Function to continue a stopped task, putting it back on the run queue.
+    // Returns false if no such task is stopped.
+    pub fn cont(&mut self, id: u16) -> Result<bool, Error> {
+        let idx = match self.stopped.iter().position(|task| task.get_id() == id) {
+            Some(idx) => idx,
+            None => return Ok(false),
+        };
+        let mut task = self.stopped.remove(idx);
+        task.cont()?;
+        self.insert(task)?;
+        Ok(true)
+    }
+
+    // Function to take a task off the red-black tree
+    fn dequeue(&mut self, id: u16) -> Option<Task> {
+        let key = self.tree.iter().find(|(_, task)| task.get_id() == id).map(|(key, _)| *key)?;
+        // Take the tasks sharing the key off the tree until the one asked for turns up
+        let mut others = Vec::new();
+        let mut found = None;
+        while let Some(task) = self.tree.remove(&key) {
+            if task.get_id() == id {
+                found = Some(task);
+                break;
+            }
+            others.push(task);
+        }
+        for task in others {
+            self.tree.insert(key, task);
+        }
+        found
+    }
+
+    // Function to find a task waiting on a device, a timer or a synchronisation primitive
+    fn waiting_mut(&mut self, id: u16) -> Option<&mut Task> {
+        let idle = self.devices.values_mut().find_map(|device| device.task_mut(id));
+        match idle.or_else(|| self.timers.task_mut(id)) {
+            Some(task) => Some(task),
+            None => self.sync.task_mut(id),
+        }
+    }
+
+    // Function to recompute the weight a task inherits from the tasks blocked on its mutexes,
+    // following the chain of holders when the task is itself blocked on a mutex
+    fn refresh_inheritance(&mut self, id: u16) {
+        let mut curr = Some(id);
+        let mut first = true;
+        while let Some(id) = curr {
+            let inherited = self.sync.max_waiter_weight(id);
+            // Stop once a holder further up the chain is unaffected
+            if !self.reweight(id, inherited) && !first {
+                break;
+            }
+            first = false;
+            curr = self.sync.waiting_on(id).and_then(|mutex| self.sync.owner(mutex));
+        }
+    }
+
+    // Function to set the weight a task inherits wherever it is queued, returning whether its
+    // effective weight changed. A task in the red-black tree is dequeued and requeued.
+    fn reweight(&mut self, id: u16, inherited: u32) -> bool {
+        if let Some(mut task) = self.dequeue(id) {
+            let changed = task.set_inherited(inherited);
+            self.tree.insert(task.get_vruntime(), task);
+            return changed;
+        }
+        if let Some(task) = self.stopped.iter_mut().find(|task| task.get_id() == id) {
+            return task.set_inherited(inherited);
+        }
+        match self.waiting_mut(id) {
+            Some(task) => task.set_inherited(inherited),
+            None => false,
+        }
+    }
+
+    // Function to pop a task from the FairAlgorithm object, failing if the tree is empty
+    #[inline]
+    pub fn try_pop(&mut self) -> Result<Box<Task>, Error> {
+        // Pop the first task from the red-black tree
+        let mut task = match self.tree.pop_first() {
+            Some((_, task)) => Box::new(task),
+            None => return Err(Error::EmptyRunQueue),
+        };
+        // Run the task, taking the CPU for the current tick
+        task.run()?;
+        let now = self.clock.time();
+        self.picks += 1;
+        task.stats_mut().picked(now, self.picks);
+        self.running = Some(task.get_id());
+        self.next_free = now + 1;
+        self.emit(EventKind::Picked { task: TaskRef::new(&task), cpu: trace::CPU });
+
+        Ok(task)
+    }
+
+    // Function to pop a task from the FairAlgorithm object, panicking if the tree is empty
     #[inline]
     pub fn pop(&mut self) -> Box<Task> {
-        // If the tree is empty, panic
-        if self.is_empty() {
-            panic!("Attempted to pop from an empty tree");
+        match self.try_pop() {
+            Ok(task) => task,
+            Err(e) => panic!("Attempted to pop from the tree: {}", e),
         }
-        // Pop the first task from the red-black tree
-        let mut task = Box::new(self.tree.pop_first().unwrap().1);
-        // Run the task
-        task.run();
+    }
+
+    // Function to get the smallest vruntime on the run queue as of the last tick, which only ever grows
+    pub fn min_vruntime(&self) -> u64 {
+        self.min_vruntime
+    }
+
+    // Function to get the largest difference between the smallest and largest vruntime seen on the run queue
+    pub fn vruntime_spread(&self) -> u64 {
+        self.vruntime_spread
+    }
 
-        task
+    // Function to take the tasks that terminated since the last call, still to be reaped
+    pub fn take_exited(&mut self) -> Vec<Task> {
+        std::mem::take(&mut self.exited)
     }
 
     // Function to check if the red-black tree in the FairAlgorithm object is empty
@@ -85,39 +456,65 @@ impl FairAlgorithm {
         self.tree.is_empty()
     }
 
-    // Function to check if the FairAlgorithm object is finished running all tasks
+    // Function to check if the FairAlgorithm object is finished running all tasks.
+    // Tasks blocked on synchronisation primitives at that point can never be woken.
     #[inline]
     pub fn is_finished(&self) -> bool {
-        // Return whether the red-black tree is empty and the idle queue has no tasks
-        self.is_empty() && self.idle.len() == 0
+        // Return whether the red-black tree is empty and no task is doing I/O or sleeping
+        self.is_empty() && self.devices.values().all(|device| device.is_idle()) && self.timers.is_empty()
+    }
+
+    // Function to list the tasks blocked on synchronisation primitives or stopped, and what they wait on
+    pub fn parked(&self) -> Vec<(u16, String)> {
+        let mut parked = self.sync.parked();
+        parked.extend(self.stopped.iter().map(|task| (task.get_id(), String::from("a continue"))));
+        parked.sort();
+        parked
     }
 
     // Function to run a task
-    pub fn run(&mut self) {
+    pub fn run(&mut self) -> Result<(), Error> {
         // If the red-black tree is empty, return
         if self.is_empty() {
-            return;
+            return Ok(());
         }
         // Pop the first task from the red-black tree
-        let mut task = *self.pop();
+        let mut task = *self.try_pop()?;
         // Run a CPU cycle for the task
-        task.cpu_cycle();
+        task.cpu_cycle()?;
         // Insert the task back into the FairAlgorithm object
-        self.insert(task);
+        self.insert(task)
     }
 
-    // Function to perform an IO cycle for an idle task
-    pub fn idle(&mut self) {
-        // If the idle queue has no tasks, return
-        if self.idle.len() == 0 {
-            return;
+    // Function to advance every device by a tick and wake the sleeping tasks whose timers fire
+    pub fn idle(&mut self) -> Result<(), Error> {
+        // Collect the tasks whose I/O completed on every device, which can run from the next tick
+        let now = self.clock.time();
+        self.next_free = now + 1;
+        let mut completed = Vec::new();
+        for (name, device) in self.devices.iter_mut() {
+            completed.extend(device.cycle(now)?.into_iter().map(|task| (name.clone(), task)));
         }
-        // Pop the first task from the idle queue
-        let mut curr = self.idle.pop_front().unwrap();
-        // Run an IO cycle for the task
-        curr.io_cycle();
-        // Insert the task back into the FairAlgorithm object
-        self.insert(curr);
+        let mut done = Vec::new();
+        for (device, task) in completed {
+            self.emit(EventKind::IoCompleted { task: TaskRef::new(&task), device });
+            done.push(task);
+        }
+        // Fire the timers expiring by the next tick, so their tasks are runnable on the tick they asked for
+        for mut task in self.timers.expire(now + 1) {
+            task.wake()?;
+            done.push(task);
+        }
+        for task in done.iter() {
+            self.emit(EventKind::Woken { task: TaskRef::new(task) });
+        }
+        // Insert the tasks back into the FairAlgorithm object, ending the tick
+        self.push(done)?;
+        if let Some((first, _)) = self.tree.get_first() {
+            self.min_vruntime = self.min_vruntime.max(*first);
+        }
+        self.emit(EventKind::Tick { runnable: self.tree.len(), min_vruntime: self.min_vruntime });
+        Ok(())
     }
 }
 // Implement the Sync trait for FairAlgorithm to make it thread-safe
@@ -0,0 +1,212 @@
+use super::trace::{self, Event, EventKind, TaskRef, TraceSink, CPU};
+use crate::proc::task::{BlockReason, TaskStatus};
+use std::io::Write;
+
+// Header of the trace, as at the top of the kernel's trace file
+const HEADER: &str = "# tracer: nop
+#
+#           TASK-PID     CPU#  ||||   TIMESTAMP  FUNCTION
+#              | |         |   ||||      |         |";
+
+// Returns the command name of a task
+fn comm(task: &TaskRef) -> String {
+    format!("task{}", task.id)
+}
+
+// Returns the kernel priority of a task, 120 plus its nice value
+fn prio(task: &TaskRef) -> i16 {
+    120 + i16::from(task.nice)
+}
+
+// Returns the letter the kernel shows for a task leaving the CPU in the given state
+fn state(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::New | TaskStatus::Runnable | TaskStatus::Running => "R",
+        TaskStatus::Blocked { reason: BlockReason::Io } => "D",
+        TaskStatus::Blocked { .. } | TaskStatus::Sleeping => "S",
+        TaskStatus::Stopped => "T",
+        TaskStatus::Zombie => "Z",
+        TaskStatus::Dead => "X",
+    }
+}
+
+// Turns scheduling events into the lines of an ftrace text trace with sched_switch, sched_wakeup,
+// sched_wakeup_new, sched_migrate_task and sched_process_exit events, as read by trace-cmd and
+// kernelshark style parsers. A tick is shown as a microsecond. When no task is picked on the tick
+// after the task on the CPU blocks or exits, the CPU switches to the idle task.
+#[derive(Clone, Debug, Default)]
+pub struct FtraceBuilder {
+    // Task on the CPU, with the state it leaves the CPU in and the last tick it ran
+    curr: Option<(TaskRef, &'static str, u128)>,
+}
+
+impl FtraceBuilder {
+    // Creates a builder with the CPU idle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the lines the event adds to the trace
+    pub fn event(&mut self, event: &Event) -> Vec<String> {
+        let time = event.time;
+        // Tasks are born before a task is picked on a tick and everything else happens after, so the
+        // CPU went idle if its task did not run on the tick before or on this one respectively
+        let mut lines: Vec<String> = match event.kind {
+            EventKind::Born { .. } | EventKind::Picked { .. } => self.idle(time.saturating_sub(1)).into_iter().collect(),
+            _ => self.idle(time).into_iter().collect(),
+        };
+        match &event.kind {
+            EventKind::Born { task, .. } => {
+                let fields = format!("comm={} pid={} prio={} target_cpu={:03}", comm(task), task.id, prio(task), CPU);
+                lines.push(self.line(time, "sched_wakeup_new", &fields));
+            }
+            // A task woken on a tick is runnable from the next one
+            EventKind::Woken { task } => {
+                let fields = format!("comm={} pid={} prio={} target_cpu={:03}", comm(task), task.id, prio(task), CPU);
+                lines.push(self.line(time + 1, "sched_wakeup", &fields));
+            }
+            EventKind::Migrated { task, from, to } => {
+                let fields = format!("comm={} pid={} prio={} orig_cpu={} dest_cpu={}", comm(task), task.id, prio(task), from, to);
+                lines.push(self.line(time, "sched_migrate_task", &fields));
+            }
+            // A task picked again right after its last tick keeps the CPU without a switch
+            EventKind::Picked { task, .. } => {
+                if let Some((prev, _, ran)) = self.curr {
+                    if prev.id == task.id && ran + 1 == time {
+                        self.curr = Some((*task, "R", time));
+                        return lines;
+                    }
+                }
+                lines.push(self.switch(time, Some(*task)));
+            }
+            EventKind::Preempted { task } => self.leave(task, "R"),
+            EventKind::Blocked { task, state: status } => self.leave(task, state(*status)),
+            EventKind::Terminated { task } => {
+                let fields = format!("comm={} pid={} prio={}", comm(task), task.id, prio(task));
+                lines.push(format(&comm(task), task.id, time, "sched_process_exit", &fields));
+                self.leave(task, "Z");
+            }
+            _ => {}
+        }
+        lines
+    }
+
+    // Returns the switch to the idle task if the task on the CPU did not run on the given tick,
+    // happening on the tick after it last ran
+    fn idle(&mut self, time: u128) -> Option<String> {
+        match self.curr {
+            Some((_, _, ran)) if ran < time => Some(self.switch(ran + 1, None)),
+            _ => None,
+        }
+    }
+
+    // Returns the lines ending the trace, switching to the idle task after the last task leaves the CPU
+    pub fn finish(&mut self) -> Vec<String> {
+        let lines = match self.curr {
+            Some((_, _, ran)) => self.idle(ran + 1).into_iter().collect(),
+            None => vec![],
+        };
+        *self = Self::default();
+        lines
+    }
+
+    // Records the state the task on the CPU leaves it in
+    fn leave(&mut self, task: &TaskRef, status: &'static str) {
+        if let Some((curr, state, _)) = self.curr.as_mut() {
+            if curr.id == task.id {
+                *state = status;
+            }
+        }
+    }
+
+    // Returns the sched_switch line from the task on the CPU, or the idle task, to the next one
+    fn switch(&mut self, time: u128, next: Option<TaskRef>) -> String {
+        let (prev_comm, prev_pid, prev_prio, prev_state) = match self.curr {
+            Some((task, status, _)) => (comm(&task), task.id, prio(&task), status),
+            None => (format!("swapper/{}", CPU), 0, 120, "R"),
+        };
+        let (next_comm, next_pid, next_prio) = match next {
+            Some(task) => (comm(&task), task.id, prio(&task)),
+            None => (format!("swapper/{}", CPU), 0, 120),
+        };
+        let fields = format!(
+            "prev_comm={} prev_pid={} prev_prio={} prev_state={} ==> next_comm={} next_pid={} next_prio={}",
+            prev_comm, prev_pid, prev_prio, prev_state, next_comm, next_pid, next_prio
+        );
+        let line = self.line(time, "sched_switch", &fields);
+        self.curr = next.map(|task| (task, "R", time));
+        line
+    }
+
+    // Returns a line of an event happening in the context of the task on the CPU
+    fn line(&self, time: u128, name: &str, fields: &str) -> String {
+        match &self.curr {
+            Some((task, ..)) => format(&comm(task), task.id, time, name, fields),
+            None => format("<idle>", 0, time, name, fields),
+        }
+    }
+}
+
+// Returns a line of an event happening in the context of the task with the given command name and pid
+fn format(comm: &str, pid: u16, time: u128, name: &str, fields: &str) -> String {
+    let (secs, usecs) = (time / 1_000_000, time % 1_000_000);
+    format!("{:>16}-{:<7} [{:03}] d..2 {:>5}.{:06}: {}: {}", comm, pid, CPU, secs, usecs, name, fields)
+}
+
+// Returns the ftrace text trace of the given events, header included
+pub fn text(events: &[Event]) -> String {
+    let mut builder = FtraceBuilder::new();
+    let mut lines = vec![String::from(HEADER)];
+    for event in events {
+        lines.extend(builder.event(event));
+    }
+    lines.extend(builder.finish());
+    lines.join("\n") + "\n"
+}
+
+// A sink writing the ftrace text trace of a run as it goes. The first write error is kept and
+// returned from `finish` once the run is over, failing the run.
+pub struct Ftrace<W: Write + Send> {
+    builder: FtraceBuilder,
+    writer: W,
+    // Whether the header was written, and the first write error
+    started: bool,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write + Send> Ftrace<W> {
+    // Creates a sink writing the trace to the given writer, such as a file
+    pub fn new(writer: W) -> Self {
+        Self { builder: FtraceBuilder::new(), writer, started: false, error: None }
+    }
+
+    // Writes a line, keeping the first error
+    fn write(&mut self, line: &str) {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{}", line).err();
+        }
+    }
+}
+
+impl<W: Write + Send> TraceSink for Ftrace<W> {
+    fn event(&mut self, event: &Event) {
+        if !std::mem::replace(&mut self.started, true) {
+            self.write(HEADER);
+        }
+        for line in self.builder.event(event) {
+            self.write(&line);
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        for line in self.builder.finish() {
+            self.write(&line);
+        }
+        let flushed = self.writer.flush();
+        self.started = false;
+        match self.error.take().or(flushed.err()) {
+            Some(e) => Err(trace::sink_error("ftrace trace", e)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use super::trace::{Event, EventKind};
+use crate::proc::task::{BlockReason, TaskStatus};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+// What a task is doing during a segment of a Gantt chart
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Segment {
+    // On the CPU
+    Running,
+    // Runnable, waiting on the run queue
+    Waiting,
+    // Blocked on an I/O
+    Io,
+    // Sleeping on a timer, or blocked on its dependencies or a synchronisation primitive
+    Blocked,
+}
+
+impl Segment {
+    // Returns the character drawing the segment in ASCII
+    fn symbol(&self) -> char {
+        match self {
+            Segment::Running => '#',
+            Segment::Waiting => '-',
+            Segment::Io => '=',
+            Segment::Blocked => '_',
+        }
+    }
+
+    // Returns the colour of the segment in SVG
+    fn colour(&self) -> &'static str {
+        match self {
+            Segment::Running => "#4caf50",
+            Segment::Waiting => "#ffc107",
+            Segment::Io => "#2196f3",
+            Segment::Blocked => "#bdbdbd",
+        }
+    }
+}
+
+// What a row of a Gantt chart shows over a span of ticks: a segment of a task, or a task run by a CPU
+#[derive(Clone, Copy, Debug)]
+enum Span {
+    Segment(Segment),
+    Task(u16),
+}
+
+// A row of a Gantt chart, with its label and its spans and the ticks they start and end at
+type Row = (String, Vec<(u128, u128, Span)>);
+
+// Whether a Gantt chart has a row per CPU, showing the task running on it, or a row per task,
+// showing what it is doing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rows {
+    Cpus,
+    Tasks,
+}
+
+// The state of a task while its events are read
+#[derive(Clone, Copy, Debug, Default)]
+struct Track {
+    // What the task is doing, and the tick it has been since
+    segment: Option<(Segment, u128)>,
+    // The last tick the task ran on and was woken on, after which its next state starts
+    ran: Option<u128>,
+    woken: Option<u128>,
+}
+
+// A Gantt chart of a run built from its scheduling events, with the segments every task spent
+// running, waiting, doing I/O or blocked, what every CPU ran, and the ticks tasks were preempted on
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gantt {
+    // Tick the chart ends at, after the last event
+    end: u128,
+    // Segments of every task, with the ticks they start and end at
+    tasks: BTreeMap<u16, Vec<(u128, u128, Segment)>>,
+    // Tasks every CPU ran, with the ticks they ran from and to
+    cpus: BTreeMap<u16, Vec<(u128, u128, u16)>>,
+    // Ticks at which a task lost the CPU to another while it was still runnable, on the CPU it ran on
+    preemptions: Vec<(u128, u16, u16)>,
+}
+
+impl Gantt {
+    // Builds the chart of a run from its events
+    pub fn new(events: &[Event]) -> Self {
+        let mut gantt = Self::default();
+        let mut tracks: BTreeMap<u16, Track> = BTreeMap::new();
+        // The task each CPU ran last and whether it was preempted, for telling preemptions apart
+        let mut last: BTreeMap<u16, (u16, u128, bool)> = BTreeMap::new();
+
+        for event in events {
+            let time = event.time;
+            gantt.end = gantt.end.max(time + 1);
+            let (id, next) = match &event.kind {
+                EventKind::Picked { task, cpu } => {
+                    // A task still runnable on the CPU last tick was preempted if another task runs now
+                    if let Some((prev, at, true)) = last.get(cpu).copied() {
+                        if prev != task.id && at + 1 == time {
+                            gantt.preemptions.push((time, *cpu, prev));
+                        }
+                    }
+                    last.insert(*cpu, (task.id, time, false));
+                    let runs = gantt.cpus.entry(*cpu).or_default();
+                    match runs.last_mut() {
+                        Some((_, end, id)) if *id == task.id && *end == time => *end = time + 1,
+                        _ => runs.push((time, time + 1, task.id)),
+                    }
+                    let track = tracks.entry(task.id).or_default();
+                    track.ran = Some(time);
+                    (task.id, Some((Segment::Running, time)))
+                }
+                EventKind::Preempted { task } => {
+                    for (prev, _, preempted) in last.values_mut() {
+                        if *prev == task.id {
+                            *preempted = true;
+                        }
+                    }
+                    (task.id, Some((Segment::Waiting, time)))
+                }
+                EventKind::Born { task, .. } | EventKind::Enqueued { task, .. } => (task.id, Some((Segment::Waiting, time))),
+                EventKind::Woken { task } => {
+                    tracks.entry(task.id).or_default().woken = Some(time);
+                    (task.id, Some((Segment::Waiting, time)))
+                }
+                EventKind::Blocked { task, state } => {
+                    let segment = match state {
+                        TaskStatus::Blocked { reason: BlockReason::Io } => Segment::Io,
+                        _ => Segment::Blocked,
+                    };
+                    (task.id, Some((segment, time)))
+                }
+                EventKind::Terminated { task } => (task.id, None),
+                _ => continue,
+            };
+            let track = tracks.entry(id).or_default();
+            // Apart from running, a task's state starts after the tick it ran or was woken on
+            let from = match next {
+                Some((Segment::Running, time)) => time,
+                _ if track.ran == Some(time) || track.woken == Some(time) => time + 1,
+                _ => time,
+            };
+            let segments = gantt.tasks.entry(id).or_default();
+            if let Some((segment, since)) = track.segment.take() {
+                if from > since {
+                    match segments.last_mut() {
+                        Some((_, end, last)) if *last == segment && *end == since => *end = from,
+                        _ => segments.push((since, from, segment)),
+                    }
+                }
+            }
+            track.segment = next.map(|(segment, _)| (segment, from));
+        }
+        gantt
+    }
+
+    // Returns the tick the chart ends at
+    pub fn end(&self) -> u128 {
+        self.end
+    }
+
+    // Returns the segments of a task, with the ticks they start and end at
+    pub fn segments(&self, id: u16) -> &[(u128, u128, Segment)] {
+        self.tasks.get(&id).map_or(&[], |segments| segments.as_slice())
+    }
+
+    // Returns the ticks at which tasks were preempted, with the CPU they ran on and their id
+    pub fn preemptions(&self) -> &[(u128, u16, u16)] {
+        &self.preemptions
+    }
+
+    // Returns the rows of the chart, each with its label and its spans
+    fn rows(&self, rows: Rows) -> Vec<Row> {
+        match rows {
+            Rows::Tasks => self
+                .tasks
+                .iter()
+                .map(|(id, segments)| {
+                    (format!("task {}", id), segments.iter().map(|(start, end, segment)| (*start, *end, Span::Segment(*segment))).collect())
+                })
+                .collect(),
+            Rows::Cpus => self
+                .cpus
+                .iter()
+                .map(|(cpu, runs)| (format!("CPU {}", cpu), runs.iter().map(|(start, end, id)| (*start, *end, Span::Task(*id))).collect()))
+                .collect(),
+        }
+    }
+
+    // Returns whether a preemption happened on the row with the given label
+    fn preempted_on(&self, rows: Rows, label: &str, time: u128) -> bool {
+        self.preemptions.iter().any(|(at, cpu, id)| {
+            *at == time
+                && match rows {
+                    Rows::Tasks => label == format!("task {}", id),
+                    Rows::Cpus => label == format!("CPU {}", cpu),
+                }
+        })
+    }
+
+    // Draws the chart in ASCII at most `width` columns wide, scaling time so that every column
+    // covers the same number of ticks and shows what takes up most of them. Task rows show
+    // segments, with `!` marking a preemption, and CPU rows the last digit of the task they run.
+    pub fn ascii(&self, rows: Rows, width: usize) -> String {
+        let width = width.max(1) as u128;
+        let scale = self.end.div_ceil(width).max(1);
+        let columns = self.end.div_ceil(scale) as usize;
+        let rows = (rows, self.rows(rows));
+        let label_width = rows.1.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+        let mut out = format!(
+            "Gantt chart over {} ticks, {} tick{} per column\n",
+            self.end,
+            scale,
+            if scale == 1 { "" } else { "s" }
+        );
+        for (label, spans) in rows.1.iter() {
+            let mut line = String::new();
+            for column in 0..columns as u128 {
+                let (from, to) = (column * scale, ((column + 1) * scale).min(self.end));
+                // The span covering most of the column, if any
+                let mut best: Option<(u128, char)> = None;
+                for (start, end, span) in spans.iter() {
+                    let overlap = (*end).min(to).saturating_sub((*start).max(from));
+                    if overlap > 0 && best.is_none_or(|(most, _)| overlap > most) {
+                        let symbol = match span {
+                            Span::Segment(segment) => segment.symbol(),
+                            Span::Task(id) => char::from_digit(u32::from(*id % 10), 10).unwrap_or('?'),
+                        };
+                        best = Some((overlap, symbol));
+                    }
+                }
+                let preempted = rows.0 == Rows::Tasks && (from..to).any(|time| self.preempted_on(rows.0, label, time));
+                line.push(match (preempted, best) {
+                    (true, _) => '!',
+                    (false, Some((_, symbol))) => symbol,
+                    (false, None) => ' ',
+                });
+            }
+            let _ = writeln!(out, "{:>width$} |{}|", label, line, width = label_width);
+        }
+
+        // An axis with a label every 10 columns
+        let mut axis = String::new();
+        for column in (0..columns).step_by(10) {
+            let label = (column as u128 * scale).to_string();
+            axis.push_str(&format!("{:<10}", label));
+        }
+        let _ = writeln!(out, "{:>width$}  {}", "", axis.trim_end(), width = label_width);
+        out.push_str(match rows.0 {
+            Rows::Tasks => "# running, - runnable, = I/O, _ sleeping or blocked, ! preempted",
+            Rows::Cpus => "digits are the ids of the tasks running, modulo 10",
+        });
+        out
+    }
+
+    // Draws the chart as a standalone SVG document, scaled to fit about 1000 pixels across
+    pub fn svg(&self, rows: Rows) -> String {
+        const LEFT: f64 = 80.0;
+        const ROW: f64 = 24.0;
+        const TOP: f64 = 30.0;
+        let chart = 1000.0;
+        let px = chart / self.end.max(1) as f64;
+        let data = self.rows(rows);
+        let height = TOP + ROW * data.len() as f64 + 60.0;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"12\">",
+            LEFT + chart + 20.0,
+            height
+        );
+        let _ = writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+        let _ = writeln!(out, "<text x=\"{}\" y=\"18\">Gantt chart over {} ticks</text>", LEFT, self.end);
+        for (idx, (label, spans)) in data.iter().enumerate() {
+            let y = TOP + ROW * idx as f64;
+            let _ = writeln!(out, "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>", LEFT - 8.0, y + ROW * 0.65, label);
+            for (start, end, span) in spans.iter() {
+                let (x, w) = (LEFT + *start as f64 * px, (*end - *start) as f64 * px);
+                let (colour, title) = match span {
+                    Span::Segment(segment) => (segment.colour(), format!("{:?}", segment)),
+                    Span::Task(id) => (TASK_COLOURS[usize::from(*id) % TASK_COLOURS.len()], format!("task {}", id)),
+                };
+                let _ = writeln!(
+                    out,
+                    "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"><title>{} {}..{}</title></rect>",
+                    x,
+                    y + 2.0,
+                    w,
+                    ROW - 4.0,
+                    colour,
+                    title,
+                    start,
+                    end
+                );
+            }
+        }
+        // Preemptions are red ticks on the rows they happened on
+        for (idx, (label, _)) in data.iter().enumerate() {
+            let y = TOP + ROW * idx as f64;
+            for (time, ..) in self.preemptions.iter().filter(|(time, ..)| self.preempted_on(rows, label, *time)) {
+                let x = LEFT + *time as f64 * px;
+                let _ = writeln!(out, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#e53935\" stroke-width=\"2\"/>", x, y, x, y + ROW);
+            }
+        }
+        // An axis with ten labelled ticks
+        let axis = TOP + ROW * data.len() as f64 + 4.0;
+        let _ = writeln!(out, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>", LEFT, axis, LEFT + chart, axis);
+        let step = self.end.div_ceil(10).max(1);
+        for time in (0..=self.end).step_by(step as usize) {
+            let x = LEFT + time as f64 * px;
+            let _ = writeln!(out, "<text x=\"{:.2}\" y=\"{}\" text-anchor=\"middle\">{}</text>", x, axis + 16.0, time);
+        }
+        // A legend of the segments
+        if rows == Rows::Tasks {
+            let y = axis + 36.0;
+            for (idx, segment) in [Segment::Running, Segment::Waiting, Segment::Io, Segment::Blocked].iter().enumerate() {
+                let x = LEFT + 160.0 * idx as f64;
+                let _ = writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>", x, y - 10.0, segment.colour());
+                let _ = writeln!(out, "<text x=\"{}\" y=\"{}\">{:?}</text>", x + 16.0, y, segment);
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+// Colours of the tasks on CPU rows
+const TASK_COLOURS: [&str; 8] = ["#4caf50", "#2196f3", "#ff9800", "#9c27b0", "#009688", "#f44336", "#3f51b5", "#795548"];
@@ -0,0 +1,178 @@
+use crate::proc::stats::TaskStats;
+use serde::Serialize;
+use std::fmt;
+
+// Mean and percentiles of a set of durations, in ticks. Percentiles use the nearest-rank method,
+// so every percentile is one of the durations.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub mean: f64,
+    pub median: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+impl Percentiles {
+    // Computes the mean and percentiles of the given durations, all zero if there are none
+    pub fn new(mut values: Vec<u64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_unstable();
+        let mean = values.iter().sum::<u64>() as f64 / values.len() as f64;
+        Self {
+            mean,
+            median: Self::percentile(&values, 50.0),
+            p95: Self::percentile(&values, 95.0),
+            p99: Self::percentile(&values, 99.0),
+        }
+    }
+
+    // Returns the nearest-rank percentile of sorted values
+    fn percentile(sorted: &[u64], p: f64) -> u64 {
+        let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+impl fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "mean {:.2}, median {}, p95 {}, p99 {} ticks", self.mean, self.median, self.p95, self.p99)
+    }
+}
+
+// Aggregate metrics of a run over the tasks that terminated
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    // Number of tasks that terminated
+    pub completed: usize,
+    // Ticks from the first arrival to the last completion
+    pub makespan: u128,
+    // Tasks completed per tick of the makespan
+    pub throughput: f64,
+    // Fraction of the makespan the CPU spent running tasks
+    pub utilisation: f64,
+    // Ticks from arrival to completion
+    pub turnaround: Percentiles,
+    // Ticks spent runnable on the run queue
+    pub waiting: Percentiles,
+    // Ticks from arrival to first run
+    pub response: Percentiles,
+    // Jain's fairness index over the runtime of every task divided by its weight, from 1/n when a
+    // single task got everything to 1 when the CPU was shared in proportion to the weights
+    pub jain_index: f64,
+    // Largest difference between the smallest and largest vruntime on the run queue
+    pub vruntime_spread: u64,
+}
+
+impl Summary {
+    // Computes the metrics of a run from the statistics of its tasks and the vruntime spread of its run queue
+    pub fn new(tasks: &[TaskStats], vruntime_spread: u64) -> Self {
+        let done: Vec<&TaskStats> = tasks.iter().filter(|stats| stats.completion.is_some()).collect();
+        let first = done.iter().map(|stats| stats.arrival).min().unwrap_or(0);
+        let last = done.iter().filter_map(|stats| stats.completion).max().unwrap_or(0);
+        let makespan = last.saturating_sub(first);
+        let busy: u64 = done.iter().map(|stats| stats.runtime).sum();
+
+        let ratio = |value: f64| if makespan == 0 { 0.0 } else { value / makespan as f64 };
+        Self {
+            completed: done.len(),
+            makespan,
+            throughput: ratio(done.len() as f64),
+            utilisation: ratio(busy as f64),
+            turnaround: Percentiles::new(done.iter().filter_map(|stats| stats.turnaround()).map(|time| time as u64).collect()),
+            waiting: Percentiles::new(done.iter().map(|stats| stats.wait).collect()),
+            response: Percentiles::new(
+                done.iter()
+                    .filter_map(|stats| stats.first_run.map(|first_run| (first_run - stats.arrival) as u64))
+                    .collect(),
+            ),
+            jain_index: jain_index(done.iter().map(|stats| stats.runtime as f64 / stats.weight.max(1) as f64)),
+            vruntime_spread,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Turnaround time: {}", self.turnaround)?;
+        writeln!(f, "Waiting time: {}", self.waiting)?;
+        writeln!(f, "Response time: {}", self.response)?;
+        writeln!(
+            f,
+            "Throughput: {:.4} tasks/tick, CPU utilisation {:.1}%, makespan {} ticks",
+            self.throughput,
+            self.utilisation * 100.0,
+            self.makespan
+        )?;
+        write!(f, "Fairness: Jain's index {:.4}, max vruntime spread {}", self.jain_index, self.vruntime_spread)
+    }
+}
+
+// Computes Jain's fairness index (sum x)^2 / (n * sum x^2), which is 1 for no values or all zeros
+pub fn jain_index(values: impl Iterator<Item = f64>) -> f64 {
+    let (mut n, mut sum, mut squares) = (0.0, 0.0, 0.0);
+    for value in values {
+        n += 1.0;
+        sum += value;
+        squares += value * value;
+    }
+    if squares == 0.0 {
+        return 1.0;
+    }
+    sum * sum / (n * squares)
+}
+
+// Names of the metrics compared between runs, in the order of `Summary::column`
+const COMPARED: [&str; 12] = [
+    "Completed",
+    "Makespan",
+    "Throughput",
+    "CPU utilisation",
+    "Turnaround mean",
+    "Turnaround p95",
+    "Waiting mean",
+    "Waiting p95",
+    "Response mean",
+    "Response p95",
+    "Jain's index",
+    "Vruntime spread",
+];
+
+impl Summary {
+    // Returns the metrics compared between runs, formatted
+    fn column(&self) -> [String; 12] {
+        [
+            self.completed.to_string(),
+            self.makespan.to_string(),
+            format!("{:.4}", self.throughput),
+            format!("{:.1}%", self.utilisation * 100.0),
+            format!("{:.2}", self.turnaround.mean),
+            self.turnaround.p95.to_string(),
+            format!("{:.2}", self.waiting.mean),
+            self.waiting.p95.to_string(),
+            format!("{:.2}", self.response.mean),
+            self.response.p95.to_string(),
+            format!("{:.4}", self.jain_index),
+            self.vruntime_spread.to_string(),
+        ]
+    }
+}
+
+// Formats the metrics of several runs side by side, with a row per metric and a column per run
+pub fn comparison(runs: &[(String, Summary)]) -> String {
+    let columns: Vec<[String; 12]> = runs.iter().map(|(_, summary)| summary.column()).collect();
+    let widths: Vec<usize> = runs.iter().map(|(name, _)| name.len().max(10)).collect();
+    let mut table = format!("{:<16}", "");
+    for ((name, _), width) in runs.iter().zip(widths.iter()) {
+        table.push_str(&format!(" {:>width$}", name, width = width));
+    }
+    for (row, metric) in COMPARED.iter().enumerate() {
+        table.push_str(&format!("\n{:<16}", metric));
+        for (column, width) in columns.iter().zip(widths.iter()) {
+            table.push_str(&format!(" {:>width$}", column[row], width = width));
+        }
+    }
+    table.push('\n');
+    table
+}
@@ -1,3 +1,11 @@
 pub mod fair;
 pub mod scheduler;
 pub mod clock;
+pub mod timer;
+pub mod report;
+pub mod metrics;
+pub mod trace;
+pub mod chrome;
+pub mod ftrace;
+pub mod gantt;
+pub mod export;
@@ -0,0 +1,113 @@
+use super::metrics::Summary;
+use crate::io::device::DeviceStats;
+use crate::proc::graph::DagReport;
+use crate::proc::histogram::Latencies;
+use crate::proc::stats::TaskStats;
+use crate::sync::pi::Inversion;
+use serde::Serialize;
+use std::fmt;
+
+// What happened during a run of the scheduler
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Report {
+    // Number of ticks the run lasted
+    pub elapsed: u128,
+    // Aggregate metrics over the tasks that terminated
+    pub summary: Summary,
+    // Latency histograms of every task that terminated, merged
+    pub latency: Latencies,
+    // Scheduling statistics of every task that terminated, in order of id
+    pub tasks: Vec<TaskStats>,
+    // Statistics of every device, in order of name
+    pub devices: Vec<DeviceStats>,
+    // Priority inversions seen on the mutexes
+    pub inversions: Vec<Inversion>,
+    // Tasks left blocked on synchronisation primitives or stopped, with what each waits on
+    pub deadlocked: Vec<(u16, String)>,
+    // Tasks left waiting for dependencies that never terminated
+    pub stalled: Vec<u16>,
+    // Makespan and critical path, for workloads with dependencies
+    pub dag: Option<DagReport>,
+}
+
+impl Report {
+    // Returns the scheduling statistics of a task, if it terminated
+    pub fn task(&self, id: u16) -> Option<&TaskStats> {
+        self.tasks.iter().find(|stats| stats.id == id)
+    }
+
+    // Formats the scheduling statistics of every task as a table, one line per task
+    pub fn task_table(&self) -> String {
+        let mut table = format!(
+            "{:>5} {:>8} {:>9} {:>10} {:>8} {:>8} {:>8} {:>8} {:>9} {:>11} {:>8}",
+            "Task", "Arrival", "First run", "Completion", "Runtime", "Wait", "I/O wait", "Sleep", "Voluntary", "Involuntary", "Max wait"
+        );
+        for stats in self.tasks.iter() {
+            let first_run = stats.first_run.map_or(String::from("-"), |time| time.to_string());
+            let completion = stats.completion.map_or(String::from("-"), |time| time.to_string());
+            table.push_str(&format!(
+                "\n{:>5} {:>8} {:>9} {:>10} {:>8} {:>8} {:>8} {:>8} {:>9} {:>11} {:>8}",
+                stats.id,
+                stats.arrival,
+                first_run,
+                completion,
+                stats.runtime,
+                stats.wait,
+                stats.io_wait,
+                stats.sleep,
+                stats.voluntary_switches,
+                stats.involuntary_switches,
+                stats.max_wait
+            ));
+        }
+        table
+    }
+}
+
+// Formats what happened during the run as printed at its end: the tasks left deadlocked or
+// stalled, the statistics of every task with the aggregate metrics and latency histograms, the
+// devices, the priority inversions and the critical path. Every part ends with a newline.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (id, what) in self.deadlocked.iter() {
+            writeln!(f, "Task {:?} is deadlocked waiting on {}", id, what)?;
+        }
+        if !self.stalled.is_empty() {
+            writeln!(f, "Tasks {:?} are blocked on dependencies that never terminated", self.stalled)?;
+        }
+        if !self.tasks.is_empty() {
+            writeln!(f, "{}", self.task_table())?;
+            writeln!(f, "{}", self.summary)?;
+        }
+        for (name, histogram) in [
+            ("Wakeup latency", &self.latency.wakeup),
+            ("Run queue wait per slice", &self.latency.wait),
+            ("Slice length", &self.latency.slice),
+        ] {
+            if !histogram.is_empty() {
+                writeln!(f, "{}:\n{}", name, histogram.ascii("ticks"))?;
+            }
+        }
+        for stats in self.devices.iter() {
+            writeln!(
+                f,
+                "Device {}: {} I/Os completed, busy {} of {} ticks ({:.1}%), mean queue length {:.2}, mean latency {:.2} ticks (max {})",
+                stats.name,
+                stats.completed,
+                stats.busy_ticks,
+                self.elapsed,
+                stats.utilisation(self.elapsed) * 100.0,
+                stats.mean_queue_length(self.elapsed),
+                stats.mean_latency(),
+                stats.max_latency
+            )?;
+        }
+        for inversion in self.inversions.iter() {
+            writeln!(f, "Priority inversion: {}", inversion)?;
+        }
+        if let Some(dag) = &self.dag {
+            writeln!(f, "{}", dag)?;
+        }
+        Ok(())
+    }
+}
@@ -4,21 +4,60 @@ use super::clock::Clock;
 // Brings the FairAlgorithm struct from the fair module in the parent module.
 use super::fair::FairAlgorithm;
 
+// Brings the Report struct from the report module in the parent module.
+use super::report::Report;
+
+// Brings the Summary struct from the metrics module in the parent module.
+use super::metrics::Summary;
+
+// Brings the trace sinks from the trace module in the parent module.
+use super::trace::{Printer, SinkError, TraceSink};
+
+// Brings the crate-level Error type.
+use crate::error::Error;
+
+// Brings the Device struct from the device module in the io module.
+use crate::io::device::Device;
+
+// Brings the DepGraph struct from the graph module in the proc module.
+use crate::proc::graph::DepGraph;
+
 // Brings the TaskQueue struct from the queue module in the proc module.
 use crate::proc::queue::TaskQueue;
 
 // Brings the Task and TaskChar structs from the task module in the proc module.
 use crate::proc::task::{Task, TaskChar};
 
+// Brings the HashMap type from the std library.
+use std::collections::HashMap;
+
 // Brings the Mutex and mpsc types from the std library.
-use std::sync::{mpsc, Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
 // Brings the thread module from the std library.
 use std::thread;
 
+// Brings the random number generator types from the rand crate.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
 // Defines the Scheduler struct.
 pub struct Scheduler {
     // The clock field is an Arc-wrapped Mutex-protected Clock instance.
     clock: Arc<Mutex<Clock>>,
+    // Seed of the random number generators the burst lengths of tasks are drawn from
+    seed: u64,
+    // Initial counts of the declared semaphores
+    semaphores: Vec<(String, u32)>,
+    // Whether mutex holders inherit the weight of the tasks blocked on them
+    inherit: bool,
+    // Devices declared before the run; others are created on first use with no queue depth limit
+    devices: Vec<Device>,
+    // Ticks the timers of sleeping tasks may fire late to coalesce wakeups
+    timer_slack: u128,
+    // Sinks receiving the scheduling events of every run, starting with a printer of the task run on every tick
+    sinks: Vec<Box<dyn TraceSink>>,
+    // Whether the report of every run is printed once it is over
+    print_report: bool,
 }
 
 impl Scheduler {
@@ -26,89 +65,166 @@ impl Scheduler {
     pub fn new() -> Self {
         let clock = Arc::new(Mutex::new(Clock::new()));
 
-        Self { clock }
+        Self {
+            clock,
+            seed: 0,
+            semaphores: Vec::new(),
+            inherit: false,
+            devices: Vec::new(),
+            timer_slack: 0,
+            sinks: vec![Box::new(Printer)],
+            print_report: true,
+        }
+    }
+
+    // Sets the seed of the simulation, making runs with stochastic bursts reproducible
+    pub fn seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    // Declares a semaphore with the given initial count; undeclared semaphores start at 1
+    pub fn semaphore(&mut self, name: &str, count: u32) {
+        self.semaphores.push((String::from(name), count));
+    }
+
+    // Declares a device serving the I/O bursts that name it
+    pub fn device(&mut self, device: Device) {
+        self.devices.push(device);
+    }
+
+    // Sets the ticks the timers of sleeping tasks may fire after they expire, so nearby wakeups are coalesced
+    pub fn timer_slack(&mut self, slack: u128) {
+        self.timer_slack = slack;
+    }
+
+    // Adds a sink receiving the scheduling events of every run
+    pub fn sink(&mut self, sink: Box<dyn TraceSink>) {
+        self.sinks.push(sink);
+    }
+
+    // Removes every sink, including the printer, so runs are silent apart from their report
+    pub fn clear_sinks(&mut self) {
+        self.sinks.clear();
+    }
+
+    // Sets whether the report of every run is printed once it is over, as it is by default.
+    // Applications printing or writing the returned report themselves turn it off.
+    pub fn print_report(&mut self, print: bool) {
+        self.print_report = print;
+    }
+
+    // Enables or disables priority inheritance, so a task holding a mutex runs with the highest
+    // weight of the tasks blocked on it. Priority inversions are reported either way.
+    pub fn priority_inheritance(&mut self, inherit: bool) {
+        self.inherit = inherit;
+    }
+
+    // Function to create every instance of every task of the workload, sorted by arrival time.
+    // The first instance of a task keeps its id while later instances get ids above every task in
+    // the workload; the highest id handed out is returned along with the tasks. Fails if the ids
+    // run out.
+    fn births(&self, tasks: &[TaskChar]) -> Result<(Vec<Task>, u16), Error> {
+        // Poisson arrivals are drawn from a stream of their own so they do not disturb burst lengths
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut last_pid = tasks.iter().map(|raw| raw.get_id()).max().unwrap_or(0);
+        let mut born_tasks = vec![];
+
+        for raw in tasks {
+            for (idx, time) in raw.get_arrival().instants(&mut rng).into_iter().enumerate() {
+                let id = if idx == 0 {
+                    raw.get_id()
+                } else {
+                    last_pid = last_pid.checked_add(1).ok_or(Error::PidsExhausted)?;
+                    last_pid
+                };
+
+                // Create a new task from the task character
+                let mut task = Task::scripted(
+                    id,
+                    raw.get_cpu_time(),
+                    raw.get_script().clone(),
+                    time,
+                    raw.get_weight(),
+                );
+                task.seed(self.seed);
+                task.set_deps(raw.get_deps().to_vec());
+                born_tasks.push(task);
+            }
+        }
+
+        // Sort by arrival, keeping the workload order between tasks arriving at the same time
+        born_tasks.sort_by_key(|task| task.get_start_time());
+
+        Ok((born_tasks, last_pid))
     }
 
-    // Function to run the scheduler
-    pub fn run(&mut self, tasks: Vec<TaskChar>) {
+    // Function to run the scheduler, returning a report of the run. The run stops at the first task
+    // asked to make a transition its state does not allow, and fails if any of its threads panics.
+    pub fn run(&mut self, tasks: Vec<TaskChar>) -> Result<Report, Error> {
+        // Check the dependencies between tasks form a DAG and every task has a weight before starting
+        let graph = DepGraph::new(&tasks)?;
+        if let Some(task) = tasks.iter().find(|task| task.get_weight() == 0) {
+            return Err(Error::ZeroWeight(task.get_id()));
+        }
+
+        // Creating every instance of every task, in order of arrival
+        let (born_tasks, last_pid) = self.births(&tasks)?;
+
         // Cloning the clock object for use in different threads
-        let clk_1 = Arc::clone(&mut self.clock);
-        let clk_2 = Arc::clone(&mut self.clock);
+        let clk_1 = Arc::clone(&self.clock);
+        let clk_2 = Arc::clone(&self.clock);
 
         let mut threads = vec![];
 
         // Creating channels for communication between different threads
-        let (clock_sender_1, spawner_clock_recv) = mpsc::channel();
-        let (clock_sender_2, clock_recv) = mpsc::channel();
+        let (clock_sender, clock_recv) = mpsc::channel();
+        let (done_sender, done_recv) = mpsc::channel();
         let (born_sender, born_recv) = mpsc::channel();
 
         // Spawning a thread to control the clock
         let clocking = thread::spawn(move || {
             // Infinite loop to keep ticking the clock
             for _ in 0..u128::MAX {
-                let mut lock = clk_1.lock().unwrap();
-                // Sending the current time of the clock to the spawner
-                match clock_sender_1.send(lock.time()) {
+                // Sending the current time of the clock to the scheduler
+                let time = clk_1.lock().unwrap_or_else(PoisonError::into_inner).time();
+                match clock_sender.send(time) {
                     Ok(_) => {}
-                    _ => {}
+                    _ => break,
                 };
-                // Sending the current time of the clock to the scheduler
-                match clock_sender_2.send(lock.time()) {
+                // Waiting for the scheduler to finish the tick, so the simulation runs in lockstep with the clock.
+                // The clock is not locked meanwhile, so the scheduler can read it.
+                match done_recv.recv() {
                     Ok(_) => {}
                     _ => break,
                 };
                 // Ticking the clock
-                lock.tick();
+                clk_1.lock().unwrap_or_else(PoisonError::into_inner).tick();
             }
-            // Dropping the clock sender channels after the loop
-            drop(clock_sender_1);
-            drop(clock_sender_2);
+            // Dropping the clock sender channel after the loop
+            drop(clock_sender);
         });
         // Adding the clock thread to the list of threads
         threads.push(clocking);
 
-        // Creating a shared vector of tasks for communication between different threads
-        let my_tasks = Arc::new(Mutex::new(tasks));
-        let tasks_cp_1 = Arc::clone(&my_tasks);
-        let tasks_cp_2 = Arc::clone(&my_tasks);
+        let semaphores = self.semaphores.clone();
+        let inherit = self.inherit;
+        let devices = self.devices.clone();
+        let timer_slack = self.timer_slack;
+        let print_report = self.print_report;
+        let sinks = std::mem::take(&mut self.sinks);
 
         // Spawning a thread to spawn tasks
         let spawning = thread::spawn(move || {
-            // Receiving the current time from the clock thread
-            let mut time = spawner_clock_recv.recv().unwrap();
-
-            // Cloning the vector of tasks
-            let born_tasks = tasks_cp_1.lock().unwrap().clone();
-
-            // Loop to create tasks and send them to the scheduler
-            for raw in born_tasks {
-                // Try to receive the current time from the clock thread
-                time = match spawner_clock_recv.try_recv() {
-                    Ok(tick) => tick,
-                    _ => time,
-                };
-
-                // Create a new task from the task character
-                let task = Task::new(
-                    raw.get_id(),
-                    raw.get_cpu_time(),
-                    raw.get_cpu_burst_length(),
-                    raw.get_io_burst_length(),
-                    time,
-                    raw.get_weight(),
-                );
-
-                // Sending the created task to the scheduler
+            // Loop to send the born tasks to the scheduler
+            for task in born_tasks {
+                // Sending the created task to the scheduler, which only stops listening if it failed
                 match born_sender.send(task) {
                     Ok(_) => {}
-                    _ => panic!("Running thread dropped unexpectedly"),
+                    _ => break,
                 };
             }
 
-            // Clearing the vector of tasks after all tasks have been created
-            tasks_cp_1.lock().unwrap().clear();
-            // Dropping the clock and born sender channels after the loop
-            drop(spawner_clock_recv);
+            // Dropping the born sender channel after the loop
             drop(born_sender);
         });
 
@@ -116,71 +232,161 @@ impl Scheduler {
         threads.push(spawning);
 
         // The first spawned thread is named "running".
-        let running = thread::spawn(move || {
+        let running = thread::spawn(move || -> (Result<Report, Error>, Vec<Box<dyn TraceSink>>) {
             // Creates a new TaskQueue instance and assigns it to the variable `task_queue`.
             let mut task_queue = TaskQueue::new();
             // Creates a new FairAlgorithm instance and assigns it to the variable `rq`.
-            let mut rq = FairAlgorithm::new(&mut clk_2.lock().unwrap());
+            let mut rq = FairAlgorithm::new(&mut clk_2.lock().unwrap_or_else(PoisonError::into_inner));
+            rq.reserve_pids(last_pid);
+            rq.set_priority_inheritance(inherit);
+            rq.set_timer_slack(timer_slack);
+            for device in devices {
+                rq.add_device(device);
+            }
+            for (name, count) in semaphores.iter() {
+                rq.declare_semaphore(name, *count);
+            }
+            for sink in sinks {
+                rq.add_sink(sink);
+            }
+
+            // The run is simulated until it ends or fails, handing the sinks back either way.
+            let report = (|| -> Result<Report, Error> {
+                // Time at which every task was released to the run queue and the time it terminated
+                let mut times: HashMap<u16, (u128, u128)> = HashMap::new();
+                // The next task received from the spawner that has not arrived yet
+                let mut next_born: Option<Task> = None;
+                // Whether the spawner has sent every task
+                let mut spawned = false;
+                // What the run leaves behind, filled in as it ends
+                let mut report = Report::default();
 
-            // An infinite loop is executed until a break statement is reached.
-            loop {
                 // The time is determined from the received value from the `clock_recv` channel.
-                let time = match clock_recv.recv() {
-                    Ok(tick) => tick,
-                    // The loop breaks if there is an error in receiving from the channel.
-                    _ => break,
-                };
+                // The loop breaks if there is an error in receiving from the channel.
+                while let Ok(time) = clock_recv.recv() {
+                    // Receives the tasks arriving by now from the `born_recv` channel and adds them to the `task_queue`.
+                    // The spawner sends tasks in order of arrival, so waiting for the first later task keeps runs deterministic.
+                    while !spawned {
+                        match next_born.take().map_or_else(|| born_recv.recv().ok(), Some) {
+                            Some(task) if task.get_start_time() <= time => task_queue.add(task),
+                            Some(task) => {
+                                next_born = Some(task);
+                                break;
+                            }
+                            None => spawned = true,
+                        }
+                    }
 
-                // Tries to receive from the `born_recv` channel and adds the received task to the `task_queue`.
-                match born_recv.try_recv() {
-                    Ok(task) => task_queue.add(task),
-                    // If there is no task received, the block is skipped.
-                    _ => {}
-                };
+                    // The function `pop_until` is called on the `task_queue` to get the tasks born by now.
+                    let born_tasks = task_queue.pop_until(time);
+                    for task in born_tasks.iter() {
+                        times.insert(task.get_id(), (time, time));
+                    }
+                    // The `born_tasks` are pushed onto the `rq` FairAlgorithm instance.
+                    rq.push(born_tasks)?;
+
+                    // If `rq` is not empty, it performs the following actions.
+                    if !rq.is_empty() {
+                        // The first task is popped from `rq`.
+                        let mut curr = rq.try_pop()?;
+                        // The function `cpu_cycle` is called on the task.
+                        curr.cpu_cycle()?;
+                        // The task is reinserted into the `rq`.
+                        rq.insert(*curr)?;
+                    }
+                    // The `idle` function is called on the `rq` FairAlgorithm instance.
+                    rq.idle()?;
+
+                    // Terminated tasks are reaped and release the tasks that depend on them.
+                    for mut task in rq.take_exited() {
+                        task.reap()?;
+                        report.tasks.push(task.stats().clone());
+                        times.entry(task.get_id()).or_insert((time, time)).1 = time;
+                        task_queue.complete(task.get_id(), time);
+                    }
 
-                // The function `pop` is called on the `task_queue` to get the born tasks.
-                let born_tasks = task_queue.pop();
-                // The `born_tasks` are pushed onto the `rq` FairAlgorithm instance.
-                rq.push(born_tasks);
-
-                // If `rq` is not empty, it performs the following actions.
-                if !rq.is_empty() {
-                    // The first task is popped from `rq`.
-                    let mut curr = rq.pop();
-                    // The task ID and the system time is printed.
-                    println!(
-                        "Running task id {:?} at system time {:?}",
-                        curr.get_id(),
-                        time
-                    );
-                    // The function `cpu_cycle` is called on the task.
-                    curr.cpu_cycle();
-                    // The task is reinserted into the `rq`.
-                    rq.insert(*curr);
+                    // If `rq` is finished and every task has been spawned and has left the `task_queue`, the loop breaks.
+                    if rq.is_finished() && spawned && next_born.is_none() {
+                        let parked = rq.parked();
+                        if !parked.is_empty() {
+                            report.deadlocked = parked;
+                            break;
+                        } else if task_queue.is_stalled() {
+                            report.stalled = task_queue.blocked().iter().map(|task| task.get_id()).collect();
+                            break;
+                        } else if task_queue.is_empty() {
+                            break;
+                        }
+                    }
+
+                    // Letting the clock tick again.
+                    if done_sender.send(()).is_err() {
+                        break;
+                    }
                 }
-                // The `idle` function is called on the `rq` FairAlgorithm instance.
-                rq.idle();
 
-                // If `rq` is finished and there are no more tasks in the `tasks_cp_2` vector, the loop breaks.
-                if rq.is_finished() && tasks_cp_2.lock().unwrap().is_empty() {
-                    break;
+                // The scheduling statistics of every task are reported in order of id, along with the
+                // aggregate metrics and the merged latency histograms over the same tasks.
+                report.tasks.sort_by_key(|stats| stats.id);
+                report.summary = Summary::new(&report.tasks, rq.vruntime_spread());
+                for stats in report.tasks.iter() {
+                    report.latency.merge(&stats.latency);
                 }
-            }
 
-            // The `clock_recv` and `born_recv` channels are dropped.
-            drop(clock_recv);
-            drop(born_recv);
+                // The utilisation of every device is reported over the whole run.
+                report.elapsed = clk_2.lock().unwrap_or_else(PoisonError::into_inner).time() + 1;
+                report.devices = rq.device_stats();
+
+                // Every priority inversion is reported, with how long the waiter was held up.
+                report.inversions = rq.sync().inversions().to_vec();
+
+                // The makespan and critical path are reported for workloads with dependencies.
+                if graph.has_edges() {
+                    report.dag = Some(graph.report(&times));
+                }
+                if print_report {
+                    print!("{}", report);
+                }
+
+                // The `clock_recv`, `done_sender` and `born_recv` channels are dropped.
+                drop(clock_recv);
+                drop(done_sender);
+                drop(born_recv);
+
+                Ok(report)
+            })();
+            // A run that went well still fails if its events could not all be written out
+            let finished = rq.finish_sinks().map_err(|e| SinkError(e.to_string()));
+            (report.and_then(|report| finished.map(|_| report).map_err(Error::from)), rq.take_sinks())
         });
 
-        // The "running" thread is pushed onto the `threads` vector.
-        threads.push(running);
+        // The "running" thread is joined first, as the others stop once it returns, even on failure.
+        let report = match running.join() {
+            Ok((report, sinks)) => {
+                self.sinks = sinks;
+                report
+            }
+            Err(_) => Err(Error::ThreadPanicked("running")),
+        };
 
         // The function joins all the threads in the `threads` vector.
-        for thread in threads {
-            thread.join().unwrap();
+        for (thread, name) in threads.into_iter().zip(["clock", "spawner"]) {
+            if thread.join().is_err() {
+                return Err(Error::ThreadPanicked(name));
+            }
         }
 
         // A message is printed indicating that the scheduler job has completed.
-        println!("Scheduler job completed!");
+        let report = report?;
+        if self.print_report {
+            println!("Scheduler job completed!");
+        }
+        Ok(report)
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
     }
 }
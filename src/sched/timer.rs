@@ -0,0 +1,79 @@
+use crate::proc::task::Task;
+use std::collections::BTreeMap;
+
+// The timers of sleeping tasks, ordered like the kernel's hrtimers by the latest tick each may
+// fire at. A timer armed with slack may fire anywhere from its expiry time to the expiry time
+// plus the slack, so once a timer cannot wait any longer, every timer that has already expired
+// fires with it and the wakeups are coalesced.
+#[derive(Default)]
+pub struct TimerQueue {
+    // Timers by latest firing tick and arming order, holding the expiry time and the sleeping task
+    timers: BTreeMap<(u128, u64), (u128, Task)>,
+    // Number of timers armed so far, keeping timers with the same firing tick in arming order
+    armed: u64,
+    // Ticks a timer may fire after its expiry time
+    slack: u128,
+}
+
+impl TimerQueue {
+    // Creates a timer queue with no slack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the ticks a timer may fire after its expiry time
+    pub fn slack(&self) -> u128 {
+        self.slack
+    }
+
+    // Sets the ticks timers armed from now on may fire after their expiry time
+    pub fn set_slack(&mut self, slack: u128) {
+        self.slack = slack;
+    }
+
+    // Arms a timer waking a sleeping task at the given tick
+    pub fn arm(&mut self, task: Task, expires: u128) {
+        self.timers.insert((expires + self.slack, self.armed), (expires, task));
+        self.armed += 1;
+    }
+
+    // Fires the timers due at the given tick, returning their tasks in firing order
+    pub fn expire(&mut self, now: u128) -> Vec<Task> {
+        // Nothing fires until some timer reaches the end of its slack
+        match self.timers.keys().next() {
+            Some((latest, _)) if *latest <= now => {}
+            _ => return Vec::new(),
+        }
+
+        let due: Vec<(u128, u64)> = self
+            .timers
+            .iter()
+            .filter(|(_, (expires, _))| *expires <= now)
+            .map(|(key, _)| *key)
+            .collect();
+        due.into_iter()
+            .filter_map(|key| self.timers.remove(&key))
+            .map(|(_, task)| task)
+            .collect()
+    }
+
+    // Returns the earliest tick any timer may fire at
+    pub fn next_expiry(&self) -> Option<u128> {
+        self.timers.values().map(|(expires, _)| *expires).min()
+    }
+
+    // Returns the number of armed timers
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    // Returns whether no timer is armed
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    // Returns a sleeping task
+    pub fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        self.timers.values_mut().map(|(_, task)| task).find(|task| task.get_id() == id)
+    }
+}
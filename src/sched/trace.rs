@@ -0,0 +1,138 @@
+use crate::proc::task::{Task, TaskStatus};
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// The CPU every task runs on, as the simulation has a single one
+pub const CPU: u16 = 0;
+
+// A task as it appears in trace events
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct TaskRef {
+    // Id of the task
+    pub id: u16,
+    // Nice value of the task at the time of the event
+    pub nice: i8,
+}
+
+impl TaskRef {
+    // Describes a task for a trace event
+    pub fn new(task: &Task) -> Self {
+        Self { id: task.get_id(), nice: task.get_nice() }
+    }
+}
+
+// What happened in a scheduling event
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+    // A task joined the scheduler, arriving, being released by its dependencies or forked by its parent
+    Born { task: TaskRef, parent: Option<u16> },
+    // A task was put on the run queue with the given vruntime
+    Enqueued { task: TaskRef, vruntime: u64 },
+    // A task was picked to run on a CPU for the tick
+    Picked { task: TaskRef, cpu: u16 },
+    // A task ran for the tick and went back on the run queue
+    Preempted { task: TaskRef },
+    // A task left the run queue to block or sleep, in the given state
+    Blocked { task: TaskRef, state: TaskStatus },
+    // A task submitted an I/O to the named device
+    IoSubmitted { task: TaskRef, device: String },
+    // The named device completed the I/O of a task
+    IoCompleted { task: TaskRef, device: String },
+    // A task blocked or sleeping became runnable again
+    Woken { task: TaskRef },
+    // A task moved between CPUs, which never happens with a single CPU
+    Migrated { task: TaskRef, from: u16, to: u16 },
+    // A task exited
+    Terminated { task: TaskRef },
+    // The scheduler finished a tick, leaving the given number of tasks on the run queue and its
+    // min_vruntime at the given value
+    Tick { runnable: usize, min_vruntime: u64 },
+}
+
+// A scheduling event and the tick it happened at
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+    pub time: u128,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+// A consumer of scheduling events, such as a printer or an exporter. Sinks are moved to the
+// thread running the scheduler, so they must be Send.
+pub trait TraceSink: Send {
+    // Handles an event; events arrive in the order they happened
+    fn event(&mut self, event: &Event);
+
+    // Called once the run is over, to flush anything buffered. Fails if the events could not all
+    // be written out.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Error returned when a sink could not write out the events of a run
+#[derive(Clone, Debug, PartialEq)]
+pub struct SinkError(pub String);
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+// Returns the error of a sink with what it was writing
+pub(crate) fn sink_error(what: &str, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("could not write the {}: {}", what, e))
+}
+
+// Prints the task picked on every tick to stdout
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Printer;
+
+impl Printer {
+    // Returns the line printed for an event, for the events of tasks picked to run
+    pub fn line(event: &Event) -> Option<String> {
+        match event.kind {
+            EventKind::Picked { task, .. } => Some(format!("Running task id {:?} at system time {:?}", task.id, event.time)),
+            _ => None,
+        }
+    }
+}
+
+impl TraceSink for Printer {
+    fn event(&mut self, event: &Event) {
+        if let Some(line) = Self::line(event) {
+            println!("{}", line);
+        }
+    }
+}
+
+// Records every event in memory. Clones share the same record, so a clone kept aside can read
+// the events after the sink has been handed to the scheduler.
+#[derive(Clone, Debug, Default)]
+pub struct EventLog {
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+impl EventLog {
+    // Creates an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the events recorded so far
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl TraceSink for EventLog {
+    fn event(&mut self, event: &Event) {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner).push(event.clone());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::proc::task::Task;
+use serde::Serialize;
+use std::fmt;
+
+// A priority inversion episode: a task blocked on a mutex held by a task of lower weight
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Inversion {
+    // Id and base weight of the blocked task
+    pub waiter: u16,
+    pub waiter_weight: u32,
+    // Id and base weight of the task holding the mutex when the waiter blocked
+    pub holder: u16,
+    pub holder_weight: u32,
+    // Name of the contended mutex
+    pub mutex: String,
+    // Tick at which the waiter blocked
+    pub start: u128,
+    // Tick at which the waiter acquired the mutex, if it has
+    pub end: Option<u128>,
+}
+
+impl Inversion {
+    // Returns how long the waiter was blocked, counting up to `now` for open episodes
+    pub fn duration(&self, now: u128) -> u128 {
+        self.end.unwrap_or(now).saturating_sub(self.start)
+    }
+}
+
+impl fmt::Display for Inversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "task {} (weight {}) blocked by task {} (weight {}) on mutex {} from {} ",
+            self.waiter, self.waiter_weight, self.holder, self.holder_weight, self.mutex, self.start
+        )?;
+        match self.end {
+            Some(end) => write!(f, "to {} ({} ticks)", end, end - self.start),
+            None => write!(f, "and never acquired it"),
+        }
+    }
+}
+
+// Records priority inversion episodes as tasks block on and acquire mutexes,
+// whether or not priority inheritance is enabled
+#[derive(Default)]
+pub struct InversionDetector {
+    episodes: Vec<Inversion>,
+}
+
+impl InversionDetector {
+    // Creates a detector with no episodes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Records that a task blocked on a mutex held by the task with the given id and base weight
+    pub fn blocked(&mut self, waiter: &Task, holder: (u16, u32), mutex: &str, now: u128) {
+        if waiter.base_weight() > holder.1 {
+            self.episodes.push(Inversion {
+                waiter: waiter.get_id(),
+                waiter_weight: waiter.base_weight(),
+                holder: holder.0,
+                holder_weight: holder.1,
+                mutex: String::from(mutex),
+                start: now,
+                end: None,
+            });
+        }
+    }
+
+    // Records that a task acquired a mutex, closing its open episode on that mutex
+    pub fn acquired(&mut self, waiter: u16, mutex: &str, now: u128) {
+        let open = self
+            .episodes
+            .iter_mut()
+            .find(|episode| episode.waiter == waiter && episode.mutex == mutex && episode.end.is_none());
+        if let Some(episode) = open {
+            episode.end = Some(now);
+        }
+    }
+
+    // Returns every episode recorded so far, in the order they started
+    pub fn episodes(&self) -> &[Inversion] {
+        &self.episodes
+    }
+}
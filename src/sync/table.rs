@@ -0,0 +1,297 @@
+use crate::error::Error;
+use crate::proc::script::SyncOp;
+use crate::proc::task::{BlockReason, Task, TransitionError};
+use super::pi::{Inversion, InversionDetector};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// Count of a semaphore that was never declared, like the kernel's DEFINE_SEMAPHORE
+pub const DEFAULT_SEMAPHORE_COUNT: u32 = 1;
+
+// A simulated mutex with a FIFO queue of waiting tasks
+#[derive(Default)]
+struct SimMutex {
+    // Id and base weight of the task holding the mutex
+    owner: Option<(u16, u32)>,
+    // Tasks waiting to acquire the mutex
+    waiters: VecDeque<Task>,
+}
+
+// A simulated counting semaphore with a FIFO queue of waiting tasks
+struct Semaphore {
+    // Number of times the semaphore can be taken without blocking
+    count: u32,
+    // Tasks waiting for the count to become positive
+    waiters: VecDeque<Task>,
+}
+
+// A simulated channel between tasks, buffering messages until they are received
+#[derive(Default)]
+struct Channel {
+    // Number of messages sent but not received yet
+    messages: usize,
+    // Tasks waiting for a message
+    receivers: VecDeque<Task>,
+}
+
+// Error returned when a task unlocks a mutex it does not hold
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnlockError {
+    // Id of the task
+    pub id: u16,
+    // Name of the mutex
+    pub mutex: String,
+}
+
+impl fmt::Display for UnlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "task {} cannot unlock mutex {} it does not hold", self.id, self.mutex)
+    }
+}
+
+impl std::error::Error for UnlockError {}
+
+// Result of carrying out a synchronisation operation
+pub struct Outcome {
+    // The task that performed the operation, or None if it blocked
+    pub caller: Option<Task>,
+    // Tasks woken by the operation, already moved past the operation they were blocked on
+    pub woken: Vec<Task>,
+}
+
+// The simulated mutexes, semaphores and wait queues of the system, created on first use
+#[derive(Default)]
+pub struct SyncTable {
+    mutexes: HashMap<String, SimMutex>,
+    semaphores: HashMap<String, Semaphore>,
+    // Tasks waiting on every wait queue, with the mutex each must reacquire when woken
+    queues: HashMap<String, VecDeque<(Task, Option<String>)>>,
+    channels: HashMap<String, Channel>,
+    // Priority inversions seen on the mutexes
+    inversions: InversionDetector,
+}
+
+impl SyncTable {
+    // Creates an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Declares a semaphore with the given initial count
+    pub fn declare_semaphore(&mut self, name: &str, count: u32) {
+        self.semaphores.insert(String::from(name), Semaphore { count, waiters: VecDeque::new() });
+    }
+
+    // Returns the id of the task holding a mutex
+    pub fn owner(&self, mutex: &str) -> Option<u16> {
+        self.mutexes.get(mutex).and_then(|mutex| mutex.owner).map(|owner| owner.0)
+    }
+
+    // Returns the highest effective weight among the tasks waiting on mutexes held by a task, or 0
+    pub fn max_waiter_weight(&self, owner: u16) -> u32 {
+        self.mutexes
+            .values()
+            .filter(|mutex| mutex.owner.map(|owner| owner.0) == Some(owner))
+            .flat_map(|mutex| mutex.waiters.iter())
+            .map(|waiter| waiter.weight())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Returns the name of the mutex a task is blocked on, if any
+    pub fn waiting_on(&self, id: u16) -> Option<&str> {
+        self.mutexes
+            .iter()
+            .find(|(_, mutex)| mutex.waiters.iter().any(|waiter| waiter.get_id() == id))
+            .map(|(name, _)| name.as_str())
+    }
+
+    // Returns a task blocked on any primitive
+    pub fn task_mut(&mut self, id: u16) -> Option<&mut Task> {
+        let mutexes = self.mutexes.values_mut().flat_map(|mutex| mutex.waiters.iter_mut());
+        let semaphores = self.semaphores.values_mut().flat_map(|semaphore| semaphore.waiters.iter_mut());
+        let queues = self.queues.values_mut().flat_map(|queue| queue.iter_mut().map(|(task, _)| task));
+        let channels = self.channels.values_mut().flat_map(|channel| channel.receivers.iter_mut());
+        mutexes.chain(semaphores).chain(queues).chain(channels).find(|task| task.get_id() == id)
+    }
+
+    // Returns the number of messages waiting to be received on a channel
+    pub fn pending_messages(&self, channel: &str) -> usize {
+        self.channels.get(channel).map_or(0, |channel| channel.messages)
+    }
+
+    // Returns the priority inversion episodes seen so far
+    pub fn inversions(&self) -> &[Inversion] {
+        self.inversions.episodes()
+    }
+
+    // Returns the current count of a semaphore
+    pub fn count(&self, semaphore: &str) -> u32 {
+        self.semaphores.get(semaphore).map_or(DEFAULT_SEMAPHORE_COUNT, |semaphore| semaphore.count)
+    }
+
+    // Returns the ids of the blocked tasks along with what each is waiting on
+    pub fn parked(&self) -> Vec<(u16, String)> {
+        let mut parked = Vec::new();
+        for (name, mutex) in self.mutexes.iter() {
+            parked.extend(mutex.waiters.iter().map(|task| (task.get_id(), format!("mutex {}", name))));
+        }
+        for (name, semaphore) in self.semaphores.iter() {
+            parked.extend(semaphore.waiters.iter().map(|task| (task.get_id(), format!("semaphore {}", name))));
+        }
+        for (name, queue) in self.queues.iter() {
+            parked.extend(queue.iter().map(|(task, _)| (task.get_id(), format!("wait queue {}", name))));
+        }
+        for (name, channel) in self.channels.iter() {
+            parked.extend(channel.receivers.iter().map(|task| (task.get_id(), format!("channel {}", name))));
+        }
+        parked.sort();
+        parked
+    }
+
+    // Returns whether no task is blocked on any primitive
+    pub fn is_empty(&self) -> bool {
+        self.mutexes.values().all(|mutex| mutex.waiters.is_empty())
+            && self.semaphores.values().all(|semaphore| semaphore.waiters.is_empty())
+            && self.queues.values().all(|queue| queue.is_empty())
+            && self.channels.values().all(|channel| channel.receivers.is_empty())
+    }
+
+    // Carries out an operation for a task at the given time, blocking it or waking others as needed.
+    // Fails if the caller or a woken task is in a state that cannot block or wake.
+    pub fn execute(&mut self, op: SyncOp, task: Task, now: u128) -> Result<Outcome, Error> {
+        let mut woken = Vec::new();
+        let caller = match op {
+            SyncOp::Lock(name) => self.lock(&name, task, now)?,
+            SyncOp::Unlock(name) => {
+                self.unlock(&name, task.get_id(), &mut woken, now)?;
+                Some(task)
+            }
+            SyncOp::Down(name) => {
+                let semaphore = self.semaphore(&name);
+                if semaphore.count > 0 {
+                    semaphore.count -= 1;
+                    Some(task)
+                } else {
+                    Self::park(&mut semaphore.waiters, task, BlockReason::Semaphore)?;
+                    None
+                }
+            }
+            SyncOp::Up(name) => {
+                // Hand the semaphore straight to the first waiter, or bank it
+                let semaphore = self.semaphore(&name);
+                match semaphore.waiters.pop_front() {
+                    Some(waiter) => woken.push(waiter),
+                    None => semaphore.count += 1,
+                }
+                Some(task)
+            }
+            SyncOp::Wait(queue, mutex) => {
+                if let Some(mutex) = mutex.as_ref() {
+                    self.unlock(mutex, task.get_id(), &mut woken, now)?;
+                }
+                let mut task = task;
+                task.block(BlockReason::WaitQueue)?;
+                self.queues.entry(queue).or_default().push_back((task, mutex));
+                None
+            }
+            SyncOp::Signal(queue) => {
+                let waiter = self.queues.get_mut(&queue).and_then(|queue| queue.pop_front());
+                if let Some((waiter, mutex)) = waiter {
+                    self.reacquire(waiter, mutex, &mut woken, now)?;
+                }
+                Some(task)
+            }
+            SyncOp::Broadcast(queue) => {
+                let waiters = self.queues.remove(&queue).unwrap_or_default();
+                for (waiter, mutex) in waiters {
+                    self.reacquire(waiter, mutex, &mut woken, now)?;
+                }
+                Some(task)
+            }
+            SyncOp::Send(name) => {
+                // Deliver the message straight to the first receiver, or buffer it
+                let channel = self.channels.entry(name).or_default();
+                match channel.receivers.pop_front() {
+                    Some(receiver) => woken.push(receiver),
+                    None => channel.messages += 1,
+                }
+                Some(task)
+            }
+            SyncOp::Recv(name) => {
+                let channel = self.channels.entry(name).or_default();
+                if channel.messages > 0 {
+                    channel.messages -= 1;
+                    Some(task)
+                } else {
+                    Self::park(&mut channel.receivers, task, BlockReason::Channel)?;
+                    None
+                }
+            }
+        };
+
+        // Woken tasks have completed the operation they were blocked on
+        for task in woken.iter_mut() {
+            task.resume()?;
+        }
+
+        Ok(Outcome { caller, woken })
+    }
+
+    // Returns a semaphore, creating it with the default count on first use
+    fn semaphore(&mut self, name: &str) -> &mut Semaphore {
+        self.semaphores
+            .entry(String::from(name))
+            .or_insert_with(|| Semaphore { count: DEFAULT_SEMAPHORE_COUNT, waiters: VecDeque::new() })
+    }
+
+    // Blocks a task for the given reason at the back of a wait queue
+    fn park(waiters: &mut VecDeque<Task>, mut task: Task, reason: BlockReason) -> Result<(), TransitionError> {
+        task.block(reason)?;
+        waiters.push_back(task);
+        Ok(())
+    }
+
+    // Acquires a mutex for a task, returning the task if it got the mutex or None if it blocked
+    fn lock(&mut self, name: &str, task: Task, now: u128) -> Result<Option<Task>, TransitionError> {
+        let mutex = self.mutexes.entry(String::from(name)).or_default();
+        match mutex.owner {
+            None => {
+                mutex.owner = Some((task.get_id(), task.base_weight()));
+                Ok(Some(task))
+            }
+            Some(owner) => {
+                self.inversions.blocked(&task, owner, name, now);
+                Self::park(&mut mutex.waiters, task, BlockReason::Mutex)?;
+                Ok(None)
+            }
+        }
+    }
+
+    // Releases a mutex held by the task, handing it to the first waiter, or fails if the task does not hold it
+    fn unlock(&mut self, name: &str, id: u16, woken: &mut Vec<Task>, now: u128) -> Result<(), UnlockError> {
+        let mutex = self.mutexes.entry(String::from(name)).or_default();
+        if mutex.owner.map(|owner| owner.0) != Some(id) {
+            return Err(UnlockError { id, mutex: String::from(name) });
+        }
+        mutex.owner = mutex.waiters.front().map(|waiter| (waiter.get_id(), waiter.base_weight()));
+        if let Some(waiter) = mutex.waiters.pop_front() {
+            self.inversions.acquired(waiter.get_id(), name, now);
+            woken.push(waiter);
+        }
+        Ok(())
+    }
+
+    // Wakes a task from a wait queue, which then has to reacquire its mutex
+    fn reacquire(&mut self, waiter: Task, mutex: Option<String>, woken: &mut Vec<Task>, now: u128) -> Result<(), TransitionError> {
+        match mutex {
+            Some(mutex) => {
+                if let Some(waiter) = self.lock(&mutex, waiter, now)? {
+                    woken.push(waiter);
+                }
+            }
+            None => woken.push(waiter),
+        }
+        Ok(())
+    }
+}
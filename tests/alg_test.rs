@@ -37,7 +37,7 @@ fn test_no_update() {
     // While the task queue is not empty, pop tasks and add to the fair scheduling algorithm
     while !task_queue.is_empty() {
         let tasks = task_queue.pop();
-        rq.push(tasks);
+        rq.push(tasks).unwrap();
         sysclock.tick();
     }
 
@@ -89,7 +89,7 @@ fn test_with_update() {
     // Loop through the task queue, pop tasks and push them to the Fair Algorithm, ticking the system clock after each iteration
     while !task_queue.is_empty() {
         let tasks = task_queue.pop();
-        rq.push(tasks);
+        rq.push(tasks).unwrap();
         sysclock.tick();
     }
 
@@ -98,18 +98,18 @@ fn test_with_update() {
     assert_eq!(curr.get_id(), 1);
 
     // Perform a CPU cycle on the task, tick the system clock, and insert the task back into the Fair Algorithm
-    curr.cpu_cycle();
+    curr.cpu_cycle().unwrap();
     sysclock.tick();
-    rq.insert(*curr);
+    rq.insert(*curr).unwrap();
 
     // Pop the next task from the Fair Algorithm and check if it's id is 2
     curr = rq.pop();
     assert_eq!(curr.get_id(), 2);
 
     // Perform a CPU cycle on the task, tick the system clock, and insert the task back into the Fair Algorithm
-    curr.cpu_cycle();
+    curr.cpu_cycle().unwrap();
     sysclock.tick();
-    rq.insert(*curr);
+    rq.insert(*curr).unwrap();
 
     // Pop the next task from the Fair Algorithm and check if it's id is 3
     curr = rq.pop();
@@ -119,7 +119,7 @@ fn test_with_update() {
 #[test]
 fn test_multithreaded_clock() {
     // Create a shared reference-counted mutex wrapped clock
-    let mut sysclock = Arc::new(Mutex::new(Clock::new()));
+    let sysclock = Arc::new(Mutex::new(Clock::new()));
 
     // A vector to store the spawned threads
     let mut threads = vec![];
@@ -127,7 +127,7 @@ fn test_multithreaded_clock() {
     let (sender, receiver) = std::sync::mpsc::channel();
 
     // Clone the shared clock and spawn a thread to send clock values through the channel
-    let clk = Arc::clone(&mut sysclock);
+    let clk = Arc::clone(&sysclock);
     let sending = thread::spawn(move || {
         // 50 clock ticks
         for _ in 0..50 {
@@ -144,7 +144,7 @@ fn test_multithreaded_clock() {
     threads.push(sending);
 
     // Clone the shared clock and spawn a thread to receive and use clock values from the channel
-    let c_clk = Arc::clone(&mut sysclock);
+    let c_clk = Arc::clone(&sysclock);
     let receiving = thread::spawn(move || {
         // Create a new fair algorithm instance using the shared clock
         let mut rq = FairAlgorithm::new(&mut c_clk.lock().unwrap());
@@ -167,25 +167,21 @@ fn test_multithreaded_clock() {
         // Pop tasks from the queue and push them to the fair algorithm
         while !task_queue.is_empty() {
             let tasks = task_queue.pop();
-            rq.push(tasks);
+            rq.push(tasks).unwrap();
         }
 
         // Continuously receive clock values from the channel and perform actions on the fair algorithm
-        loop {
-            // Get the next value from the channel, if unavailable break from the loop
-            let _time = match receiver.recv() {
-                Ok(tick) => tick,
-                Err(_) => break,
-            };
+        // Get the next value from the channel, if unavailable break from the loop
+        while let Ok(_time) = receiver.recv() {
 
             // If the fair algorithm is not empty, pop a task and perform a CPU cycle on it
             if !rq.is_empty() {
                 let mut curr = rq.pop();
-                curr.cpu_cycle();
-                rq.insert(*curr);
+                curr.cpu_cycle().unwrap();
+                rq.insert(*curr).unwrap();
             }
             // Call the idle function on the fair algorithm
-            rq.idle();
+            rq.idle().unwrap();
         }
         // Drop the receiver channel
         drop(receiver);
@@ -201,7 +197,7 @@ fn test_multithreaded_clock() {
 
 #[test]
 fn test_efficient_threads() {
-    let mut sysclock = Arc::new(Mutex::new(Clock::new()));
+    let sysclock = Arc::new(Mutex::new(Clock::new()));
     // Create a vector to store all the threads
     let mut threads = vec![];
     // Create two channels to send the current clock time to task_spawning thread
@@ -211,16 +207,13 @@ fn test_efficient_threads() {
     let (born_sender, born_recv) = std::sync::mpsc::channel();
 
     // Clone the reference to the system clock for use in this thread
-    let clk = Arc::clone(&mut sysclock);
+    let clk = Arc::clone(&sysclock);
     // Spawn a thread that ticks the system clock
     let ticking = thread::spawn(move || {
         for _ in 0..100 {
             let mut lock = clk.lock().unwrap();
             // Send the current time to both task_spawning and task scheduling threads
-            match clock_sender_1.send(lock.time()) {
-                Ok(_) => {}
-                _ => {}
-            };
+            let _ = clock_sender_1.send(lock.time());
             clock_sender_2.send(lock.time()).unwrap();
             lock.tick();
         }
@@ -232,7 +225,7 @@ fn test_efficient_threads() {
     threads.push(ticking);
 
     // Clone the reference to the system clock for use in this thread
-    let c_clk = Arc::clone(&mut sysclock);
+    let c_clk = Arc::clone(&sysclock);
     // Spawn a thread that creates two tasks and sends them to the task scheduling thread
     let task_spawning = thread::spawn(move || {
         // Receive the current clock time from the ticking thread
@@ -243,10 +236,7 @@ fn test_efficient_threads() {
         // Create the first task
         let task_one = Task::new(1, 15, 5, 3, time, 1);
         // Send the first task to the task scheduling thread
-        match born_sender.send(task_one) {
-            Ok(_) => {}
-            _ => {}
-        };
+        let _ = born_sender.send(task_one);
         // Receive the updated clock time from the ticking thread
         time = match spawner_clock_recv.try_recv() {
             Ok(tick) => tick,
@@ -255,10 +245,7 @@ fn test_efficient_threads() {
         // Create the second task
         let task_two = Task::new(2, 15, 3, 5, time, 1);
         // Send the second task to the task scheduling thread
-        match born_sender.send(task_two) {
-            Ok(_) => {}
-            _ => {}
-        };
+        let _ = born_sender.send(task_two);
     });
     // Add the task_spawning thread to the vector of threads
     threads.push(task_spawning);
@@ -268,37 +255,31 @@ fn test_efficient_threads() {
         let mut task_queue = TaskQueue::new();
         let mut rq = FairAlgorithm::new(&mut c_clk.lock().unwrap());
 
-        loop {
-            // Receive the tick time from the clock
-            let _time = match clock_recv.recv() {
-                Ok(tick) => tick,
-                Err(_) => break, // Break the loop if there's no tick time
-            };
-
-            // Try to receive a new task from the `born_recv` channel
-            match born_recv.try_recv() {
-                Ok(task) => task_queue.add(task), // Add the task to the task queue
-                _ => {}                           // No task received, do nothing
-            };
+        // Receive the tick time from the clock, breaking the loop if there's no tick time
+        while let Ok(_time) = clock_recv.recv() {
+            // Try to receive a new task from the `born_recv` channel and add it to the task queue
+            if let Ok(task) = born_recv.try_recv() {
+                task_queue.add(task);
+            }
 
             // Get the newly born tasks from the task queue
             let born_tasks = task_queue.pop();
 
             // Push the newly born tasks to the FairAlgorithm instance
-            rq.push(born_tasks);
+            rq.push(born_tasks).unwrap();
 
             // If there are tasks in the FairAlgorithm instance
             if !rq.is_empty() {
                 // Pop the first task and run it for one cycle
                 let mut curr = rq.pop();
-                curr.cpu_cycle();
+                curr.cpu_cycle().unwrap();
 
                 // Insert the task back to the FairAlgorithm instance
-                rq.insert(*curr);
+                rq.insert(*curr).unwrap();
             }
 
             // If there's no task to run, make the FairAlgorithm instance idle
-            rq.idle();
+            rq.idle().unwrap();
         }
         // Drop the `clock_recv` channel
         drop(clock_recv);
@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::arrival::Arrival;
+use cfs::proc::dist::task_rng;
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::task::Task;
+
+#[test]
+fn test_instants() {
+    let mut rng = task_rng(0, 1);
+
+    // A single arrival and a periodic one
+    assert_eq!(Arrival::At(7).instants(&mut rng), vec![7]);
+    let periodic = Arrival::Periodic { start: 5, period: 10, count: 3 };
+    assert_eq!(periodic.instants(&mut rng), vec![5, 15, 25]);
+
+    // Poisson arrivals are ordered, start on time and are reproducible through the seed
+    let poisson = Arrival::Poisson { start: 5, mean_gap: 20.0, count: 50 };
+    let instants = poisson.instants(&mut task_rng(3, 1));
+    assert_eq!(instants.len(), 50);
+    assert_eq!(instants[0], 5);
+    assert!(instants.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(instants, poisson.instants(&mut task_rng(3, 1)));
+    assert_ne!(instants, poisson.instants(&mut task_rng(4, 1)));
+}
+
+#[test]
+fn test_parse() {
+    // Every form parses and prints back to the same text
+    for text in ["12", "0 every 50 count 10", "3 poisson 25.5 count 4"] {
+        assert_eq!(text.parse::<Arrival>().unwrap().to_string(), text);
+    }
+
+    // Malformed arrivals are rejected
+    assert!("soon".parse::<Arrival>().is_err());
+    assert!("0 every 0 count 3".parse::<Arrival>().is_err());
+    assert!("0 poisson -1 count 3".parse::<Arrival>().is_err());
+    assert!("0 every 5".parse::<Arrival>().is_err());
+}
+
+#[test]
+fn test_pop_until() {
+    // Tasks arriving at ticks 0, 4 and 9
+    let mut task_queue = TaskQueue::new();
+    task_queue.append(&[
+        Task::new(1, 10, 5, 1, 9, 1),
+        Task::new(2, 10, 5, 1, 0, 1),
+        Task::new(3, 10, 5, 1, 4, 1),
+    ]);
+
+    // Only the tasks that have arrived by the given time are released, earliest first
+    assert_eq!(task_queue.pop_until(3).iter().map(|task| task.get_id()).collect::<Vec<u16>>(), vec![2]);
+    assert!(task_queue.pop_until(3).is_empty());
+    assert_eq!(task_queue.pop_until(20).iter().map(|task| task.get_id()).collect::<Vec<u16>>(), vec![3, 1]);
+    assert!(task_queue.is_empty());
+}
@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::script::Script;
+use cfs::proc::task::TaskChar;
+use cfs::sched::chrome::{self, ChromeTrace};
+use cfs::sched::scheduler::Scheduler;
+use cfs::sched::trace::EventLog;
+use serde_json::Value;
+use std::fs::File;
+
+// Returns the trace events of the given phase
+fn phase<'a>(trace: &'a Value, ph: &str) -> Vec<&'a Value> {
+    trace["traceEvents"].as_array().unwrap().iter().filter(|event| event["ph"] == ph).collect()
+}
+
+#[test]
+fn test_document() {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let log = EventLog::new();
+    scheduler.sink(Box::new(log.clone()));
+
+    // Tasks 1 and 2 take turns until task 1 reads from disk for 3 ticks, then task 1 runs once more
+    let tasks = vec![
+        TaskChar::scripted(1, 100, "cpu 2; io 3 disk; cpu 1".parse::<Script>().unwrap(), 2),
+        TaskChar::scripted(2, 100, "cpu 2".parse::<Script>().unwrap(), 1),
+    ];
+    scheduler.run(tasks).unwrap();
+    let trace = chrome::document(&log.events());
+
+    // Slices on the CPU track cover the ticks every task ran in a row
+    let slices: Vec<(String, u64, u64)> = phase(&trace, "X")
+        .iter()
+        .map(|event| (event["name"].as_str().unwrap().to_string(), event["ts"].as_u64().unwrap(), event["dur"].as_u64().unwrap()))
+        .collect();
+    let expected = [("task 1", 0, 1), ("task 2", 1, 1), ("task 1", 2, 1), ("task 2", 3, 1), ("task 1", 6, 1)];
+    assert_eq!(slices, expected.iter().map(|(name, ts, dur)| (String::from(*name), *ts, *dur)).collect::<Vec<_>>());
+
+    // The device serves the I/O for 3 ticks, from the tick after task 1 submits it
+    let io: Vec<(&str, u64)> = phase(&trace, "b").iter().chain(phase(&trace, "e").iter()).map(|event| (event["ph"].as_str().unwrap(), event["ts"].as_u64().unwrap())).collect();
+    assert_eq!(io, vec![("b", 3), ("e", 6)]);
+
+    // The CPU and the device are named, and the run queue length is a counter
+    let names: Vec<&str> = phase(&trace, "M").iter().map(|event| event["args"]["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["CPUs", "CPU 0", "device disk"]);
+    assert_eq!(phase(&trace, "C")[0]["args"]["runnable"], 2);
+}
+
+#[test]
+fn test_sink() {
+    // The sink writes the trace out once the run is over
+    let path = std::env::temp_dir().join(format!("cfs-chrome-{}.json", std::process::id()));
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    scheduler.sink(Box::new(ChromeTrace::new(File::create(&path).unwrap())));
+    scheduler.run(vec![TaskChar::new(1, 3, 3, 0, 1)]).unwrap();
+
+    let trace: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(phase(&trace, "X").len(), 1);
+    assert_eq!(phase(&trace, "X")[0]["dur"], 3);
+}
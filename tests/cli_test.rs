@@ -0,0 +1,82 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::cli::{Cli, Command, GenerateOptions, ReportFormat, RunOptions};
+use cfs::proc::generator::Profile;
+use cfs::proc::workload::{Format, Policy};
+use cfs::sched::gantt::Rows;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_run() {
+    // Without a command, the tasks file is run as before
+    let cli = Cli::parse::<&str>(&[]).unwrap();
+    assert_eq!(cli.command, Command::Run { workload: PathBuf::from("tasks.txt"), trace: true, options: RunOptions::default() });
+    assert_eq!((cli.seed, cli.quiet, cli.output), (None, false, None));
+
+    // Options may come before or after the command
+    let cli = Cli::parse(&[
+        "--seed", "7", "report", "w.toml", "-q", "--policy", "batch", "--timer-slack", "3", "--format", "json",
+        "--gantt-ascii", "--gantt-rows", "cpus", "--tasks-csv", "t.csv", "-o", "r.json",
+    ])
+    .unwrap();
+    assert_eq!((cli.seed, cli.quiet, cli.output), (Some(7), true, Some(PathBuf::from("r.json"))));
+    let options = match cli.command {
+        Command::Run { workload, trace: false, options } if workload == Path::new("w.toml") => options,
+        command => panic!("{:?}", command),
+    };
+    assert_eq!((options.policy, options.timer_slack, options.format), (Some(Policy::Batch), Some(3), ReportFormat::Json));
+    assert_eq!((options.outputs.gantt_ascii, options.outputs.gantt_rows), (true, Some(Rows::Cpus)));
+    assert_eq!(options.outputs.tasks_csv, Some(PathBuf::from("t.csv")));
+
+    // The flags of the runs before there were commands still work
+    let cli = Cli::parse(&["--workload", "w.json", "--chrome-trace", "trace.json"]).unwrap();
+    match cli.command {
+        Command::Run { workload, trace: true, options } => {
+            assert_eq!(workload, PathBuf::from("w.json"));
+            assert_eq!(options.outputs.chrome_trace, Some(PathBuf::from("trace.json")));
+        }
+        command => panic!("{:?}", command),
+    }
+}
+
+#[test]
+fn test_commands() {
+    let command = |args: &[&str]| Cli::parse(args).unwrap().command;
+    assert_eq!(command(&["replay", "trace.txt", "--tick-ns", "10"]), Command::Replay {
+        trace: PathBuf::from("trace.txt"),
+        tick: 10,
+        options: RunOptions::default(),
+    });
+    assert_eq!(command(&["validate", "a.toml", "b.txt"]), Command::Validate { workloads: vec![PathBuf::from("a.toml"), PathBuf::from("b.txt")] });
+    match command(&["compare", "a.toml", "b.toml", "--format", "csv"]) {
+        Command::Compare { workloads, options } => assert_eq!((workloads.len(), options.format), (2, ReportFormat::Csv)),
+        command => panic!("{:?}", command),
+    }
+    assert_eq!(command(&["generate", "web", "--tasks", "3", "--nice", "-5..5", "--cpu-time", "10", "--format", "legacy"]), Command::Generate(GenerateOptions {
+        profile: Profile::WebServer,
+        tasks: Some(3),
+        utilisation: None,
+        cpu_time: Some((10, 10)),
+        nice: Some((-5, 5)),
+        format: Some(Format::Legacy),
+    }));
+    assert_eq!(command(&["run", "--help"]), Command::Help);
+    assert_eq!(command(&["help"]), Command::Help);
+    assert_eq!(command(&["-V"]), Command::Version);
+}
+
+#[test]
+fn test_errors() {
+    let error = |args: &[&str]| Cli::parse(args).unwrap_err().to_string();
+    assert_eq!(error(&["run", "--cpus", "4"]), "unknown option --cpus for run");
+    assert_eq!(error(&["run", "--seed"]), "missing value after --seed");
+    assert_eq!(error(&["run", "--seed", "x"]), "invalid value `x` after --seed");
+    assert_eq!(error(&["validate", "--gantt-ascii", "a.toml"]), "unknown option --gantt-ascii for validate");
+    assert_eq!(error(&["compare", "--chrome-trace", "t.json", "a.toml"]), "unknown option --chrome-trace for compare");
+    assert_eq!(error(&["run", "a.toml", "b.toml"]), "unexpected argument `b.toml` after the workload");
+    assert_eq!(error(&["compare"]), "missing workloads to compare");
+    assert_eq!(error(&["generate", "huge"]), "unknown profile `huge`, expected batch, interactive, mixed, web or tiny");
+    assert_eq!(error(&["report", "--format", "svg"]), "expected text, json or csv after --format, found `svg`");
+}
@@ -0,0 +1,70 @@
+// Helpers shared by the tests driving the run queue tick by tick. Every test file uses some of them.
+#![allow(dead_code)]
+
+use cfs::proc::script::Script;
+use cfs::proc::stats::TaskStats;
+use cfs::proc::task::Task;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+// Creates a task of weight 1 following the given script
+pub fn task(id: u16, script: &str) -> Task {
+    weighted(id, script, 1)
+}
+
+// Creates a task with the given weight following the given script
+pub fn weighted(id: u16, script: &str, weight: u32) -> Task {
+    Task::scripted(id, 1000, script.parse::<Script>().unwrap(), 0, weight)
+}
+
+// What happened while the run queue ran until it was finished
+pub struct Run {
+    // The tick at which every task ran and its id
+    pub ran: Vec<(u128, u16)>,
+    // The tasks as they terminated
+    pub exited: Vec<Task>,
+    // Number of ticks the run took
+    pub ticks: u128,
+}
+
+impl Run {
+    // Returns the id of the task run at every tick
+    pub fn ids(&self) -> Vec<u16> {
+        self.ran.iter().map(|(_, id)| *id).collect()
+    }
+
+    // Returns the statistics of the terminated tasks in order of id
+    pub fn stats(&self) -> Vec<TaskStats> {
+        let mut stats: Vec<TaskStats> = self.exited.iter().map(|task| task.stats().clone()).collect();
+        stats.sort_by_key(|stats| stats.id);
+        stats
+    }
+}
+
+// Runs a single tick of the run queue: the task with the smallest vruntime runs and goes back on
+// the run queue, then the timers and devices move on. Returns the id of the task that ran, if any.
+pub fn step(rq: &mut FairAlgorithm, sysclock: &mut Clock) -> Option<u16> {
+    let mut ran = None;
+    if !rq.is_empty() {
+        let mut curr = rq.pop();
+        ran = Some(curr.get_id());
+        curr.cpu_cycle().unwrap();
+        rq.insert(*curr).unwrap();
+    }
+    rq.idle().unwrap();
+    sysclock.tick();
+    ran
+}
+
+// Runs the run queue until it is finished
+pub fn run(rq: &mut FairAlgorithm, sysclock: &mut Clock) -> Run {
+    let mut run = Run { ran: vec![], exited: vec![], ticks: 0 };
+    while !rq.is_finished() {
+        let time = sysclock.time();
+        if let Some(id) = step(rq, sysclock) {
+            run.ran.push((time, id));
+        }
+        run.exited.extend(rq.take_exited());
+        run.ticks += 1;
+    }
+    run
+}
@@ -0,0 +1,78 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::graph::{DepGraph, GraphError};
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::task::{BlockReason, Task, TaskChar, TaskStatus};
+
+use std::collections::HashMap;
+
+#[test]
+fn test_blocked_release() {
+    // Task 3 can only start once tasks 1 and 2 have terminated
+    let task_one = Task::new(1, 10, 5, 1, 0, 1);
+    let task_two = Task::new(2, 10, 5, 1, 0, 1);
+    let mut task_three = Task::new(3, 10, 5, 1, 0, 1);
+    task_three.set_deps(vec![1, 2]);
+
+    let mut task_queue = TaskQueue::new();
+    task_queue.append(&[task_one, task_two, task_three]);
+
+    // Only the first two tasks are born, the third one is blocked rather than new
+    let born: Vec<u16> = task_queue.pop().iter().map(|task| task.get_id()).collect();
+    assert_eq!(born, vec![1, 2]);
+    assert!(task_queue.is_stalled());
+    assert_eq!(task_queue.blocked()[0].get_status(), TaskStatus::Blocked { reason: BlockReason::Dependencies });
+
+    // Terminating one predecessor is not enough
+    task_queue.complete(1, 20);
+    assert!(task_queue.pop().is_empty());
+
+    // Terminating the other one releases the task, born at the time of release
+    task_queue.complete(2, 30);
+    let released = task_queue.pop();
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].get_id(), 3);
+    assert_eq!(released[0].get_status(), TaskStatus::New);
+    assert_eq!(released[0].get_start_time(), 30);
+    assert!(task_queue.is_empty());
+}
+
+#[test]
+fn test_invalid_graphs() {
+    // A dependency on a task outside the workload is rejected
+    let tasks = vec![TaskChar::new(1, 10, 5, 1, 1).with_deps(vec![7])];
+    assert_eq!(DepGraph::new(&tasks).err(), Some(GraphError::UnknownTask { task: 1, dep: 7 }));
+
+    // So are cycles
+    let tasks = vec![
+        TaskChar::new(1, 10, 5, 1, 1).with_deps(vec![3]),
+        TaskChar::new(2, 10, 5, 1, 1).with_deps(vec![1]),
+        TaskChar::new(3, 10, 5, 1, 1).with_deps(vec![2]),
+    ];
+    assert!(matches!(DepGraph::new(&tasks), Err(GraphError::Cycle(_))));
+}
+
+#[test]
+fn test_critical_path() {
+    // A diamond: 1 fans out to 2 and 3, which both feed 4
+    let tasks = vec![
+        TaskChar::new(1, 10, 5, 1, 1),
+        TaskChar::new(2, 10, 5, 1, 1).with_deps(vec![1]),
+        TaskChar::new(3, 10, 5, 1, 1).with_deps(vec![1]),
+        TaskChar::new(4, 10, 5, 1, 1).with_deps(vec![2, 3]),
+    ];
+    let graph = DepGraph::new(&tasks).unwrap();
+    assert!(graph.has_edges());
+
+    // Release and termination times of every task, task 3 being the slow branch
+    let times: HashMap<u16, (u128, u128)> = [(1, (0, 10)), (2, (10, 15)), (3, (10, 40)), (4, (40, 45))]
+        .into_iter()
+        .collect();
+
+    let report = graph.report(&times);
+    assert_eq!(report.makespan, 45);
+    assert_eq!(report.critical_path, vec![1, 3, 4]);
+    assert_eq!(report.critical_length, 45);
+}
@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::io::device::Device;
+use cfs::proc::dist::Dist;
+use cfs::proc::task::{BlockReason, TaskStatus};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use common::{run, task};
+
+#[test]
+fn test_concurrent_io() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Ten tasks doing 5 ticks of I/O on a device with no queue depth limit
+    rq.push((1..=10).map(|id| task(id, "io 5 disk; cpu 1")).collect()).unwrap();
+
+    // Every I/O progresses at once, so the I/O takes as long as one and the CPU bursts follow
+    assert_eq!(run(&mut rq, &mut sysclock).ticks, 15);
+    let stats = &rq.device_stats()[0];
+    assert_eq!((stats.name.as_str(), stats.completed, stats.busy_ticks), ("disk", 10, 5));
+}
+
+#[test]
+fn test_queue_depth() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_device(Device::new("disk", Some(2)));
+
+    // Four tasks doing 3 ticks of I/O on a device serving two at once
+    rq.push((1..=4).map(|id| task(id, "io 3 disk; cpu 1")).collect()).unwrap();
+
+    // The I/Os are served in two rounds, with two waiting meanwhile
+    let ticks = run(&mut rq, &mut sysclock).ticks;
+    let stats = &rq.device_stats()[0];
+    assert_eq!((stats.completed, stats.busy_ticks), (4, 6));
+    assert_eq!(stats.queued_ticks, 4 * 3 + 2 * 3);
+    assert_eq!(stats.utilisation(ticks), 6.0 / ticks as f64);
+}
+
+#[test]
+fn test_service_time() {
+    // A device that takes 4 ticks per I/O, whatever the burst length drawn by the task
+    let mut device = Device::new("net", Some(1)).with_service(Dist::Constant(4));
+    let mut io = task(1, "cpu 1; io 1 net; cpu 1");
+    io.schedule().unwrap();
+    io.run().unwrap();
+    io.cpu_cycle().unwrap();
+    assert_eq!(io.get_status(), TaskStatus::Blocked { reason: BlockReason::Io });
+    device.submit(io, 0);
+
+    // The I/O completes after the device's service time
+    for now in 0..3 {
+        assert!(device.cycle(now).unwrap().is_empty());
+    }
+    let done = device.cycle(3).unwrap();
+    assert_eq!(done.len(), 1);
+    assert_eq!(done[0].get_status(), TaskStatus::Runnable);
+    assert!(device.is_idle());
+    assert_eq!(device.stats().mean_latency(), 4.0);
+}
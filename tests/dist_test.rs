@@ -0,0 +1,87 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::dist::{task_rng, Dist};
+use cfs::proc::script::{Phase, Script};
+use cfs::proc::task::{BlockReason, Task, TaskStatus};
+
+#[test]
+fn test_parse() {
+    // Every supported distribution parses and prints back to the same text
+    for text in ["50", "uniform(10,20)", "exp(50)", "normal(50,5)", "pareto(10,1.5)", "hist(10:3,20:1)"] {
+        let dist: Dist = text.parse().unwrap();
+        assert_eq!(dist.to_string(), text);
+    }
+
+    // Distributions can be used as phase lengths, spaces included
+    let script: Script = "cpu uniform(1, 5); io exp(20) disk0".parse().unwrap();
+    assert_eq!(script.phases()[0], Phase::Cpu(Dist::Uniform(1, 5)));
+
+    // Invalid parameters are rejected
+    assert!("uniform(5,1)".parse::<Dist>().is_err());
+    assert!("exp(-1)".parse::<Dist>().is_err());
+    assert!("pareto(1)".parse::<Dist>().is_err());
+    assert!("hist(10:0)".parse::<Dist>().is_err());
+    assert!("zipf(2)".parse::<Dist>().is_err());
+}
+
+#[test]
+fn test_sampling() {
+    let dists = [
+        Dist::Uniform(10, 20),
+        Dist::Exponential(50.0),
+        Dist::Normal(50.0, 5.0),
+        Dist::Pareto(10.0, 1.5),
+        Dist::Empirical(vec![(10, 3.0), (20, 1.0)]),
+    ];
+
+    for dist in dists.iter() {
+        // The same seed yields the same sequence of burst lengths
+        let mut first = task_rng(42, 1);
+        let mut second = task_rng(42, 1);
+        let a: Vec<u64> = (0..100).map(|_| dist.sample(&mut first)).collect();
+        let b: Vec<u64> = (0..100).map(|_| dist.sample(&mut second)).collect();
+        assert_eq!(a, b);
+    }
+
+    // Samples stay within the support of bounded distributions
+    let mut rng = task_rng(7, 3);
+    for _ in 0..1000 {
+        let length = Dist::Uniform(10, 20).sample(&mut rng);
+        assert!((10..=20).contains(&length));
+        assert!([10, 20].contains(&Dist::Empirical(vec![(10, 3.0), (20, 1.0)]).sample(&mut rng)));
+    }
+
+    // The sample mean of an exponential distribution is close to its mean
+    let total: u64 = (0..10000).map(|_| Dist::Exponential(50.0).sample(&mut rng)).sum();
+    assert!((total as f64 / 10000.0 - 50.0).abs() < 3.0);
+}
+
+#[test]
+fn test_seeded_task() {
+    // Runs a task with stochastic bursts to completion, returning the ticks at which it left the CPU
+    let run = |seed: u64| {
+        let mut task = Task::scripted(1, 200, Script::bursts(Dist::Exponential(10.0), 1), 0, 1);
+        task.seed(seed);
+        let mut switches = vec![];
+        for tick in 0..200 {
+            task.schedule().unwrap();
+            task.run().unwrap();
+            task.cpu_cycle().unwrap();
+            match task.get_status() {
+                TaskStatus::Zombie => break,
+                TaskStatus::Blocked { reason: BlockReason::Io } => {
+                    switches.push(tick);
+                    task.io_cycle().unwrap();
+                }
+                _ => {}
+            }
+        }
+        switches
+    };
+
+    // Runs are reproducible through the seed and differ between seeds
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+}
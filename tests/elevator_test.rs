@@ -0,0 +1,96 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::io::elevator::{self, Bfq, Deadline, IoKind, IoScheduler, Kyber, Request};
+use cfs::proc::script::Script;
+use cfs::proc::task::Task;
+
+// Creates an I/O of the given kind and length submitted by a task with the given weight
+fn request(id: u16, weight: u32, kind: IoKind, length: u64, submitted: u128) -> Request {
+    let script: Script = format!("io {}; cpu 1", length).parse().unwrap();
+    let task = Task::scripted(id, 100, script, 0, weight);
+    Request { task, kind, submitted }
+}
+
+// Dispatches every queued I/O at the given tick, returning the ids of their tasks in order
+fn drain(scheduler: &mut dyn IoScheduler, now: u128) -> Vec<u16> {
+    let mut ids = vec![];
+    while let Some(request) = scheduler.dispatch(now) {
+        ids.push(request.task.get_id());
+    }
+    ids
+}
+
+#[test]
+fn test_noop() {
+    // I/Os are dispatched in submission order, whatever their kind
+    let mut noop = elevator::by_name("noop").unwrap();
+    noop.add(request(1, 1, IoKind::Write, 1, 0));
+    noop.add(request(2, 1, IoKind::Read, 1, 0));
+    noop.add(request(3, 1, IoKind::Write, 1, 0));
+    assert_eq!(noop.len(), 3);
+    assert_eq!(drain(noop.as_mut(), 0), vec![1, 2, 3]);
+
+    // Unknown schedulers are rejected
+    assert!(elevator::by_name("cfq").is_err());
+}
+
+#[test]
+fn test_deadline() {
+    let mut deadline = Deadline::with_expiry(10, 100);
+
+    // Reads are preferred, but pending writes are only passed over twice
+    deadline.add(request(1, 1, IoKind::Write, 1, 0));
+    for id in 2..=5 {
+        deadline.add(request(id, 1, IoKind::Read, 1, 0));
+    }
+    assert_eq!(drain(&mut deadline, 0), vec![2, 3, 1, 4, 5]);
+
+    // A write that waited past its expiry time goes ahead of fresh reads
+    deadline.add(request(6, 1, IoKind::Write, 1, 0));
+    deadline.add(request(7, 1, IoKind::Read, 1, 100));
+    assert_eq!(drain(&mut deadline, 100), vec![6, 7]);
+}
+
+#[test]
+fn test_bfq() {
+    let mut bfq = Bfq::new();
+
+    // Two tasks with weights 1 and 3 queue four I/Os of the same length each
+    for _ in 0..4 {
+        bfq.add(request(1, 1, IoKind::Read, 2, 0));
+        bfq.add(request(2, 3, IoKind::Read, 2, 0));
+    }
+
+    // The heavier task gets three I/Os served for every one of the lighter task
+    let order = drain(&mut bfq, 0);
+    assert_eq!(&order[..4], &[2, 2, 2, 1]);
+    assert_eq!(order.len(), 8);
+}
+
+#[test]
+fn test_kyber() {
+    let mut kyber = Kyber::with_target(10, 4);
+
+    // Reads always go first, and writes are limited to the write depth
+    for id in 1..=6 {
+        kyber.add(request(id, 1, IoKind::Write, 1, 0));
+    }
+    kyber.add(request(7, 1, IoKind::Read, 1, 0));
+    assert_eq!(drain(&mut kyber, 0), vec![7, 1, 2, 3, 4]);
+
+    // A read over its latency target halves the write depth
+    kyber.completed(IoKind::Read, 20);
+    assert_eq!(kyber.write_depth(), 2);
+
+    // Completed writes free their slots, but only as many as the new depth allows
+    for _ in 0..4 {
+        kyber.completed(IoKind::Write, 1);
+    }
+    assert_eq!(drain(&mut kyber, 1), vec![5, 6]);
+
+    // Reads well within the target let the write depth grow back
+    kyber.completed(IoKind::Read, 2);
+    assert_eq!(kyber.write_depth(), 3);
+}
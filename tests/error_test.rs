@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::error::Error;
+use cfs::proc::dist::Dist;
+use cfs::proc::arrival::Arrival;
+use cfs::proc::graph::GraphError;
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::script::Script;
+use cfs::proc::task::{Task, TaskChar};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, scheduler::Scheduler};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_empty_queues() {
+    // Taking a task from an empty run queue is an error rather than a panic
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    assert_eq!(rq.try_pop().unwrap_err(), Error::EmptyRunQueue);
+    assert!(rq.run().is_ok());
+
+    // Removing a start time no task has yields nothing
+    let mut task_queue = TaskQueue::new();
+    assert!(task_queue.remove(3).is_none());
+}
+
+#[test]
+fn test_invalid_dist() {
+    // Distributions with parameters the parser rejects still draw lengths
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(Dist::Normal(4.0, f64::NAN).sample(&mut rng), 4);
+    assert_eq!(Dist::Empirical(vec![]).sample(&mut rng), 0);
+    assert!((2..=5).contains(&Dist::Uniform(5, 2).sample(&mut rng)));
+}
+
+#[test]
+fn test_run_errors() {
+    // Two tasks depending on each other
+    let mut scheduler = Scheduler::new();
+    let tasks = vec![
+        TaskChar::new(1, 2, 2, 0, 1).with_deps(vec![2]),
+        TaskChar::new(2, 2, 2, 0, 1).with_deps(vec![1]),
+    ];
+
+    // The run fails with the cycle instead of starting
+    match scheduler.run(tasks) {
+        Err(Error::Graph(GraphError::Cycle(_))) => {}
+        other => panic!("Expected a dependency cycle, got {:?}", other),
+    }
+
+    // A task of weight 0 is refused rather than dividing its vruntime by 0
+    let tasks = vec![TaskChar::new(1, 3, 2, 0, 1), TaskChar::new(2, 3, 2, 0, 0)];
+    assert_eq!(scheduler.run(tasks).unwrap_err(), Error::ZeroWeight(2));
+    let task = Task::new(1, 3, 2, 0, 0, 0);
+    assert_eq!(task.base_weight(), 0);
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    assert_eq!(rq.insert(task).unwrap_err(), Error::ZeroWeight(1));
+    assert!(rq.is_empty());
+
+    // Instances of a periodic task get ids of their own until the ids run out
+    let periodic = |count| vec![TaskChar::new(u16::MAX - 1, 3, 2, 0, 1).with_arrival(Arrival::Periodic { start: 0, period: 5, count })];
+    assert!(scheduler.run(periodic(2)).is_ok());
+    assert_eq!(scheduler.run(periodic(3)).unwrap_err(), Error::PidsExhausted);
+
+    // So do forked children
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let script = "fork { cpu 1 }; cpu 1".parse::<Script>().unwrap();
+    assert_eq!(rq.push(vec![Task::scripted(u16::MAX, 2, script, 0, 1)]).unwrap_err(), Error::PidsExhausted);
+
+    // A valid workload runs to completion and returns its report
+    let tasks = vec![TaskChar::new(1, 3, 2, 0, 1)];
+    let report = scheduler.run(tasks).unwrap();
+    assert!(report.deadlocked.is_empty() && report.stalled.is_empty());
+    assert!(report.dag.is_none());
+}
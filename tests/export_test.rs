@@ -0,0 +1,98 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::script::Script;
+use cfs::proc::task::TaskChar;
+use cfs::sched::export::{self, JsonLines, SeriesCsv};
+use cfs::sched::scheduler::Scheduler;
+use cfs::sched::trace::EventLog;
+use serde_json::Value;
+use std::fs::File;
+
+// Tasks 1 and 2 take turns until task 1 sleeps for 3 ticks, then task 1 runs once more
+fn tasks() -> Vec<TaskChar> {
+    vec![
+        TaskChar::scripted(1, 100, "cpu 2; sleep 3; cpu 1".parse::<Script>().unwrap(), 2),
+        TaskChar::scripted(2, 100, "cpu 2".parse::<Script>().unwrap(), 1),
+    ]
+}
+
+#[test]
+fn test_tasks() {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let report = scheduler.run(tasks()).unwrap();
+
+    // The CSV has a header and a row per task, in order of id
+    let csv = export::tasks_csv(&report.tasks);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("id,weight,arrival,first_run,completion,turnaround,runtime,wait,io_wait,sleep"));
+    let task1 = &report.tasks[0];
+    let expected = format!(
+        "1,2,0,0,{},{},3,{},{},{}",
+        task1.completion.unwrap(),
+        task1.completion.unwrap(),
+        task1.wait,
+        task1.io_wait,
+        task1.sleep
+    );
+    assert!(lines[1].starts_with(&expected));
+
+    // The JSON holds the same statistics
+    let json: Value = serde_json::from_str(&export::tasks_json(&report.tasks).unwrap()).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 2);
+    assert_eq!(json[1]["id"], 2);
+    assert_eq!(json[1]["runtime"], 2);
+}
+
+#[test]
+fn test_series() {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let log = EventLog::new();
+    scheduler.sink(Box::new(log.clone()));
+    scheduler.run(tasks()).unwrap();
+    let samples = export::series(&log.events());
+
+    // There is a sample per tick, idle while task 1 sleeps after task 2 exits
+    let running: Vec<Option<u16>> = samples.iter().map(|sample| sample.running).collect();
+    assert_eq!(running, vec![Some(1), Some(2), Some(1), Some(2), None, None, Some(1)]);
+    assert_eq!(samples[4].utilisation, 0.0);
+    assert_eq!(samples[6].cumulative_utilisation, 5.0 / 7.0);
+
+    // min_vruntime never goes back, and the run queue is empty once everything ran
+    assert!(samples.windows(2).all(|pair| pair[0].min_vruntime <= pair[1].min_vruntime));
+    assert_eq!(samples.last().unwrap().runnable, 0);
+
+    // The CSV leaves the running column empty on idle ticks
+    let csv = export::series_csv(&samples);
+    assert_eq!(csv.lines().next(), Some("time,runnable,min_vruntime,running,utilisation,cumulative_utilisation"));
+    assert!(csv.lines().nth(5).unwrap().contains(",,0.0000,"));
+}
+
+#[test]
+fn test_sinks() {
+    // The sinks write the events and the time series as the run goes
+    let dir = std::env::temp_dir();
+    let events = dir.join(format!("cfs-events-{}.jsonl", std::process::id()));
+    let series = dir.join(format!("cfs-series-{}.csv", std::process::id()));
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let log = EventLog::new();
+    scheduler.sink(Box::new(log.clone()));
+    scheduler.sink(Box::new(JsonLines::new(File::create(&events).unwrap())));
+    scheduler.sink(Box::new(SeriesCsv::new(File::create(&series).unwrap())));
+    scheduler.run(tasks()).unwrap();
+
+    // Every event is a line of JSON, as it serialises
+    let lines: Vec<Value> = std::fs::read_to_string(&events).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let expected: Vec<Value> = log.events().iter().map(|event| serde_json::to_value(event).unwrap()).collect();
+    assert_eq!(lines, expected);
+
+    // The streamed time series matches the one built afterwards
+    assert_eq!(std::fs::read_to_string(&series).unwrap(), export::series_csv(&export::series(&log.events())));
+    std::fs::remove_file(&events).unwrap();
+    std::fs::remove_file(&series).unwrap();
+}
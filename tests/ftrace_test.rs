@@ -0,0 +1,87 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::error::Error;
+use cfs::proc::task::Task;
+use cfs::proc::task::TaskChar;
+use cfs::sched::ftrace::{self, Ftrace};
+use cfs::sched::trace::EventLog;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, scheduler::Scheduler};
+use std::fs::File;
+use std::io::Cursor;
+use common::task;
+
+// Runs the given tasks until they are finished, returning the ftrace text of the run
+fn trace(tasks: Vec<Task>) -> String {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let log = EventLog::new();
+    rq.add_sink(Box::new(log.clone()));
+    rq.push(tasks).unwrap();
+    common::run(&mut rq, &mut sysclock);
+    ftrace::text(&log.events())
+}
+
+#[test]
+fn test_text() {
+    // Task 1 reads for 3 ticks after its first tick, while task 2 runs once with nice 5
+    let text = trace(vec![task(1, "cpu 1; io 3; cpu 1"), task(2, "nice 5; cpu 1")]);
+    let lines: Vec<&str> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(
+        lines[1],
+        "          <idle>-0       [000] d..2     0.000000: sched_wakeup_new: comm=task2 pid=2 prio=125 target_cpu=000"
+    );
+
+    // Task 1 leaves the CPU in uninterruptible sleep for its I/O
+    assert_eq!(
+        lines[3],
+        "           task1-1       [000] d..2     0.000001: sched_switch: prev_comm=task1 prev_pid=1 prev_prio=120 prev_state=D ==> next_comm=task2 next_pid=2 next_prio=125"
+    );
+    assert_eq!(lines[4], "           task2-2       [000] d..2     0.000001: sched_process_exit: comm=task2 pid=2 prio=125");
+
+    // With nothing left to run the CPU goes idle until the I/O completes
+    assert!(lines[5].ends_with("prev_state=Z ==> next_comm=swapper/0 next_pid=0 next_prio=120"));
+    assert!(lines[6].starts_with("          <idle>-0       [000] d..2     0.000004: sched_wakeup: comm=task1"));
+    assert!(lines[9].contains("0.000005: sched_switch: prev_comm=task1"));
+}
+
+#[test]
+fn test_preemption() {
+    // Tasks taking turns are switched out runnable, and a task that exits is switched out a zombie
+    let text = trace(vec![task(1, "cpu 2"), task(2, "cpu 2")]);
+    let switches: Vec<&str> = text.lines().filter(|line| line.contains("sched_switch")).collect();
+    assert_eq!(switches.len(), 5);
+    assert!(switches[1].contains("0.000001: sched_switch: prev_comm=task1 prev_pid=1 prev_prio=120 prev_state=R ==> next_comm=task2"));
+    assert!(switches[3].contains("0.000003: sched_switch: prev_comm=task1 prev_pid=1 prev_prio=120 prev_state=Z ==> next_comm=task2"));
+
+    // A task running twice in a row is not switched
+    let text = trace(vec![task(1, "cpu 3")]);
+    assert_eq!(text.lines().filter(|line| line.contains("sched_switch")).count(), 2);
+}
+
+#[test]
+fn test_sink() {
+    // The sink writes the header and the lines of the run as it goes
+    let path = std::env::temp_dir().join(format!("cfs-ftrace-{}.txt", std::process::id()));
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    scheduler.sink(Box::new(Ftrace::new(File::create(&path).unwrap())));
+    scheduler.run(vec![TaskChar::new(1, 2, 2, 0, 1)]).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(text.starts_with("# tracer: nop"));
+    assert_eq!(text.lines().filter(|line| line.contains("sched_switch")).count(), 2);
+
+    // A trace that does not fit where it is written fails the run
+    scheduler.clear_sinks();
+    scheduler.sink(Box::new(Ftrace::new(Cursor::new([0; 16]))));
+    match scheduler.run(vec![TaskChar::new(1, 2, 2, 0, 1)]) {
+        Err(Error::Sink(e)) => assert_eq!(e.to_string(), "could not write the ftrace trace: failed to write whole buffer"),
+        other => panic!("Expected the trace to fail, got {:?}", other),
+    }
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::script::Script;
+use cfs::proc::task::TaskChar;
+use cfs::sched::gantt::{Gantt, Rows, Segment};
+use cfs::sched::scheduler::Scheduler;
+use cfs::sched::trace::EventLog;
+
+// Runs the tasks and returns the Gantt chart of the run
+fn chart(tasks: Vec<TaskChar>) -> Gantt {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let log = EventLog::new();
+    scheduler.sink(Box::new(log.clone()));
+    scheduler.run(tasks).unwrap();
+    Gantt::new(&log.events())
+}
+
+#[test]
+fn test_segments() {
+    // Tasks 1 and 2 take turns until task 1 reads from disk for 3 ticks, then task 1 runs once more
+    let gantt = chart(vec![
+        TaskChar::scripted(1, 100, "cpu 2; io 3 disk; cpu 1".parse::<Script>().unwrap(), 2),
+        TaskChar::scripted(2, 100, "cpu 2".parse::<Script>().unwrap(), 1),
+    ]);
+    assert_eq!(gantt.end(), 7);
+
+    // Task 1 waits while task 2 runs, then does I/O on the ticks 3 to 5 while off the CPU
+    assert_eq!(
+        gantt.segments(1),
+        &[(0, 1, Segment::Running), (1, 2, Segment::Waiting), (2, 3, Segment::Running), (3, 6, Segment::Io), (6, 7, Segment::Running)]
+    );
+    assert_eq!(gantt.segments(2), &[(0, 1, Segment::Waiting), (1, 2, Segment::Running), (2, 3, Segment::Waiting), (3, 4, Segment::Running)]);
+
+    // Each task loses the CPU to the other while still runnable, until task 1 blocks on the I/O
+    assert_eq!(gantt.preemptions(), &[(1, 0, 1), (2, 0, 2)]);
+}
+
+#[test]
+fn test_ascii() {
+    let gantt = chart(vec![
+        TaskChar::scripted(1, 100, "cpu 2; io 3 disk; cpu 1".parse::<Script>().unwrap(), 2),
+        TaskChar::scripted(2, 100, "cpu 2".parse::<Script>().unwrap(), 1),
+    ]);
+
+    // Task rows show segments, with preemptions marked, and CPU rows the task running
+    let tasks = gantt.ascii(Rows::Tasks, 80);
+    let lines: Vec<&str> = tasks.lines().collect();
+    assert_eq!(lines[0], "Gantt chart over 7 ticks, 1 tick per column");
+    assert_eq!(lines[1], "task 1 |#!#===#|");
+    assert_eq!(lines[2], "task 2 |-#!#   |");
+    let cpus = gantt.ascii(Rows::Cpus, 80);
+    assert_eq!(cpus.lines().nth(1), Some("CPU 0 |1212  1|"));
+
+    // Long runs are scaled to fit the width, every column showing what takes up most of its ticks
+    let gantt = chart(vec![TaskChar::new(1, 100, 100, 0, 1)]);
+    let scaled = gantt.ascii(Rows::Tasks, 10);
+    let lines: Vec<&str> = scaled.lines().collect();
+    assert_eq!(lines[0], "Gantt chart over 100 ticks, 10 ticks per column");
+    assert_eq!(lines[1], "task 1 |##########|");
+}
+
+#[test]
+fn test_svg() {
+    let gantt = chart(vec![
+        TaskChar::scripted(1, 100, "cpu 2; io 3 disk; cpu 1".parse::<Script>().unwrap(), 2),
+        TaskChar::scripted(2, 100, "cpu 2".parse::<Script>().unwrap(), 1),
+    ]);
+
+    // The SVG is a standalone document with a bar for every segment and a line for every preemption
+    let svg = gantt.svg(Rows::Tasks);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<title>").count(), 9);
+    assert_eq!(svg.matches("stroke=\"#e53935\"").count(), 2);
+    assert!(svg.contains("<title>Io 3..6</title>"));
+}
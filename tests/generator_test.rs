@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::error::Error;
+use cfs::proc::generator::{Generator, Profile};
+use cfs::proc::validate;
+use cfs::proc::workload::{Format, Workload};
+use cfs::sched::scheduler::Scheduler;
+
+const PROFILES: [Profile; 5] = [Profile::Batch, Profile::Interactive, Profile::Mixed, Profile::WebServer, Profile::Tiny];
+
+#[test]
+fn test_seed() {
+    // The same seed gives the same workload, and another seed another one
+    let toml = |seed| Generator::new(Profile::Mixed).with_seed(seed).generate().unwrap().write(Format::Toml).unwrap();
+    assert_eq!(toml(7), toml(7));
+    assert_ne!(toml(7), toml(8));
+    assert_eq!("web".parse(), Ok(Profile::WebServer));
+    assert!("server".parse::<Profile>().is_err());
+}
+
+#[test]
+fn test_profiles() {
+    for profile in PROFILES {
+        // Every profile gives a valid workload, which reads back the same in every format
+        let workload = Generator::new(profile).with_seed(1).generate().unwrap();
+        assert!(!validate::has_errors(&validate::validate(&workload)), "{:?}", profile);
+        for format in [Format::Toml, Format::Json, Format::Legacy] {
+            let text = workload.write(format).unwrap();
+            let read = Workload::parse(&text, format).unwrap();
+            assert_eq!(read.write(format).unwrap(), text, "{:?} {:?}", profile, format);
+            assert_eq!(read.tasks.len(), workload.tasks.len());
+        }
+    }
+    assert_eq!(Generator::new(Profile::Tiny).generate().unwrap().tasks.len(), 200);
+    assert_eq!(Generator::new(Profile::WebServer).generate().unwrap().devices[0].name, "disk");
+}
+
+#[test]
+fn test_ranges() {
+    // Tasks are drawn from the given ranges
+    let workload = Generator::new(Profile::Interactive).with_tasks(20).with_cpu_time(30, 40).with_nice(-20, -20).generate().unwrap();
+    assert_eq!(workload.tasks.len(), 20);
+    for task in workload.tasks.iter() {
+        assert!((30..=40).contains(&task.task.get_cpu_time()));
+        assert_eq!(task.task.get_weight(), 88761);
+    }
+
+    // There are no more tasks than task ids
+    assert_eq!(Generator::new(Profile::Tiny).with_tasks(usize::from(u16::MAX)).generate().unwrap().tasks.len(), 65535);
+    assert_eq!(Generator::new(Profile::Tiny).with_tasks(usize::from(u16::MAX) + 1).generate().unwrap_err(), Error::PidsExhausted);
+}
+
+#[test]
+fn test_utilisation() {
+    // Batch tasks sized to keep half of the CPU busy between them
+    let workload = Generator::new(Profile::Batch).with_seed(2).with_tasks(4).with_cpu_time(2000, 2000).with_utilisation(0.5).generate().unwrap();
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let tasks = workload.apply(&mut scheduler);
+    let report = scheduler.run(tasks).unwrap();
+    assert!((0.35..0.65).contains(&report.summary.utilisation), "{}", report.summary.utilisation);
+}
@@ -0,0 +1,80 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::histogram::{Bucket, Histogram};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use common::{step, task};
+
+#[test]
+fn test_buckets() {
+    // Small values get a bucket each, larger ones share buckets an eighth of their power of two wide
+    let mut histogram = Histogram::new();
+    for value in [3, 15, 16, 17, 100, 1000] {
+        histogram.record(value);
+    }
+    let buckets = histogram.buckets();
+    assert_eq!(buckets[0], Bucket { low: 3, high: 3, count: 1 });
+    assert_eq!(buckets[1], Bucket { low: 15, high: 15, count: 1 });
+    assert_eq!(buckets[2], Bucket { low: 16, high: 17, count: 2 });
+    assert_eq!(buckets[3], Bucket { low: 96, high: 103, count: 1 });
+    assert_eq!(buckets[4], Bucket { low: 960, high: 1023, count: 1 });
+
+    // Percentiles are exact up to the width of their bucket, and never above the largest value
+    assert_eq!((histogram.min(), histogram.max(), histogram.count()), (3, 1000, 6));
+    assert_eq!(histogram.percentile(50.0), 17);
+    assert_eq!(histogram.percentile(80.0), 103);
+    assert_eq!(histogram.percentile(100.0), 1000);
+    assert_eq!(Histogram::new().percentile(99.0), 0);
+}
+
+#[test]
+fn test_merge() {
+    // Merging adds up the counts of histograms recorded separately
+    let mut first = Histogram::new();
+    let mut second = Histogram::new();
+    (0..10).for_each(|value| first.record(value));
+    (5..500).for_each(|value| second.record(value));
+    let mut merged = first.clone();
+    merged.merge(&second);
+    merged.merge(&Histogram::new());
+
+    let mut whole = Histogram::new();
+    (0..10).chain(5..500).for_each(|value| whole.record(value));
+    assert_eq!(merged, whole);
+    assert_eq!(merged.mean(), whole.mean());
+
+    // The drawing has a header and a line per non-empty bucket
+    assert_eq!(merged.ascii("ticks").lines().count(), merged.buckets().len() + 1);
+}
+
+#[test]
+fn test_latencies() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Task 1 runs 3 ticks in a row while task 2 waits, then both sleep and wake together
+    rq.push(vec![task(1, "cpu 3; sleep 2; cpu 1"), task(2, "cpu 1; sleep 1; cpu 1")]).unwrap();
+    rq.stop(2).unwrap();
+    let mut exited = vec![];
+    while !rq.is_finished() {
+        if sysclock.time() == 3 {
+            rq.cont(2).unwrap();
+        }
+        step(&mut rq, &mut sysclock);
+        exited.extend(rq.take_exited());
+    }
+    exited.sort_by_key(|task| task.get_id());
+
+    // Task 1 ran a slice of 3 ticks and one of 1, waking straight onto the CPU both times
+    let latency = &exited[0].stats().latency;
+    assert_eq!(latency.slice.buckets().iter().map(|bucket| bucket.low).collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!((latency.wakeup.count(), latency.wakeup.max()), (2, 0));
+
+    // Task 2 waited 3 ticks on its arrival while it was stopped
+    let latency = &exited[1].stats().latency;
+    assert_eq!(latency.wakeup.max(), 3);
+    assert_eq!(latency.slice.count(), 2);
+}
@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::stats::TaskStats;
+use cfs::proc::task::TaskChar;
+use cfs::sched::export;
+use cfs::sched::metrics::{self, Percentiles, Summary};
+use cfs::sched::scheduler::Scheduler;
+
+// Creates the statistics of a task that terminated
+fn finished(id: u16, weight: u32, arrival: u128, first_run: u128, completion: u128, runtime: u64) -> TaskStats {
+    let mut stats = TaskStats::new(id, arrival);
+    stats.weight = weight;
+    stats.first_run = Some(first_run);
+    stats.completion = Some(completion);
+    stats.runtime = runtime;
+    stats.wait = (completion - arrival) as u64 - runtime;
+    stats
+}
+
+#[test]
+fn test_percentiles() {
+    // Nearest-rank percentiles of 1 to 100 are the values at those ranks
+    let percentiles = Percentiles::new((1..=100).rev().collect());
+    assert_eq!(percentiles.mean, 50.5);
+    assert_eq!((percentiles.median, percentiles.p95, percentiles.p99), (50, 95, 99));
+
+    // With few values, the tail percentiles are the largest value
+    let percentiles = Percentiles::new(vec![3, 1, 2]);
+    assert_eq!((percentiles.median, percentiles.p95, percentiles.p99), (2, 3, 3));
+    assert_eq!(Percentiles::new(vec![]), Percentiles::default());
+}
+
+#[test]
+fn test_jain_index() {
+    // Equal shares are perfectly fair, and one task getting everything gives 1/n
+    assert_eq!(metrics::jain_index([2.0, 2.0, 2.0].into_iter()), 1.0);
+    assert_eq!(metrics::jain_index([6.0, 0.0, 0.0].into_iter()), 1.0 / 3.0);
+    assert_eq!(metrics::jain_index(std::iter::empty()), 1.0);
+}
+
+#[test]
+fn test_summary() {
+    // A task of weight 2 running twice as long as a task of weight 1 gets a fair share
+    let tasks = vec![finished(1, 2, 0, 0, 10, 6), finished(2, 1, 0, 2, 9, 3), TaskStats::new(3, 0)];
+    let summary = Summary::new(&tasks, 4);
+
+    // Unfinished tasks are left out
+    assert_eq!((summary.completed, summary.makespan), (2, 10));
+    assert_eq!((summary.throughput, summary.utilisation), (0.2, 0.9));
+    assert_eq!((summary.turnaround.mean, summary.waiting.mean, summary.response.mean), (9.5, 5.0, 1.0));
+    assert_eq!((summary.jain_index, summary.vruntime_spread), (1.0, 4));
+}
+
+#[test]
+fn test_serialise() {
+    // The report of a run serialises with its per-task statistics and summary
+    let mut scheduler = Scheduler::new();
+    let report = scheduler.run(vec![TaskChar::new(1, 4, 2, 1, 1), TaskChar::new(2, 4, 4, 0, 1)]).unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["summary"]["completed"], 2);
+    assert_eq!(json["tasks"][1]["id"], 2);
+    assert_eq!(json["tasks"][0]["runtime"], 4);
+    assert!(json["tasks"][0].get("runnable_since").is_none());
+}
+
+#[test]
+fn test_comparison() {
+    // Runs are compared in a column each, as a table or as CSV
+    let runs = vec![
+        (String::from("a.toml"), Summary::new(&[finished(1, 2, 0, 0, 10, 6)], 0)),
+        (String::from("b.toml"), Summary::new(&[finished(1, 1, 0, 2, 9, 3)], 5)),
+    ];
+    let table = metrics::comparison(&runs);
+    assert!(table.lines().next().unwrap().ends_with("    a.toml     b.toml"));
+    assert!(table.contains("\nCPU utilisation       60.0%      33.3%\n"));
+    assert!(table.ends_with("\nVruntime spread           0          5\n"));
+    let csv = export::summaries_csv(&runs);
+    assert_eq!(csv.lines().nth(2), Some("b.toml,1,9,0.1111,0.3333,9.0000,9,9,9,6.0000,6,6,6,2.0000,2,2,2,1.0000,5"));
+
+    // The report of a run prints as it would at its end
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    scheduler.print_report(false);
+    let report = scheduler.run(vec![TaskChar::new(1, 4, 2, 1, 1)]).unwrap();
+    let text = report.to_string();
+    assert!(text.starts_with(&report.task_table()));
+    assert!(text.contains(&report.summary.to_string()));
+}
@@ -0,0 +1,114 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use common::{run, step, weighted};
+
+// Runs the run queue until the given number of tasks are blocked on primitives
+fn run_until_parked(rq: &mut FairAlgorithm, sysclock: &mut Clock, count: usize) {
+    for _ in 0..100 {
+        if rq.parked().len() == count {
+            return;
+        }
+        step(rq, sysclock);
+    }
+    panic!("{} tasks never blocked", count);
+}
+
+#[test]
+fn test_inversion_detected() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // A light task takes the mutex and a heavy task blocks on it, with inheritance off
+    rq.push(vec![
+        weighted(1, "lock m; cpu 3; unlock m", 1),
+        weighted(2, "cpu 1; lock m; cpu 1; unlock m", 10),
+    ]).unwrap();
+    run(&mut rq, &mut sysclock);
+
+    // The heavy task was held up by the light one until it released the mutex
+    let inversions = rq.sync().inversions();
+    assert_eq!(inversions.len(), 1);
+    assert_eq!((inversions[0].waiter, inversions[0].holder), (2, 1));
+    assert_eq!((inversions[0].waiter_weight, inversions[0].holder_weight), (10, 1));
+    assert_eq!(inversions[0].mutex, "m");
+    assert!(inversions[0].end.unwrap() > inversions[0].start);
+
+    // A light task blocked by a heavier one is not an inversion
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.push(vec![
+        weighted(1, "lock m; cpu 3; unlock m", 10),
+        weighted(2, "cpu 1; lock m; cpu 1; unlock m", 1),
+    ]).unwrap();
+    run(&mut rq, &mut sysclock);
+    assert!(rq.sync().inversions().is_empty());
+}
+
+#[test]
+fn test_inheritance() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_priority_inheritance(true);
+
+    // The heavy task blocks on the mutex held by the light one
+    rq.push(vec![
+        weighted(1, "lock m; cpu 3; unlock m; cpu 5", 1),
+        weighted(2, "cpu 1; lock m; cpu 1; unlock m", 10),
+    ]).unwrap();
+    run_until_parked(&mut rq, &mut sysclock, 1);
+
+    // The holder, now alone on the run queue, runs with the waiter's weight
+    let mut holder = rq.pop();
+    assert_eq!(holder.get_id(), 1);
+    assert_eq!((holder.weight(), holder.base_weight()), (10, 1));
+
+    // Run the holder alone until it releases the mutex
+    loop {
+        holder.cpu_cycle().unwrap();
+        rq.insert(*holder).unwrap();
+        sysclock.tick();
+        if rq.sync().owner("m") != Some(1) {
+            break;
+        }
+        holder = rq.pop();
+    }
+
+    // Releasing the mutex hands it to the waiter and drops the inherited weight
+    assert_eq!(rq.sync().owner("m"), Some(2));
+    let tasks = [rq.pop(), rq.pop()];
+    let holder = tasks.iter().find(|task| task.get_id() == 1).unwrap();
+    assert_eq!(holder.weight(), 1);
+}
+
+#[test]
+fn test_transitive_inheritance() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_priority_inheritance(true);
+
+    // Task 3 blocks on task 2, which blocks on task 1
+    rq.push(vec![
+        weighted(1, "lock a; cpu 10; unlock a", 1),
+        weighted(2, "lock b; cpu 1; lock a; cpu 1; unlock a; unlock b", 2),
+        weighted(3, "cpu 1; lock b; cpu 1; unlock b", 10),
+    ]).unwrap();
+    run_until_parked(&mut rq, &mut sysclock, 2);
+    assert_eq!(rq.parked(), vec![(2, String::from("mutex a")), (3, String::from("mutex b"))]);
+
+    // The weight of task 3 is passed along the chain to task 1
+    let holder = rq.pop();
+    assert_eq!(holder.get_id(), 1);
+    assert_eq!(holder.weight(), 10);
+    rq.insert(*holder).unwrap();
+
+    // Every task gets the mutexes in the end, and both inversions are closed
+    run(&mut rq, &mut sysclock);
+    assert!(rq.parked().is_empty());
+    let inversions = rq.sync().inversions();
+    assert_eq!(inversions.len(), 2);
+    assert!(inversions.iter().all(|inversion| inversion.end.is_some()));
+}
@@ -37,11 +37,11 @@ fn test_popping() {
         let tasks = task_queue.pop();
         for mut task in tasks {
             // Schedule the task
-            task.schedule();
+            task.schedule().unwrap();
             // Assert that the start time of the task is equal to the current time
             assert_eq!(task.get_start_time(), idx);
             // Assert that the status of the task is waiting
-            assert_eq!(task.get_status(), TaskStatus::Waiting);
+            assert_eq!(task.get_status(), TaskStatus::Runnable);
         }
         // Update the current time
         idx = match idx {
@@ -83,14 +83,14 @@ fn test_remove() {
     // Until the task queue is empty.
     while !task_queue.is_empty() {
         // Removing tasks from the queue with the task_id specified by `idx`.
-        let tasks = task_queue.remove(idx);
+        let tasks = task_queue.remove(idx).unwrap();
         for mut task in tasks {
             // Scheduling the task.
-            task.schedule();
+            task.schedule().unwrap();
             // Checking if the task's start_time is equal to the value of `idx`.
             assert_eq!(task.get_start_time(), idx);
-            // Checking if the task's status is 'Waiting'.
-            assert_eq!(task.get_status(), TaskStatus::Waiting);
+            // Checking if the task's status is 'Runnable'.
+            assert_eq!(task.get_status(), TaskStatus::Runnable);
         }
         // Updating the value of `idx` in each iteration.
         idx = match idx {
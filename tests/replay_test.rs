@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::replay::{self, Replay};
+use cfs::proc::stats::TaskStats;
+use cfs::proc::task::Task;
+use cfs::sched::ftrace;
+use cfs::sched::trace::EventLog;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use common::weighted;
+
+// Runs the given tasks until they are finished, returning their statistics and the ftrace text of the run
+fn simulate(tasks: Vec<Task>) -> (Vec<TaskStats>, String) {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let log = EventLog::new();
+    rq.add_sink(Box::new(log.clone()));
+    rq.push(tasks).unwrap();
+    let stats = common::run(&mut rq, &mut sysclock).stats();
+    (stats, ftrace::text(&log.events()))
+}
+
+#[test]
+fn test_round_trip() {
+    // Replaying the trace of a simulated run gives back the scripts of its tasks
+    let scripts = ["cpu 2; io 3; cpu 1", "cpu 1; sleep 4; cpu 2"];
+    let (stats, text) = simulate(scripts.iter().enumerate().map(|(idx, script)| weighted(idx as u16 + 1, script, 1024)).collect());
+    let replayed = Replay::new().import(&text).unwrap();
+    assert_eq!(replay::tasks_file(&replayed), "3 1024 : cpu 2; io 3; cpu 1 at 0\n3 1024 : cpu 1; sleep 4; cpu 2 at 0\n");
+    assert_eq!((replayed[1].pid, replayed[1].comm.as_str()), (2, "task2"));
+
+    // What the trace shows of every task is what the simulator recorded
+    for (replayed, stats) in replayed.iter().zip(stats.iter()) {
+        assert_eq!(serde_json::to_value(&replayed.observed).unwrap(), serde_json::to_value(stats).unwrap());
+    }
+}
+
+#[test]
+fn test_formats() {
+    // trace-cmd's short format and perf's raw fields, with a nice 5 thread and perf already running when
+    // the trace starts and a thread that never runs
+    let text = "\
+cpus=2
+  <idle>-0     [001]  50.000000: sched_wakeup:         sshd:300 [120] CPU:001
+   bash-100   [001]  50.001000: sched_switch:         bash:100 [125] S ==> kworker/1:0:7 [120]
+   kworker/1:0-7     [001]  50.003000: sched_switch:  kworker/1:0:7 [120] D ==> swapper/1:0 [120]
+  <idle>-0     [001]  50.006400: sched_wakeup:         kworker/1:0:7 [120] CPU:001
+    perf   200 [000]  50.007000: sched:sched_switch: prev_comm=perf prev_pid=200 prev_prio=120 prev_state=R+ ==> next_comm=kworker/1:0 next_pid=7 next_prio=120
+ kworker 7 [000] 50.008000: sched:sched_process_exit: comm=kworker/1:0 pid=7 prio=120
+ kworker 7 [000] 50.008000: sched:sched_switch: prev_comm=kworker/1:0 prev_pid=7 prev_prio=120 prev_state=X ==> next_comm=perf next_pid=200 next_prio=120
+";
+    // A tick of a millisecond
+    let replayed = Replay::new().with_tick(1_000_000).import(text).unwrap();
+    let lines: Vec<String> = replayed.iter().map(|replayed| format!("{} {}", replayed.comm, replay::tasks_file(std::slice::from_ref(replayed)).trim())).collect();
+    assert_eq!(lines, vec!["bash 1 335 : cpu 1 at 0", "perf 7 1024 : cpu 7 at 0", "kworker/1:0 3 1024 : cpu 2; io 3; cpu 1 at 1"]);
+
+    // The kworker waited a tick for perf to be preempted after waking up
+    let kworker = &replayed[2].observed;
+    assert_eq!((kworker.first_run, kworker.completion), (Some(1), Some(8)));
+    assert_eq!((kworker.runtime, kworker.wait, kworker.io_wait, kworker.sleep), (3, 1, 3, 0));
+}
+
+#[test]
+fn test_out_of_order() {
+    // The buffer of a second CPU dumped before the first one's replays as if the trace were sorted
+    let scripts = ["cpu 2; io 3; cpu 1", "cpu 1; sleep 4; cpu 2"];
+    let (_, text) = simulate(scripts.iter().enumerate().map(|(idx, script)| weighted(idx as u16 + 1, script, 1024)).collect());
+    let time = |line: &str| line.split_whitespace().find_map(|word| word.strip_suffix(':')?.parse::<f64>().ok());
+    let (header, body): (Vec<&str>, Vec<&str>) = text.lines().partition(|line| line.starts_with('#'));
+    let split = (body.len() / 2..body.len()).find(|idx| time(body[*idx]) != time(body[idx - 1])).unwrap();
+    let shuffled: Vec<&str> = header.into_iter().chain(body[split..].iter().copied()).chain(body[..split].iter().copied()).collect();
+    let replayed = Replay::new().import(&shuffled.join("\n")).unwrap();
+    assert_eq!(replay::tasks_file(&replayed), replay::tasks_file(&Replay::new().import(&text).unwrap()));
+
+    // A line earlier than the one before it replays in its place rather than underflowing
+    let lines = [
+        "  a-1 [000] 0.000010: sched_switch: prev_comm=a prev_pid=1 prev_prio=120 prev_state=S ==> next_comm=b next_pid=2 next_prio=120",
+        "  b-2 [000] 0.000020: sched_switch: prev_comm=b prev_pid=2 prev_prio=120 prev_state=S ==> next_comm=a next_pid=1 next_prio=120",
+        "  a-1 [001] 0.000005: sched_switch: prev_comm=a prev_pid=1 prev_prio=120 prev_state=R ==> next_comm=b next_pid=2 next_prio=120",
+    ];
+    let replayed = Replay::new().import(&lines.join("\n")).unwrap();
+    let sorted = Replay::new().import(&[lines[2], lines[0], lines[1]].join("\n")).unwrap();
+    assert_eq!(replay::tasks_file(&replayed), replay::tasks_file(&sorted));
+    assert_eq!(replayed[0].observed.runtime, 10);
+}
+
+#[test]
+fn test_errors() {
+    // Errors name the line they are on
+    let text = "# tracer: nop\n  a-1 [000] d..2 0.000001: sched_switch: prev_comm=a prev_pid=x prev_state=S ==> next_comm=b next_pid=2\n";
+    let e = Replay::new().import(text).unwrap_err();
+    assert_eq!(e.line(), 2);
+    assert_eq!(e.to_string(), "invalid trace at line 2: invalid prev_pid `x`");
+
+    // A trace without any switch has nothing to replay
+    let e = Replay::new().import("cpus=1\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid trace: no sched_switch events found");
+
+    // Every thread that ran needs a task id of its own; the last thread switched to never runs
+    let switches = |threads: u32| {
+        (1..=threads)
+            .map(|pid| format!("  t-{} [000] {}.{:06}: sched_switch: t:{} [120] S ==> t:{} [120]\n", pid - 1, pid / 1_000_000, pid % 1_000_000, pid - 1, pid))
+            .collect::<String>()
+    };
+    assert_eq!(Replay::new().import(&switches(65536)).unwrap().len(), 65535);
+    let e = Replay::new().import(&switches(65537)).unwrap_err();
+    assert_eq!(e.to_string(), "invalid trace: more than 65535 threads ran, one for every task id");
+}
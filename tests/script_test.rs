@@ -0,0 +1,97 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::script::{Phase, Script};
+use cfs::proc::task::{BlockReason, Task, TaskStatus, NICE_TO_WEIGHT};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+#[test]
+fn test_parse() {
+    // Parse a script that starts CPU-bound and turns interactive
+    let script: Script = "nice -5; cpu 3; loop { cpu 1; io 2 disk0; sleep 1 }".parse().unwrap();
+
+    // Check the phases and where the loop starts
+    assert_eq!(
+        script.phases(),
+        &[
+            Phase::Nice(-5),
+            Phase::Cpu(3.into()),
+            Phase::Cpu(1.into()),
+            Phase::Io(2.into(), Some(String::from("disk0"))),
+            Phase::Sleep(1.into()),
+        ]
+    );
+    assert_eq!(script.loop_start(), Some(2));
+
+    // The textual form parses back to the same script
+    assert_eq!(script.to_string().parse::<Script>().unwrap(), script);
+
+    // Channel operations round-trip too
+    let script: Script = "send req; recv resp; cpu 1; write 2 disk0; sleep until 40".parse().unwrap();
+    assert_eq!(script.to_string(), "send req; recv resp; cpu 1; write 2 disk0; sleep until 40");
+
+    // Malformed scripts are rejected
+    assert!("cpu".parse::<Script>().is_err());
+    assert!("loop { nice 1 }".parse::<Script>().is_err());
+    assert!("loop { cpu 1 }; cpu 2".parse::<Script>().is_err());
+    assert!("jump 4".parse::<Script>().is_err());
+}
+
+#[test]
+fn test_phases() {
+    // A task that runs 2 ticks, waits 1 tick on I/O, then loops over 1 tick of CPU and 2 ticks of sleep
+    let script: Script = "cpu 2; io 1; loop { cpu 1; sleep 2 }".parse().unwrap();
+    let mut task = Task::scripted(1, 4, script, 0, 1);
+
+    // Run the first CPU phase
+    task.schedule().unwrap();
+    task.run().unwrap();
+    task.cpu_cycle().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Running);
+    task.cpu_cycle().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Blocked { reason: BlockReason::Io });
+
+    // The I/O phase hands the task back to the scheduler
+    task.io_cycle().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Runnable);
+
+    // Enter the loop, which sleeps after every tick on the CPU until a timer wakes the task
+    task.run().unwrap();
+    task.cpu_cycle().unwrap();
+    assert_eq!(task.get_phase(), Some(&Phase::Sleep(2.into())));
+    assert_eq!(task.get_status(), TaskStatus::Sleeping);
+    task.wake().unwrap();
+    assert_eq!(task.get_phase(), Some(&Phase::Cpu(1.into())));
+
+    // The task terminates once its CPU time is used up
+    task.run().unwrap();
+    task.cpu_cycle().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Zombie);
+}
+
+#[test]
+fn test_nice_and_fork() {
+    // A task that renices itself and forks a child after its first burst
+    let script: Script = "nice 5; cpu 1; fork { cpu 2 }; cpu 1".parse().unwrap();
+    let mut task = Task::scripted(1, 10, script, 0, 1);
+
+    // The nice change applies before the first CPU phase
+    assert_eq!(task.get_nice(), 5);
+    assert_eq!(task.weight(), NICE_TO_WEIGHT[25]);
+
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.insert(task).unwrap();
+
+    // Run the parent's first burst, which forks the child
+    task = *rq.pop();
+    task.cpu_cycle().unwrap();
+    rq.insert(task).unwrap();
+
+    // Both the parent and the child are runnable, the child taking the next free id
+    let mut ids = vec![rq.pop().get_id(), rq.pop().get_id()];
+    ids.sort();
+    assert_eq!(ids, vec![1, 2]);
+    assert!(rq.is_empty());
+}
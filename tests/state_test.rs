@@ -0,0 +1,82 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::task::{BlockReason, TaskStatus, TransitionError};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use common::task;
+
+#[test]
+fn test_invalid_transitions() {
+    // A new task cannot run before it is scheduled
+    let mut task = task(1, "cpu 1");
+    let err = task.run().unwrap_err();
+    assert_eq!(err, TransitionError { id: 1, from: TaskStatus::New, to: TaskStatus::Running });
+    assert_eq!(err.to_string(), "task 1 cannot go from new to running");
+
+    // Only a running task runs CPU cycles, and only a task blocked on I/O runs I/O cycles
+    task.schedule().unwrap();
+    assert!(task.cpu_cycle().is_err());
+    assert!(task.io_cycle().is_err());
+    assert!(task.wake().is_err());
+
+    // A finished task is a zombie that can only be reaped, after which it is dead for good
+    task.run().unwrap();
+    task.cpu_cycle().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Zombie);
+    assert!(task.schedule().is_err());
+    assert!(task.block(BlockReason::Mutex).is_err());
+    task.reap().unwrap();
+    assert_eq!(task.get_status(), TaskStatus::Dead);
+    assert!(task.reap().is_err());
+    assert!(task.resume().is_err());
+}
+
+#[test]
+fn test_stop_and_continue() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.push(vec![task(1, "cpu 2"), task(2, "cpu 2")]).unwrap();
+
+    // A stopped task leaves the run queue and cannot be stopped twice or run
+    assert!(rq.stop(1).unwrap());
+    assert!(!rq.stop(1).unwrap());
+    assert_eq!(rq.parked(), vec![(1, String::from("a continue"))]);
+    let mut curr = rq.pop();
+    assert_eq!(curr.get_id(), 2);
+    curr.cpu_cycle().unwrap();
+    rq.insert(*curr).unwrap();
+
+    // Continuing it puts it back on the run queue
+    assert!(rq.cont(1).unwrap());
+    assert!(!rq.cont(1).unwrap());
+    assert!(rq.parked().is_empty());
+    let mut ids = vec![];
+    while !rq.is_empty() {
+        let mut curr = rq.pop();
+        ids.push(curr.get_id());
+        curr.cpu_cycle().unwrap();
+        rq.insert(*curr).unwrap();
+    }
+    assert_eq!(ids.iter().filter(|id| **id == 1).count(), 2);
+
+    // Tasks leave the run queue as zombies to be reaped
+    for mut task in rq.take_exited() {
+        assert_eq!(task.get_status(), TaskStatus::Zombie);
+        task.reap().unwrap();
+    }
+}
+
+#[test]
+fn test_stop_waiting_task() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // A task waiting for I/O is not runnable, so it cannot be stopped
+    rq.push(vec![task(1, "io 3; cpu 1")]).unwrap();
+    let err = rq.stop(1).unwrap_err();
+    assert_eq!(err.from, TaskStatus::Blocked { reason: BlockReason::Io });
+    assert_eq!(err.to_string(), "task 1 cannot go from blocked on I/O to stopped");
+}
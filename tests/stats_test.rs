@@ -0,0 +1,84 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::stats::TaskStats;
+use cfs::proc::task::TaskChar;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, scheduler::Scheduler};
+use common::{run, task};
+
+// Checks every tick of a task's lifetime is spent running, waiting, waiting for I/O or sleeping
+fn check_lifetime(stats: &TaskStats) {
+    let accounted = stats.runtime + stats.wait + stats.io_wait + stats.sleep;
+    assert_eq!(Some(accounted as u128), stats.turnaround(), "task {}", stats.id);
+}
+
+#[test]
+fn test_blocking() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Task 1 runs at 0 and 4 around a sleep, task 2 at 1 and 5 around an I/O
+    rq.push(vec![task(1, "cpu 1; sleep 3; cpu 1"), task(2, "cpu 1; io 3; cpu 1")]).unwrap();
+    let stats = run(&mut rq, &mut sysclock).stats();
+
+    // Both block for the three ticks between their bursts, task 1 sleeping and task 2 waiting for
+    // its I/O, and task 2 first waits for task 1
+    let (sleeper, reader) = (&stats[0], &stats[1]);
+    assert_eq!((sleeper.first_run, sleeper.completion), (Some(0), Some(5)));
+    assert_eq!((sleeper.runtime, sleeper.wait, sleeper.io_wait, sleeper.sleep), (2, 0, 0, 3));
+    assert_eq!((reader.first_run, reader.completion), (Some(1), Some(6)));
+    assert_eq!((reader.runtime, reader.wait, reader.io_wait, reader.sleep, reader.max_wait), (2, 1, 3, 0, 1));
+    assert_eq!(reader.blocked(), 3);
+
+    // Blocking and exiting are voluntary switches
+    for stats in stats.iter() {
+        assert_eq!((stats.voluntary_switches, stats.involuntary_switches, stats.migrations), (2, 0, 0));
+        check_lifetime(stats);
+    }
+}
+
+#[test]
+fn test_preemption() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Two CPU-bound tasks of the same weight take turns on the CPU
+    rq.push(vec![task(1, "cpu 4"), task(2, "cpu 4")]).unwrap();
+    let stats = run(&mut rq, &mut sysclock).stats();
+
+    // Every time one is preempted for the other counts as an involuntary switch, and the last
+    // one to finish completes once the CPU has been busy for all 8 ticks
+    let involuntary: u64 = stats.iter().map(|stats| stats.involuntary_switches).sum();
+    assert!(involuntary > 0);
+    assert_eq!(stats.iter().filter_map(|stats| stats.completion).max(), Some(8));
+    for stats in stats.iter() {
+        assert_eq!((stats.runtime, stats.voluntary_switches), (4, 1));
+        check_lifetime(stats);
+    }
+}
+
+#[test]
+fn test_report() {
+    // Task 2 waits for task 1 to terminate before it starts
+    let mut scheduler = Scheduler::new();
+    let tasks = vec![
+        TaskChar::new(1, 3, 3, 0, 1),
+        TaskChar::new(2, 2, 2, 0, 1).with_deps(vec![1]),
+    ];
+    let report = scheduler.run(tasks).unwrap();
+
+    // The time task 2 spends waiting for its dependency counts as sleep
+    let first = report.task(1).unwrap();
+    let second = report.task(2).unwrap();
+    assert_eq!(first.completion, Some(3));
+    assert_eq!(second.sleep as u128, second.first_run.unwrap() - second.arrival);
+    for stats in report.tasks.iter() {
+        check_lifetime(stats);
+    }
+
+    // The table has a header and a line per task
+    assert_eq!(report.task_table().lines().count(), 3);
+}
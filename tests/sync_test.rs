@@ -0,0 +1,137 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::error::Error;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::sync::table::UnlockError;
+use common::{run, task};
+
+#[test]
+fn test_mutex_fifo() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Task 1 takes the mutex first, tasks 2 and 3 queue behind it in that order
+    rq.push(vec![
+        task(1, "lock m; cpu 3; unlock m"),
+        task(2, "cpu 1; lock m; cpu 2; unlock m"),
+        task(3, "cpu 2; lock m; cpu 1; unlock m"),
+    ]).unwrap();
+    assert_eq!(rq.sync().owner("m"), Some(1));
+
+    // Once task 1 releases the mutex, it goes to task 2 and then task 3 in the order they blocked
+    let ran = run(&mut rq, &mut sysclock).ids();
+    assert!(ran.ends_with(&[1, 2, 2, 3]));
+    assert_eq!(ran.iter().filter(|id| **id == 3).count(), 3);
+    assert_eq!(rq.sync().owner("m"), None);
+    assert!(rq.parked().is_empty());
+}
+
+#[test]
+fn test_unlock_not_held() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Unlocking a mutex held by another task, or by no task, is an error rather than ignored
+    rq.push(vec![task(1, "lock m; cpu 3; unlock m")]).unwrap();
+    let err = rq.push(vec![task(2, "unlock m; cpu 1")]).unwrap_err();
+    assert_eq!(err, Error::Unlock(UnlockError { id: 2, mutex: String::from("m") }));
+    assert_eq!(err.to_string(), "task 2 cannot unlock mutex m it does not hold");
+    assert_eq!(rq.sync().owner("m"), Some(1));
+}
+
+#[test]
+fn test_semaphore() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.declare_semaphore("pool", 2);
+
+    // Only two of the three tasks get into the pool at once
+    rq.push(vec![
+        task(1, "down pool; cpu 2; up pool"),
+        task(2, "down pool; cpu 2; up pool"),
+        task(3, "down pool; cpu 2; up pool"),
+    ]).unwrap();
+    assert_eq!(rq.sync().count("pool"), 0);
+    assert_eq!(rq.parked(), vec![(3, String::from("semaphore pool"))]);
+
+    // Every task gets its turn and the count is restored
+    let ran = run(&mut rq, &mut sysclock).ids();
+    assert_eq!(ran.len(), 6);
+    assert_eq!(rq.sync().count("pool"), 2);
+}
+
+#[test]
+fn test_wait_queue() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Task 1 waits on the queue releasing the mutex, task 2 signals it while holding the mutex
+    rq.push(vec![
+        task(1, "lock m; wait c m; cpu 2; unlock m"),
+        task(2, "cpu 2; lock m; signal c; unlock m; cpu 1"),
+    ]).unwrap();
+    assert_eq!(rq.sync().owner("m"), None);
+    assert_eq!(rq.parked(), vec![(1, String::from("wait queue c"))]);
+
+    // Task 1 only runs once it is signalled and has reacquired the mutex
+    let ran = run(&mut rq, &mut sysclock).ids();
+    assert_eq!(&ran[..2], &[2, 2]);
+    assert_eq!(ran.iter().filter(|id| **id == 1).count(), 2);
+    assert!(rq.parked().is_empty());
+}
+
+#[test]
+fn test_deadlock() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // Two tasks taking two mutexes in opposite orders
+    rq.push(vec![
+        task(1, "lock a; cpu 1; lock b; cpu 1"),
+        task(2, "lock b; cpu 1; lock a; cpu 1"),
+    ]).unwrap();
+
+    // Both end up blocked forever once the run queue drains
+    run(&mut rq, &mut sysclock);
+    assert_eq!(
+        rq.parked(),
+        vec![(1, String::from("mutex b")), (2, String::from("mutex a"))]
+    );
+    assert!(rq.sync().owner("a") == Some(1) && rq.sync().owner("b") == Some(2));
+}
+
+#[test]
+fn test_channel() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // A server waiting for requests from a client, which waits for the replies
+    rq.push(vec![
+        task(1, "recv req; cpu 2; send resp; recv req; cpu 2; send resp"),
+        task(2, "cpu 1; send req; recv resp; cpu 1; send req; recv resp"),
+    ]).unwrap();
+    assert_eq!(rq.parked(), vec![(1, String::from("channel req"))]);
+
+    // The server only runs once a request wakes it, and the client only once the reply wakes it
+    let ran = run(&mut rq, &mut sysclock).ids();
+    assert_eq!(ran, vec![2, 1, 1, 2, 1, 1]);
+    assert!(rq.parked().is_empty());
+
+    // Messages sent with nobody receiving are buffered
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.push(vec![task(3, "send log; send log; cpu 1")]).unwrap();
+    assert_eq!(rq.sync().pending_messages("log"), 2);
+    rq.push(vec![task(4, "recv log; cpu 1")]).unwrap();
+    assert_eq!(rq.sync().pending_messages("log"), 1);
+    assert!(rq.parked().is_empty());
+
+    // A woken receiver behind the sender on vruntime preempts it on the next tick
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.push(vec![task(5, "recv m; cpu 2"), task(6, "cpu 4; send m; cpu 3")]).unwrap();
+    let ran = run(&mut rq, &mut sysclock).ids();
+    assert_eq!(ran, vec![6, 6, 6, 6, 5, 5, 6, 6, 6]);
+}
@@ -3,7 +3,7 @@
 extern crate completely_fair_scheduler as cfs;
 extern crate raw_pointer as rptr;
 
-use cfs::proc::task::{Task, TaskStatus};
+use cfs::proc::task::{BlockReason, Task, TaskStatus};
 use rptr::Pointer;
 
 #[test]
@@ -23,15 +23,13 @@ fn test_execute() {
     tasks.push(Pointer::new(&mut other_task));
 
     // Keeps running tasks while there is any task in the task queue
-    while tasks.len() > 0 {
-        let y = tasks.len();
-
+    while !tasks.is_empty() {
         // Vector to store the indices of tasks that need to be removed
         let mut to_remove: Vec<usize> = Vec::new();
 
         // Loop through all tasks
-        for i in 0..y {
-            let mut task = tasks[i];
+        for (i, task) in tasks.iter().enumerate() {
+            let mut task = *task;
 
             println!("Performing sequence for task {:?}", task.get_id());
 
@@ -40,25 +38,27 @@ fn test_execute() {
                 TaskStatus::Running => {
                     match task.get_id() {
                         1 => {
-                            x = x + 1;
+                            x += 1;
                             println!("Executing task 1");
                         }
                         id => println!("Executing task {:?}", id),
                     };
-                    task.cpu_cycle();
+                    task.cpu_cycle().unwrap();
                 }
-                TaskStatus::Waiting => {
-                    task.run();
+                TaskStatus::Runnable => {
+                    task.run().unwrap();
                 }
-                TaskStatus::Idle => {
-                    task.io_cycle();
+                TaskStatus::Blocked { reason: BlockReason::Io } => {
+                    task.io_cycle().unwrap();
                 }
                 TaskStatus::New => {
-                    task.schedule();
+                    task.schedule().unwrap();
                 }
-                TaskStatus::Terminated => {
+                TaskStatus::Zombie => {
                     to_remove.push(i);
                 }
+                // Tasks without dependencies are never blocked, and tasks without sleep phases never sleep
+                _ => {}
             };
         }
 
@@ -82,13 +82,13 @@ fn test_preempt() {
     let mut my_task = Task::new(1, 8, 2, 5, 1, 1);
     let mut other_task = Task::new(2, 10, 7, 3, 1, 1);
 
-    my_task.schedule();
-    other_task.schedule();
+    my_task.schedule().unwrap();
+    other_task.schedule().unwrap();
 
     tasks.push(Pointer::new(&mut my_task));
     tasks.push(Pointer::new(&mut other_task));
 
-    while tasks.len() > 0 {
+    while !tasks.is_empty() {
         let y = tasks.len();
         let mut to_remove: Vec<usize> = Vec::new();
 
@@ -97,7 +97,7 @@ fn test_preempt() {
             for i in 1..y {
                 if tasks[i].get_runtime() < tasks[min_key].get_runtime() {
                     if tasks[min_key].get_status() == TaskStatus::Running {
-                        tasks[min_key].schedule(); // preempt
+                        tasks[min_key].schedule().unwrap(); // preempt
                     }
 
                     min_key = i;
@@ -108,29 +108,29 @@ fn test_preempt() {
         };
 
         // If the task's status is "Waiting", run the task.
-        if task.get_status() == TaskStatus::Waiting {
-            task.run();
+        if task.get_status() == TaskStatus::Runnable {
+            task.run().unwrap();
         }
 
         // If the task's status is "Running", execute the task's code.
         if task.get_status() == TaskStatus::Running {
             match task.get_id() {
                 1 => {
-                    x = x + 1;
+                    x += 1;
                 }
                 id => println!("Executing task {:?}", id),
             };
-            task.cpu_cycle();
+            task.cpu_cycle().unwrap();
         }
 
         // Check all tasks for termination or idle status.
-        for i in 0..y {
-            let curr = tasks[i];
-            if curr.get_status() == TaskStatus::Terminated {
+        for (i, curr) in tasks.iter().enumerate() {
+            let mut curr = *curr;
+            if curr.get_status() == TaskStatus::Zombie {
                 to_remove.push(i);
             }
-            if curr.get_status() == TaskStatus::Idle {
-                curr.io_cycle();
+            if curr.get_status() == (TaskStatus::Blocked { reason: BlockReason::Io }) {
+                curr.io_cycle().unwrap();
             }
         }
 
@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::task::{Task, TaskStatus};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, timer::TimerQueue};
+use common::{run, task, Run};
+
+#[test]
+fn test_slack() {
+    // Without slack, timers fire as soon as they expire
+    let mut timers = TimerQueue::new();
+    timers.arm(task(1, "sleep 5"), 5);
+    timers.arm(task(2, "sleep 7"), 7);
+    assert!(timers.expire(4).is_empty());
+    assert_eq!(timers.expire(5).len(), 1);
+    assert_eq!(timers.next_expiry(), Some(7));
+    assert_eq!(timers.expire(7).len(), 1);
+    assert!(timers.is_empty());
+
+    // With slack, the first timer waits and the second one fires with it
+    timers.set_slack(3);
+    timers.arm(task(1, "sleep 5"), 5);
+    timers.arm(task(2, "sleep 7"), 7);
+    for now in 5..8 {
+        assert!(timers.expire(now).is_empty());
+    }
+    let fired: Vec<u16> = timers.expire(8).iter().map(Task::get_id).collect();
+    assert_eq!(fired, vec![1, 2]);
+}
+
+#[test]
+fn test_sleep_and_io() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // One task sleeps and the other waits for I/O for as long between two ticks on the CPU
+    rq.push(vec![task(1, "cpu 1; sleep 3; cpu 1"), task(2, "cpu 1; io 3; cpu 1")]).unwrap();
+    let Run { ran, exited, .. } = run(&mut rq, &mut sysclock);
+
+    // Both are off the CPU for the 3 ticks after the one they left it on
+    assert_eq!(ran, vec![(0, 1), (1, 2), (4, 1), (5, 2)]);
+
+    // Sleeping and waiting for I/O are accounted separately
+    let sleeper = exited.iter().find(|task| task.get_id() == 1).unwrap();
+    let reader = exited.iter().find(|task| task.get_id() == 2).unwrap();
+    assert_eq!((sleeper.get_sleep_time(), sleeper.get_io_wait()), (3, 0));
+    assert_eq!((reader.get_sleep_time(), reader.get_io_wait()), (0, 3));
+}
+
+#[test]
+fn test_sleep_until() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    // A task sleeping until an absolute tick, then until a tick that has already passed
+    let mut sleeper = task(1, "sleep until 10; cpu 1; sleep until 5; cpu 1");
+    rq.insert(sleeper).unwrap();
+    let Run { ran, mut exited, .. } = run(&mut rq, &mut sysclock);
+
+    // It runs at the tick it asked for, and on the next tick once the tick has passed
+    assert_eq!(ran, vec![(10, 1), (11, 1)]);
+    sleeper = exited.pop().unwrap();
+    assert_eq!(sleeper.get_status(), TaskStatus::Zombie);
+    assert_eq!(sleeper.get_sleep_time(), 10);
+}
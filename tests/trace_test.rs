@@ -0,0 +1,94 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+mod common;
+
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::sched::trace::{Event, EventKind, EventLog, TaskRef};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, scheduler::Scheduler};
+use common::{run, task};
+
+#[test]
+fn test_events() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let log = EventLog::new();
+    rq.add_sink(Box::new(log.clone()));
+
+    // The task runs for 2 ticks, sleeps and runs once more
+    rq.push(vec![task(1, "cpu 2; sleep 2; cpu 1")]).unwrap();
+    run(&mut rq, &mut sysclock);
+
+    let me = TaskRef { id: 1, nice: 0 };
+    let expected = vec![
+        (0, EventKind::Born { task: me, parent: None }),
+        (0, EventKind::Enqueued { task: me, vruntime: 0 }),
+        (0, EventKind::Picked { task: me, cpu: 0 }),
+        (0, EventKind::Preempted { task: me }),
+        (0, EventKind::Enqueued { task: me, vruntime: 0 }),
+        (0, EventKind::Tick { runnable: 1, min_vruntime: 0 }),
+        (1, EventKind::Picked { task: me, cpu: 0 }),
+        (1, EventKind::Blocked { task: me, state: TaskStatus::Sleeping }),
+        (1, EventKind::Tick { runnable: 0, min_vruntime: 0 }),
+        (2, EventKind::Tick { runnable: 0, min_vruntime: 0 }),
+        (3, EventKind::Woken { task: me }),
+        (3, EventKind::Enqueued { task: me, vruntime: 3 }),
+        (3, EventKind::Tick { runnable: 1, min_vruntime: 3 }),
+        (4, EventKind::Picked { task: me, cpu: 0 }),
+        (4, EventKind::Terminated { task: me }),
+        (4, EventKind::Tick { runnable: 0, min_vruntime: 3 }),
+    ];
+    let expected: Vec<Event> = expected.into_iter().map(|(time, kind)| Event { time, kind }).collect();
+    assert_eq!(log.events(), expected);
+
+    // Events serialise flat, tagged with their kind
+    let json = serde_json::to_value(&expected[7]).unwrap();
+    assert_eq!(json, serde_json::json!({ "time": 1, "kind": "blocked", "task": { "id": 1, "nice": 0 }, "state": "sleeping" }));
+
+    // Taking the sinks back leaves the run queue without any
+    assert_eq!(rq.take_sinks().len(), 1);
+    assert!(rq.take_sinks().is_empty());
+}
+
+#[test]
+fn test_fork() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let log = EventLog::new();
+    rq.add_sink(Box::new(log.clone()));
+
+    // A forked child is born with its parent's id
+    rq.push(vec![task(1, "cpu 1; fork { cpu 1 }; cpu 1")]).unwrap();
+    run(&mut rq, &mut sysclock);
+    let births: Vec<(u16, Option<u16>)> = log
+        .events()
+        .iter()
+        .filter_map(|event| match event.kind {
+            EventKind::Born { task, parent } => Some((task.id, parent)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(births, vec![(1, None), (2, Some(1))]);
+}
+
+#[test]
+fn test_scheduler_sinks() {
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let log = EventLog::new();
+    scheduler.sink(Box::new(log.clone()));
+
+    // Every tick a task runs is a pick, and every task is born and terminates once
+    let tasks = vec![TaskChar::new(1, 3, 3, 0, 1), TaskChar::new(2, 2, 2, 0, 1)];
+    scheduler.run(tasks.clone()).unwrap();
+    let count = |events: &[Event], matches: fn(&EventKind) -> bool| events.iter().filter(|event| matches(&event.kind)).count();
+    let events = log.events();
+    assert_eq!(count(&events, |kind| matches!(kind, EventKind::Picked { .. })), 5);
+    assert_eq!(count(&events, |kind| matches!(kind, EventKind::Born { .. })), 2);
+    assert_eq!(count(&events, |kind| matches!(kind, EventKind::Terminated { .. })), 2);
+
+    // The sinks are handed back after the run and keep receiving the events of later runs
+    scheduler.run(tasks).unwrap();
+    assert_eq!(log.events().len(), events.len() * 2);
+}
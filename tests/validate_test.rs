@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::validate::{self, Diagnostic, Severity};
+use cfs::proc::workload::{self, Format, Workload};
+
+// Returns the diagnostics of a workload in the line-based format, as printed
+fn check(text: &str) -> Vec<String> {
+    validate::validate(&workload::legacy(text).unwrap()).iter().map(Diagnostic::to_string).collect()
+}
+
+#[test]
+fn test_tasks() {
+    // A valid workload has no diagnostics
+    assert!(check("device disk 1\n100 5 3 2\n50 1 : cpu 5; io 2 disk after 1\n").is_empty());
+
+    // A zero burst would loop forever on I/O and a zero weight divide by zero in vruntime
+    assert_eq!(
+        check("10 0 3 1\n10 2 2 0\n"),
+        vec![
+            "error at line 1: task 1: its CPU bursts are 0 ticks long, so it loops forever without running and never terminates",
+            "error at line 2: task 2: weight must be positive",
+        ]
+    );
+    assert_eq!(check("10 1 : loop { sleep 5 }\n"), vec!["error at line 1: task 1: it loops forever without running on the CPU, so it never terminates"]);
+
+    // Bursts longer than cpu_time are only warnings
+    assert_eq!(
+        check("10 20 2 1\n5 1 : cpu 3; io 2; cpu 4\n0 2 2 1\n"),
+        vec![
+            "warning at line 1: task 1: the CPU burst `20` is longer than the cpu_time of 10, so the task ends before it does",
+            "warning at line 2: task 2: the script runs 7 ticks on the CPU, more than the cpu_time of 5, so it is cut short",
+            "warning at line 3: task 3: cpu_time is 0, but the task still runs for a tick",
+        ]
+    );
+
+    // I/O to a device that is not declared is an error
+    assert_eq!(check("device disk 1\n50 1 : cpu 5; io 2 dsk\n"), vec!["error at line 2: task 1: device `dsk` is not declared"]);
+
+    // Children are checked like their parents
+    assert_eq!(check("100 1 : fork { loop { io 2 } }; cpu 5\n").len(), 1);
+}
+
+#[test]
+fn test_workload() {
+    // Dependencies must name tasks other than the task itself
+    assert_eq!(
+        check("10 2 2 1 after 1\n10 2 2 1 after 7\n"),
+        vec!["error at line 1: task 1: depends on itself", "error at line 2: task 2: depends on task 7, which does not exist"]
+    );
+
+    // Groups must be declared, and names and declarations unique
+    let text = "[[group]]\nname = \"web\"\n\n[[task]]\nname = \"a\"\ncpu_time = 10\ncpu_burst = 2\ngroup = \"db\"\n\n[[task]]\nname = \"a\"\ncpu_time = 10\ncpu_burst = 2\n";
+    let mut workload = Workload::parse(text, Format::Toml).unwrap();
+    workload.groups.push(String::from("web"));
+    workload.tasks.push(workload.tasks[0].clone());
+    let diagnostics = validate::validate(&workload);
+    let messages: Vec<String> = diagnostics.iter().map(Diagnostic::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "error: group `web` is declared twice",
+            "error at line 10: task 2: name `a` is already used by task 1",
+            "error at line 4: task 1: name `a` is already used by task 1",
+            "error at line 4: task 1: group `db` is not declared",
+            "error at line 4: task 1: group `db` is not declared",
+        ]
+    );
+    assert!(validate::has_errors(&diagnostics));
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].task, Some(2));
+}
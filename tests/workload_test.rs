@@ -0,0 +1,182 @@
+#![cfg(test)]
+
+extern crate completely_fair_scheduler as cfs;
+
+use cfs::proc::arrival::Arrival;
+use cfs::proc::dist::Dist;
+use cfs::proc::workload::{self, Format, Policy, Workload, IDLE_WEIGHT};
+use cfs::sched::scheduler::Scheduler;
+use std::path::Path;
+
+const WORKLOAD: &str = r#"
+timer_slack = 2
+
+[[device]]
+name = "disk"
+depth = 1
+scheduler = "deadline"
+
+[[group]]
+name = "web"
+
+[[task]]
+name = "db"
+cpu_time = 30
+nice = 5
+cpu_burst = "exp(4)"
+io_burst = 3
+group = "web"
+affinity = [0]
+
+[[task]]
+name = "worker"
+cpu_time = 20
+policy = "idle"
+script = "cpu 5; io 2 disk; cpu 5"
+after = ["db"]
+arrival = "3 every 10 count 2"
+"#;
+
+#[test]
+fn test_toml() {
+    let workload = Workload::parse(WORKLOAD, Format::Toml).unwrap();
+    assert_eq!(workload.timer_slack, Some(2));
+    assert_eq!(workload.groups, vec![String::from("web")]);
+    assert_eq!(workload.devices[0].depth, Some(1));
+    assert_eq!(workload.devices[0].scheduler.as_deref(), Some("deadline"));
+
+    // Tasks are numbered in order and keep their names, nice values turning into weights
+    let db = &workload.tasks[0];
+    assert_eq!((db.task.get_id(), db.task.get_weight(), db.line), (1, 335, 12));
+    assert_eq!(db.task.get_cpu_burst_length(), &Dist::Exponential(4.0));
+    assert_eq!(db.group.as_deref(), Some("web"));
+    assert_eq!(workload.name(1), Some("db"));
+
+    // Idle tasks run at the idle weight, and dependencies may name tasks
+    let worker = &workload.tasks[1];
+    assert_eq!((worker.policy, worker.task.get_weight()), (Policy::Idle, IDLE_WEIGHT));
+    assert_eq!(worker.task.get_deps(), &[1]);
+    assert_eq!(worker.task.get_arrival(), &Arrival::Periodic { start: 3, period: 10, count: 2 });
+
+    // The workload runs with its devices
+    let mut scheduler = Scheduler::new();
+    scheduler.clear_sinks();
+    let tasks = workload.apply(&mut scheduler);
+    let report = scheduler.run(tasks).unwrap();
+    assert_eq!(report.tasks.len(), 3);
+    assert!(report.devices.iter().any(|device| device.name == "disk"));
+}
+
+#[test]
+fn test_errors() {
+    // Errors point at the line of the offending field, or of the task breaking a rule
+    let err = |text: &str, format: Format| Workload::parse(text, format).unwrap_err();
+    let bad = WORKLOAD.replace("nice = 5", "nice = \"x\"");
+    assert_eq!(err(&bad, Format::Toml).line(), 15);
+    let bad = WORKLOAD.replace("io_burst = 3", "io_burst = \"exp(-1)\"");
+    assert_eq!(err(&bad, Format::Toml).to_string(), "invalid workload at line 17: invalid distribution: exponential mean -1 must be positive");
+    let bad = WORKLOAD.replace("nice = 5", "nicety = 5");
+    assert!(err(&bad, Format::Toml).to_string().starts_with("invalid workload at line 15: unknown field `nicety`"));
+    let bad = WORKLOAD.replace("nice = 5", "nice = 5\nweight = 2");
+    assert_eq!(err(&bad, Format::Toml).to_string(), "invalid workload at line 12: task 1 (`db`): `nice` cannot be given along with a weight");
+    let bad = WORKLOAD.replace("affinity = [0]", "affinity = [1]");
+    assert!(err(&bad, Format::Toml).to_string().contains("`affinity` must include CPU 0"));
+    let bad = WORKLOAD.replace("after = [\"db\"]", "after = [\"cache\"]");
+    assert!(err(&bad, Format::Toml).to_string().contains("`after` names task `cache`, which does not exist"));
+
+    // JSON errors carry the line too
+    let json = "{\n  \"tasks\": [\n    { \"cpu_time\": 10, \"cpu_burst\": \"uniform(3)\" }\n  ]\n}";
+    let e = err(json, Format::Json);
+    assert_eq!(e.line(), 3);
+    assert!(e.to_string().contains("invalid distribution"));
+}
+
+#[test]
+fn test_json() {
+    let json = r#"{
+        "devices": [{ "name": "ssd", "service": 2 }],
+        "tasks": [
+            { "name": "a", "cpu_time": 10, "weight": 2, "cpu_burst": 5, "io_burst": "uniform(1,3)" },
+            { "cpu_time": 10, "script": "cpu 2; io 1 ssd", "after": [1], "policy": "batch" }
+        ]
+    }"#;
+    let workload = Workload::parse(json, Format::Json).unwrap();
+    assert_eq!(workload.devices[0].service, Some(Dist::Constant(2)));
+    assert_eq!(workload.tasks[0].task.get_weight(), 2);
+    assert_eq!(workload.tasks[1].task.get_weight(), 1024);
+    assert_eq!(workload.tasks[1].policy, Policy::Batch);
+
+    // Tasks keep the lines they start on, even with brackets and braces inside strings
+    assert_eq!(workload.tasks.iter().map(|task| task.line).collect::<Vec<usize>>(), vec![4, 5]);
+    let bad = json.replace("\"name\": \"a\"", "\"name\": \"a]}[\",\n \"nice\": 1");
+    assert_eq!(Workload::parse(&bad, Format::Json).unwrap_err().to_string(), "invalid workload at line 4: task 1 (`a]}[`): `nice` cannot be given along with a weight");
+    assert_eq!(Format::from_path(Path::new("w.json")), Format::Json);
+    assert_eq!(Format::from_path(Path::new("tasks.txt")), Format::Legacy);
+}
+
+#[test]
+fn test_legacy() {
+    // Every kind of line of the tasks file is read, comments and blank lines skipped
+    let text = "# a comment\nsemaphore pool 2\ntimer_slack 3\ndevice disk 1 exp(5) scheduler bfq\n\n100 5 3 2\n50 1 : cpu 5; io 2 disk at 10 after 1\n";
+    let workload = workload::legacy(text).unwrap();
+    assert_eq!(workload.semaphores, vec![(String::from("pool"), 2)]);
+    assert_eq!(workload.timer_slack, Some(3));
+    assert_eq!(workload.devices[0].scheduler.as_deref(), Some("bfq"));
+    assert_eq!(workload.tasks.len(), 2);
+    assert_eq!((workload.tasks[1].task.get_id(), workload.tasks[1].line), (2, 7));
+    assert_eq!(workload.tasks[1].task.get_deps(), &[1]);
+    assert_eq!(workload.tasks[1].task.get_arrival(), &Arrival::At(10));
+
+    // Malformed lines are errors rather than skipped or panicking
+    let e = workload::legacy("100 5 3 2\n100 5 x 2\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid workload at line 2: invalid distribution: `constant` expects numbers, found `x`");
+    let e = workload::legacy("100 5 3\n").unwrap_err();
+    assert!(e.to_string().starts_with("invalid workload at line 1: expected `cpu_time cpu_burst io_burst weight`"));
+    let e = workload::legacy("100 5 3 heavy\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid workload at line 1: weight must be a number, found `heavy`");
+}
+
+#[test]
+fn test_write() {
+    // Structured formats keep everything, with nice values written as weights
+    let workload = Workload::parse(WORKLOAD, Format::Toml).unwrap();
+    for format in [Format::Toml, Format::Json] {
+        let read = Workload::parse(&workload.write(format).unwrap(), format).unwrap();
+        assert_eq!(read.write(Format::Toml).unwrap(), workload.write(Format::Toml).unwrap());
+        assert_eq!((read.tasks[1].policy, read.name(2)), (Policy::Idle, Some("worker")));
+    }
+    assert!(workload.write(Format::Toml).unwrap().contains("weight = 335\n"));
+
+    // The tasks file has no names, policies or groups, but runs the same tasks
+    let text = workload.write(Format::Legacy).unwrap();
+    assert!(text.contains("\n30 exp(4) 3 335\n"));
+    let read = workload::legacy(&text).unwrap();
+    assert_eq!(read.tasks[1].task.get_weight(), IDLE_WEIGHT);
+    assert_eq!(read.tasks[1].task.get_deps(), &[1]);
+    assert_eq!(read.devices[0].scheduler.as_deref(), Some("deadline"));
+}
+
+#[test]
+fn test_policy() {
+    // Idle tasks run at the idle weight, and tasks leaving the idle policy at the weight of nice 0
+    let mut workload = Workload::parse(WORKLOAD, Format::Toml).unwrap();
+    workload.set_policy(Policy::Batch);
+    assert_eq!((workload.tasks[0].task.get_weight(), workload.tasks[1].task.get_weight()), (335, 1024));
+    workload.set_policy(Policy::Idle);
+    assert!(workload.tasks.iter().all(|task| task.policy == Policy::Idle && task.task.get_weight() == IDLE_WEIGHT));
+}
+
+#[test]
+fn test_legacy_histograms() {
+    // Colons inside histograms do not start a script, in the classic form or inside a script
+    let text = "100 hist(10:3,20:1) 2 1024\n50 335 : cpu hist(1:1,2:1); io 3\n";
+    let workload = workload::legacy(text).unwrap();
+    assert_eq!(workload.tasks[0].task.get_cpu_burst_length(), &Dist::Empirical(vec![(10, 3.0), (20, 1.0)]));
+    assert_eq!(workload.tasks[1].task.get_weight(), 335);
+
+    // The tasks file written back reads back the same
+    let written = workload.write(Format::Legacy).unwrap();
+    assert_eq!(written, text);
+    let read = workload::legacy(&written).unwrap();
+    assert_eq!(read.write(Format::Toml).unwrap(), workload.write(Format::Toml).unwrap());
+}